@@ -0,0 +1,179 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use graphql_introspection_parser::{parse, parse_reader};
+use serde_json::{json, Value};
+
+/// Build an introspection response with `type_count` objects, each with a matching input object
+/// and enum, roughly the shape of a large federated schema
+fn large_introspection(type_count: usize) -> String {
+    let named = |kind: &str, name: &str| json!({ "kind": kind, "name": name, "ofType": null });
+    let non_null = |of_type: Value| json!({ "kind": "NON_NULL", "name": null, "ofType": of_type });
+    let list = |of_type: Value| json!({ "kind": "LIST", "name": null, "ofType": of_type });
+
+    let mut types = vec![
+        json!({ "kind": "SCALAR", "name": "String", "description": null }),
+        json!({ "kind": "SCALAR", "name": "ID", "description": null }),
+        json!({ "kind": "SCALAR", "name": "Int", "description": null }),
+    ];
+    let mut query_fields = vec![];
+
+    for i in 0..type_count {
+        let object = format!("Type{}", i);
+        let input = format!("Type{}Input", i);
+        let enum_name = format!("Type{}Status", i);
+        let related = format!("Type{}", (i + 1) % type_count);
+
+        let fields: Vec<Value> = (0..20)
+            .map(|f| {
+                json!({
+                    "name": format!("field{}", f),
+                    "description": format!("Field {} of {}, described in a sentence", f, object),
+                    "args": [{
+                        "name": "first",
+                        "description": null,
+                        "type": named("SCALAR", "Int"),
+                        "defaultValue": "10",
+                    }],
+                    "type": match f % 4 {
+                        0 => non_null(named("SCALAR", "ID")),
+                        1 => named("ENUM", &enum_name),
+                        2 => list(non_null(named("OBJECT", &related))),
+                        _ => named("SCALAR", "String"),
+                    },
+                    "isDeprecated": false,
+                    "deprecationReason": null,
+                })
+            })
+            .collect();
+
+        types.push(json!({
+            "kind": "OBJECT",
+            "name": object,
+            "description": format!("The {} object", object),
+            "fields": fields,
+            "inputFields": null,
+            "interfaces": [],
+            "enumValues": null,
+            "possibleTypes": null,
+        }));
+        let input_fields: Vec<Value> = (0..5)
+            .map(|f| {
+                json!({
+                    "name": format!("input{}", f),
+                    "description": null,
+                    "type": named("SCALAR", "String"),
+                    "defaultValue": null,
+                })
+            })
+            .collect();
+        types.push(json!({
+            "kind": "INPUT_OBJECT",
+            "name": input,
+            "description": null,
+            "fields": null,
+            "inputFields": input_fields,
+            "interfaces": null,
+            "enumValues": null,
+            "possibleTypes": null,
+        }));
+        let enum_values: Vec<Value> = ["ACTIVE", "INACTIVE", "ARCHIVED"]
+            .iter()
+            .map(|value| {
+                json!({
+                    "name": value,
+                    "description": null,
+                    "isDeprecated": false,
+                    "deprecationReason": null,
+                })
+            })
+            .collect();
+        types.push(json!({
+            "kind": "ENUM",
+            "name": enum_name,
+            "description": null,
+            "fields": null,
+            "inputFields": null,
+            "interfaces": null,
+            "enumValues": enum_values,
+            "possibleTypes": null,
+        }));
+
+        query_fields.push(json!({
+            "name": format!("type{}", i),
+            "description": null,
+            "args": [{
+                "name": "input",
+                "description": null,
+                "type": named("INPUT_OBJECT", &input),
+                "defaultValue": null,
+            }],
+            "type": named("OBJECT", &object),
+            "isDeprecated": false,
+            "deprecationReason": null,
+        }));
+    }
+
+    types.push(json!({
+        "kind": "OBJECT",
+        "name": "Query",
+        "description": null,
+        "fields": query_fields,
+        "inputFields": null,
+        "interfaces": [],
+        "enumValues": null,
+        "possibleTypes": null,
+    }));
+
+    json!({
+        "data": {
+            "__schema": {
+                "queryType": { "name": "Query" },
+                "mutationType": null,
+                "subscriptionType": null,
+                "types": types,
+                "directives": [],
+            }
+        }
+    })
+    .to_string()
+}
+
+fn parse_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+
+    for type_count in [100, 1_000, 5_000].iter() {
+        let introspection = large_introspection(*type_count);
+        group.throughput(Throughput::Bytes(introspection.len() as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("str", type_count),
+            &introspection,
+            |b, introspection| b.iter(|| parse::<String>(introspection).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("borrowed", type_count),
+            &introspection,
+            |b, introspection| b.iter(|| parse::<&str>(introspection).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("reader", type_count),
+            &introspection,
+            |b, introspection| b.iter(|| parse_reader(introspection.as_bytes()).unwrap()),
+        );
+        #[cfg(feature = "rayon")]
+        group.bench_with_input(
+            BenchmarkId::new("parallel", type_count),
+            &introspection,
+            |b, introspection| {
+                b.iter(|| {
+                    graphql_introspection_parser::parse_parallel::<String>(introspection).unwrap()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parse_benchmark);
+criterion_main!(benches);
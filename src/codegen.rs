@@ -0,0 +1,787 @@
+use crate::naming::{field_identifier, to_pascal_case, to_snake_case};
+use crate::schema::{
+    fragments, is_list, named_type, operation_parts, OperationKind, Schema, TYPENAME_FIELD,
+};
+use graphql_parser::query::{
+    self, Definition, FragmentDefinition, OperationDefinition, Selection, SelectionSet, Text,
+    TypeCondition, VariableDefinition,
+};
+use graphql_parser::schema::{Type, TypeDefinition};
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt::{self, Write};
+
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    /// Rust types for custom scalars, keyed by the GraphQL scalar name
+    pub scalars: HashMap<String, String>,
+    /// The Rust type used for any custom scalar missing from `scalars`
+    pub default_scalar: String,
+    /// Derives added to every generated struct alongside the serde derive
+    pub derives: Vec<String>,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions {
+            scalars: HashMap::new(),
+            default_scalar: "::serde_json::Value".to_string(),
+            derives: vec!["Debug".to_string(), "Clone".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodegenError {
+    AnonymousOperation,
+    MissingRootType(OperationKind),
+    UnknownType(String),
+    UnknownField {
+        type_name: String,
+        field: String,
+    },
+    UnknownFragment(String),
+    /// Selections with type conditions are deserialized by `__typename`, so it has to be queried
+    MissingTypename(String),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodegenError::AnonymousOperation => {
+                f.write_str("operations need a name to generate a module for them")
+            }
+            CodegenError::MissingRootType(kind) => {
+                write!(f, "the schema has no {:?} root type", kind)
+            }
+            CodegenError::UnknownType(name) => write!(f, "unknown type '{}'", name),
+            CodegenError::UnknownField { type_name, field } => {
+                write!(f, "unknown field '{}' on type '{}'", field, type_name)
+            }
+            CodegenError::UnknownFragment(name) => write!(f, "unknown fragment '{}'", name),
+            CodegenError::MissingTypename(path) => write!(
+                f,
+                "'{}' selects fields on specific types but doesn't query __typename",
+                path
+            ),
+        }
+    }
+}
+
+impl Error for CodegenError {}
+
+/// Generate Rust `Variables` and `ResponseData` types for every operation in `operations`.
+///
+/// Each operation gets its own module named after it. Enums, input objects and custom scalars
+/// are shared at the top level so the modules can be used together.
+pub fn generate<'s, 'q, T, Q>(
+    schema: &Schema<'s, T>,
+    operations: &query::Document<'q, Q>,
+    options: &CodegenOptions,
+) -> Result<String, CodegenError>
+where
+    T: Text<'s>,
+    Q: Text<'q>,
+{
+    let mut generator = Generator {
+        schema,
+        options,
+        fragments: fragments(&operations.definitions),
+        scalars: BTreeSet::new(),
+        enums: BTreeSet::new(),
+        input_objects: BTreeSet::new(),
+    };
+
+    let mut modules = vec![];
+    for definition in &operations.definitions {
+        if let Definition::Operation(operation) = definition {
+            modules.push(generator.operation_module(operation)?);
+        }
+    }
+
+    let mut out = String::from("use serde::{Deserialize, Serialize};\n");
+
+    for scalar in &generator.scalars {
+        let rust_type = options
+            .scalars
+            .get(scalar)
+            .unwrap_or(&options.default_scalar);
+        write!(
+            out,
+            "\npub type {} = {};\n",
+            to_pascal_case(scalar),
+            rust_type
+        )
+        .unwrap();
+    }
+
+    for name in &generator.enums {
+        out.push('\n');
+        out.push_str(&generator.enum_definition(name)?);
+    }
+
+    for input_object in generator.input_object_definitions()? {
+        out.push('\n');
+        out.push_str(&input_object);
+    }
+
+    for module in modules {
+        out.push('\n');
+        out.push_str(&module);
+    }
+
+    Ok(out)
+}
+
+struct Generator<'a, 's, 'q, T: Text<'s>, Q: Text<'q>> {
+    schema: &'a Schema<'s, T>,
+    options: &'a CodegenOptions,
+    fragments: HashMap<&'a str, &'a FragmentDefinition<'q, Q>>,
+    scalars: BTreeSet<String>,
+    enums: BTreeSet<String>,
+    input_objects: BTreeSet<String>,
+}
+
+/// A response key with every selection made on it, merged across fragments
+struct FieldSelection<'a, 'q, Q: Text<'q>> {
+    response_key: &'a str,
+    name: &'a str,
+    selection_sets: Vec<&'a SelectionSet<'q, Q>>,
+}
+
+impl<'a, 's, 'q, T: Text<'s>, Q: Text<'q>> Generator<'a, 's, 'q, T, Q> {
+    fn operation_module(
+        &mut self,
+        operation: &'a OperationDefinition<'q, Q>,
+    ) -> Result<String, CodegenError> {
+        let parts = operation_parts(operation);
+        let name = parts.name.ok_or(CodegenError::AnonymousOperation)?;
+        let root_type = self
+            .schema
+            .root_type_name(parts.kind)
+            .ok_or(CodegenError::MissingRootType(parts.kind))?;
+
+        let mut items = vec![
+            format!("pub const OPERATION_NAME: &str = {:?};\n", name),
+            format!(
+                "pub const QUERY: &str = {:?};\n",
+                self.operation_text(operation)?
+            ),
+            self.variables_struct(parts.variable_definitions)?,
+        ];
+        self.selection_struct(
+            "ResponseData",
+            root_type,
+            &[parts.selection_set],
+            false,
+            &mut items,
+        )?;
+
+        let mut out = format!("pub mod {} {{\n    use super::*;\n", to_snake_case(name));
+        for item in items {
+            out.push('\n');
+            for line in item.lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    writeln!(out, "    {}", line).unwrap();
+                }
+            }
+        }
+        out.push_str("}\n");
+
+        Ok(out)
+    }
+
+    /// The operation along with every fragment it spreads, ready to send to a server
+    fn operation_text(
+        &self,
+        operation: &'a OperationDefinition<'q, Q>,
+    ) -> Result<String, CodegenError> {
+        let mut used = vec![];
+        self.used_fragments(operation_parts(operation).selection_set, &mut used)?;
+
+        // the same text as printing a document of these definitions, separated by blank lines
+        let mut text = operation.to_string();
+        for fragment in used {
+            write!(text, "\n{}", fragment).unwrap();
+        }
+
+        Ok(text)
+    }
+
+    fn used_fragments(
+        &self,
+        selection_set: &'a SelectionSet<'q, Q>,
+        used: &mut Vec<&'a FragmentDefinition<'q, Q>>,
+    ) -> Result<(), CodegenError> {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => self.used_fragments(&field.selection_set, used)?,
+                Selection::InlineFragment(fragment) => {
+                    self.used_fragments(&fragment.selection_set, used)?
+                }
+                Selection::FragmentSpread(spread) => {
+                    let fragment = self.fragment(spread.fragment_name.as_ref())?;
+                    if !used.iter().any(|f| f.name == fragment.name) {
+                        used.push(fragment);
+                        self.used_fragments(&fragment.selection_set, used)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fragment(&self, name: &str) -> Result<&'a FragmentDefinition<'q, Q>, CodegenError> {
+        self.fragments
+            .get(name)
+            .cloned()
+            .ok_or_else(|| CodegenError::UnknownFragment(name.to_string()))
+    }
+
+    fn variables_struct(
+        &mut self,
+        variables: &[VariableDefinition<'q, Q>],
+    ) -> Result<String, CodegenError> {
+        let derives = self.derives("Serialize");
+        if variables.is_empty() {
+            return Ok(format!("{}\npub struct Variables;\n", derives));
+        }
+
+        let mut out = format!("{}\npub struct Variables {{\n", derives);
+        let mut names = vec![];
+        for variable in variables {
+            let leaf = self.leaf_type(named_type(&variable.var_type))?;
+            let mut rust_type = wrap_type(&variable.var_type, &leaf, true);
+            let optional = rust_type.starts_with("Option<");
+            // a default value means the server can fill it in, even for non-null variables
+            if !optional && variable.default_value.is_some() {
+                rust_type = format!("Option<{}>", rust_type);
+            }
+
+            let rust_name = unique_field_name(&mut names, variable.name.as_ref());
+            write_field(
+                &mut out,
+                variable.name.as_ref(),
+                &rust_name,
+                &rust_type,
+                optional || variable.default_value.is_some(),
+            );
+        }
+        out.push_str("}\n");
+
+        Ok(out)
+    }
+
+    fn selection_struct(
+        &mut self,
+        struct_name: &str,
+        parent_type: &'a str,
+        selection_sets: &[&'a SelectionSet<'q, Q>],
+        typename_is_tag: bool,
+        structs: &mut Vec<String>,
+    ) -> Result<(), CodegenError> {
+        let mut fields = vec![];
+        let mut conditional = vec![];
+        for selection_set in selection_sets {
+            self.collect_fields(parent_type, selection_set, &mut fields, &mut conditional)?;
+        }
+
+        if !conditional.is_empty() && !fields.iter().any(|f| f.name == TYPENAME_FIELD) {
+            return Err(CodegenError::MissingTypename(struct_name.to_string()));
+        }
+
+        let mut out = format!(
+            "{}\npub struct {} {{\n",
+            self.derives("Deserialize"),
+            struct_name
+        );
+        let mut nested = vec![];
+        let mut names = vec![];
+
+        for field in &fields {
+            if field.name == TYPENAME_FIELD {
+                // the tagged enum consumes __typename so it never reaches the struct
+                if !typename_is_tag && conditional.is_empty() {
+                    let rust_name = unique_field_name(&mut names, field.response_key);
+                    write_field(&mut out, field.response_key, &rust_name, "String", false);
+                }
+                continue;
+            }
+            let rust_name = unique_field_name(&mut names, field.response_key);
+
+            let schema_field = self.schema.field(parent_type, field.name).ok_or_else(|| {
+                CodegenError::UnknownField {
+                    type_name: parent_type.to_string(),
+                    field: field.name.to_string(),
+                }
+            })?;
+            let field_type_name = named_type(&schema_field.field_type);
+
+            let leaf = if self.schema.is_leaf(field_type_name) {
+                self.leaf_type(field_type_name)?
+            } else {
+                let nested_name = format!("{}{}", struct_name, to_pascal_case(&rust_name));
+                self.selection_struct(
+                    &nested_name,
+                    field_type_name,
+                    &field.selection_sets,
+                    false,
+                    &mut nested,
+                )?;
+                nested_name
+            };
+
+            write_field(
+                &mut out,
+                field.response_key,
+                &rust_name,
+                &wrap_type(&schema_field.field_type, &leaf, true),
+                false,
+            );
+        }
+
+        if !conditional.is_empty() {
+            let on_name = format!("{}On", struct_name);
+            writeln!(out, "    #[serde(flatten)]\n    pub on: {},", on_name).unwrap();
+            self.conditional_enum(&on_name, parent_type, &conditional, &mut nested)?;
+        }
+        out.push_str("}\n");
+
+        structs.push(out);
+        structs.append(&mut nested);
+
+        Ok(())
+    }
+
+    /// Build a `__typename` tagged enum with a variant for every concrete type a fragment
+    /// targets, falling back to `Other` for the rest
+    fn conditional_enum(
+        &mut self,
+        enum_name: &str,
+        parent_type: &'a str,
+        conditional: &[(&'a str, &'a SelectionSet<'q, Q>)],
+        structs: &mut Vec<String>,
+    ) -> Result<(), CodegenError> {
+        let mut out = format!(
+            "{}\n#[serde(tag = \"__typename\")]\npub enum {} {{\n",
+            self.derives("Deserialize"),
+            enum_name
+        );
+        let mut variants = vec![];
+
+        let schema = self.schema;
+        for possible_type in schema.possible_types(parent_type) {
+            let selection_sets: Vec<&'a SelectionSet<'q, Q>> = conditional
+                .iter()
+                .filter(|(condition, _)| {
+                    *condition == possible_type
+                        || schema.possible_types(condition).contains(&possible_type)
+                })
+                .map(|(_, selection_set)| *selection_set)
+                .collect();
+            if selection_sets.is_empty() {
+                continue;
+            }
+
+            let variant = to_pascal_case(possible_type);
+            let variant_struct = format!("{}{}", enum_name, variant);
+            self.selection_struct(
+                &variant_struct,
+                possible_type,
+                &selection_sets,
+                true,
+                &mut variants,
+            )?;
+
+            if variant != possible_type {
+                writeln!(out, "    #[serde(rename = {:?})]", possible_type).unwrap();
+            }
+            writeln!(out, "    {}({}),", variant, variant_struct).unwrap();
+        }
+        out.push_str("    #[serde(other)]\n    Other,\n}\n");
+
+        structs.push(out);
+        structs.append(&mut variants);
+
+        Ok(())
+    }
+
+    /// Gather the fields that always apply to `parent_type`, and set aside the selections that
+    /// only apply to some of its possible types
+    fn collect_fields(
+        &self,
+        parent_type: &str,
+        selection_set: &'a SelectionSet<'q, Q>,
+        fields: &mut Vec<FieldSelection<'a, 'q, Q>>,
+        conditional: &mut Vec<(&'a str, &'a SelectionSet<'q, Q>)>,
+    ) -> Result<(), CodegenError> {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let response_key = field.alias.as_ref().unwrap_or(&field.name).as_ref();
+                    match fields.iter_mut().find(|f| f.response_key == response_key) {
+                        Some(existing) => existing.selection_sets.push(&field.selection_set),
+                        None => fields.push(FieldSelection {
+                            response_key,
+                            name: field.name.as_ref(),
+                            selection_sets: vec![&field.selection_set],
+                        }),
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    let fragment = self.fragment(spread.fragment_name.as_ref())?;
+                    let TypeCondition::On(condition) = &fragment.type_condition;
+                    let condition = condition.as_ref();
+                    if self.always_applies(parent_type, condition) {
+                        self.collect_fields(
+                            parent_type,
+                            &fragment.selection_set,
+                            fields,
+                            conditional,
+                        )?;
+                    } else {
+                        conditional.push((condition, &fragment.selection_set));
+                    }
+                }
+                Selection::InlineFragment(fragment) => match &fragment.type_condition {
+                    Some(TypeCondition::On(condition))
+                        if !self.always_applies(parent_type, condition.as_ref()) =>
+                    {
+                        conditional.push((condition.as_ref(), &fragment.selection_set));
+                    }
+                    _ => self.collect_fields(
+                        parent_type,
+                        &fragment.selection_set,
+                        fields,
+                        conditional,
+                    )?,
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    fn always_applies(&self, parent_type: &str, condition: &str) -> bool {
+        if parent_type == condition {
+            return true;
+        }
+
+        let parent_types = self.schema.possible_types(parent_type);
+        let condition_types = self.schema.possible_types(condition);
+
+        !parent_types.is_empty() && parent_types.iter().all(|t| condition_types.contains(t))
+    }
+
+    /// The Rust type for a scalar, enum or input object, remembering any shared definitions
+    /// we'll need to generate for it
+    fn leaf_type(&mut self, name: &str) -> Result<String, CodegenError> {
+        let builtin = match name {
+            "Int" => Some("i64"),
+            "Float" => Some("f64"),
+            "String" | "ID" => Some("String"),
+            "Boolean" => Some("bool"),
+            _ => None,
+        };
+        if let Some(builtin) = builtin {
+            return Ok(builtin.to_string());
+        }
+
+        match self.schema.get_type(name) {
+            Some(TypeDefinition::Scalar(_)) => {
+                self.scalars.insert(name.to_string());
+            }
+            Some(TypeDefinition::Enum(_)) => {
+                self.enums.insert(name.to_string());
+            }
+            Some(TypeDefinition::InputObject(_)) => {
+                self.input_objects.insert(name.to_string());
+            }
+            _ => return Err(CodegenError::UnknownType(name.to_string())),
+        }
+
+        Ok(to_pascal_case(name))
+    }
+
+    fn enum_definition(&self, name: &str) -> Result<String, CodegenError> {
+        let values = match self.schema.get_type(name) {
+            Some(TypeDefinition::Enum(enum_type)) => &enum_type.values,
+            _ => return Err(CodegenError::UnknownType(name.to_string())),
+        };
+        let rust_name = to_pascal_case(name);
+        // values like FOO_BAR and FooBar are the same in Pascal case, so number the later ones
+        let mut variants: Vec<(String, &str)> = vec![];
+        for value in values {
+            let base = to_pascal_case(value.name.as_ref());
+            let mut variant = base.clone();
+            let mut suffix = 1;
+            while variants.iter().any(|(existing, _)| *existing == variant) {
+                suffix += 1;
+                variant = format!("{}{}", base, suffix);
+            }
+            variants.push((variant, value.name.as_ref()));
+        }
+        let fallback = vec!["Other".to_string(), "UnknownValue".to_string()]
+            .into_iter()
+            .chain((2..).map(|suffix| format!("UnknownValue{}", suffix)))
+            .find(|fallback| !variants.iter().any(|(variant, _)| variant == fallback))
+            .expect("some fallback name is free");
+
+        let mut out = format!(
+            "#[derive(Debug, Clone, PartialEq, Eq, Hash)]\npub enum {} {{\n",
+            rust_name
+        );
+        for (variant, _) in &variants {
+            writeln!(out, "    {},", variant).unwrap();
+        }
+        writeln!(out, "    {}(String),\n}}\n", fallback).unwrap();
+
+        writeln!(
+            out,
+            "impl Serialize for {} {{\n    \
+             fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{\n        \
+             serializer.serialize_str(match self {{",
+            rust_name
+        )
+        .unwrap();
+        for (variant, value) in &variants {
+            writeln!(
+                out,
+                "            {}::{} => {:?},",
+                rust_name, variant, value
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "            {}::{}(value) => value,\n        }})\n    }}\n}}\n",
+            rust_name, fallback
+        )
+        .unwrap();
+
+        writeln!(
+            out,
+            "impl<'de> Deserialize<'de> for {} {{\n    \
+             fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{\n        \
+             let value = String::deserialize(deserializer)?;\n        \
+             Ok(match value.as_str() {{",
+            rust_name
+        )
+        .unwrap();
+        for (variant, value) in &variants {
+            writeln!(
+                out,
+                "            {:?} => {}::{},",
+                value, rust_name, variant
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "            _ => {}::{}(value),\n        }})\n    }}\n}}",
+            rust_name, fallback
+        )
+        .unwrap();
+
+        Ok(out)
+    }
+
+    /// Every input object reachable from the operations' variables
+    fn input_object_definitions(&mut self) -> Result<Vec<String>, CodegenError> {
+        let mut done = BTreeSet::new();
+        let mut definitions = vec![];
+
+        while let Some(name) = self
+            .input_objects
+            .iter()
+            .find(|name| !done.contains(*name))
+            .cloned()
+        {
+            let mut out = format!(
+                "{}\npub struct {} {{\n",
+                self.derives("Serialize"),
+                to_pascal_case(&name)
+            );
+            let schema = self.schema;
+            let mut names = vec![];
+            for input_field in schema.input_fields(&name) {
+                let field_type_name = named_type(&input_field.value_type);
+                let mut leaf = self.leaf_type(field_type_name)?;
+                if self.input_reaches(field_type_name, &name, &mut BTreeSet::new()) {
+                    leaf = format!("Box<{}>", leaf);
+                }
+                let rust_type = wrap_type(&input_field.value_type, &leaf, true);
+                let optional = rust_type.starts_with("Option<");
+
+                let rust_name = unique_field_name(&mut names, input_field.name.as_ref());
+                write_field(
+                    &mut out,
+                    input_field.name.as_ref(),
+                    &rust_name,
+                    &rust_type,
+                    optional,
+                );
+            }
+            out.push_str("}\n");
+
+            definitions.push(out);
+            done.insert(name);
+        }
+
+        Ok(definitions)
+    }
+
+    /// Whether `from` can contain a `target` somewhere inside it, meaning it has to be boxed
+    fn input_reaches<'r>(
+        &'r self,
+        from: &'r str,
+        target: &str,
+        visited: &mut BTreeSet<&'r str>,
+    ) -> bool {
+        if !visited.insert(from) {
+            return false;
+        }
+
+        self.schema.input_fields(from).iter().any(|input_field| {
+            let next = named_type(&input_field.value_type);
+            // a Vec already puts its items on the heap
+            !is_list(&input_field.value_type)
+                && (next == target || self.input_reaches(next, target, visited))
+        })
+    }
+
+    fn derives(&self, serde_derive: &str) -> String {
+        let mut derives = self.options.derives.clone();
+        derives.push(serde_derive.to_string());
+
+        format!("#[derive({})]", derives.join(", "))
+    }
+}
+
+fn wrap_type<'a, T: Text<'a>>(value_type: &Type<'a, T>, inner: &str, nullable: bool) -> String {
+    let rust_type = match value_type {
+        Type::NonNullType(value_type) => return wrap_type(value_type, inner, false),
+        Type::ListType(value_type) => format!("Vec<{}>", wrap_type(value_type, inner, true)),
+        Type::NamedType(_) => inner.to_string(),
+    };
+
+    if nullable {
+        format!("Option<{}>", rust_type)
+    } else {
+        rust_type
+    }
+}
+
+/// The Rust name for a field of a struct, numbering it if an earlier field in `names` already
+/// has the name, as `userId` and `user_id` are both `user_id` in snake case
+fn unique_field_name(names: &mut Vec<String>, graphql_name: &str) -> String {
+    let base = field_identifier(graphql_name);
+    let mut rust_name = base.clone();
+    let mut suffix = 1;
+    while names.contains(&rust_name) {
+        suffix += 1;
+        rust_name = format!("{}{}", base, suffix);
+    }
+    names.push(rust_name.clone());
+    rust_name
+}
+
+fn write_field(
+    out: &mut String,
+    graphql_name: &str,
+    rust_name: &str,
+    rust_type: &str,
+    skip_if_none: bool,
+) {
+    if rust_name.trim_start_matches("r#") != graphql_name {
+        writeln!(out, "    #[serde(rename = {:?})]", graphql_name).unwrap();
+    }
+    if skip_if_none {
+        out.push_str("    #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+    }
+    writeln!(out, "    pub {}: {},", rust_name, rust_type).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        schema { query: Query }
+        type Query { user(id: ID!): User search(status: Status): [Result!]! }
+        type User { id: ID! name: String status: Status! userId: ID user_id: ID _: String team: Team }
+        type Team { id: ID! size: Int }
+        union Result = User | Team
+        enum Status { FOO_BAR FooBar OTHER UNKNOWN_VALUE }
+    "#;
+
+    fn generate_sdl(operations: &str) -> Result<String, CodegenError> {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap());
+        let operations = query::parse_query::<String>(operations).unwrap();
+        generate(&schema, &operations, &CodegenOptions::default())
+    }
+
+    #[test]
+    fn generated_code_snapshot() {
+        let generated = generate_sdl(
+            r#"
+            query UserStatus($id: ID!) { user(id: $id) { id name status } }
+            query Search { search(status: FOO_BAR) { __typename ... on Team { size } } }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(generated, include_str!("../tests/fixtures/codegen.rs.snap"));
+    }
+
+    #[test]
+    fn type_conditions_need_typename() {
+        assert_eq!(
+            generate_sdl("query Search { search { ... on Team { size } } }"),
+            Err(CodegenError::MissingTypename(
+                "ResponseDataSearch".to_string()
+            ))
+        );
+        assert_eq!(
+            generate_sdl("{ user(id: 1) { id } }"),
+            Err(CodegenError::AnonymousOperation)
+        );
+    }
+
+    #[test]
+    fn colliding_field_names_are_numbered() {
+        let generated = generate_sdl(
+            "query Ids($user_id: ID!, $userId: ID) { \
+             user(id: $user_id) { userId user_id _ team { id } Team: team { size } } }",
+        )
+        .unwrap();
+        assert!(generated.contains(
+            "    pub struct Variables {
+        pub user_id: String,
+        #[serde(rename = \"userId\")]
+        #[serde(skip_serializing_if = \"Option::is_none\")]
+        pub user_id2: Option<String>,
+    }
+"
+        ));
+        assert!(generated.contains(
+            "    pub struct ResponseDataUser {
+        #[serde(rename = \"userId\")]
+        pub user_id: Option<String>,
+        #[serde(rename = \"user_id\")]
+        pub user_id2: Option<String>,
+        #[serde(rename = \"_\")]
+        pub underscore: Option<String>,
+        pub team: Option<ResponseDataUserTeam>,
+        #[serde(rename = \"Team\")]
+        pub team2: Option<ResponseDataUserTeam2>,
+    }
+"
+        ));
+        assert!(generated.contains("    pub struct ResponseDataUserTeam2 {\n"));
+    }
+}
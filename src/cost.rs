@@ -0,0 +1,526 @@
+use crate::schema::{
+    fragments, is_list, named_type, operation_parts, OperationKind, OperationParts, Schema,
+};
+use graphql_parser::query::{
+    self, Definition, FragmentDefinition, OperationDefinition, Selection, SelectionSet, Text,
+    TypeCondition, Value,
+};
+use graphql_parser::schema::Field;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+/// How to price an operation. In TOML:
+///
+/// ```toml
+/// default_list_size = 20
+///
+/// [field_costs]
+/// "Query.search" = 10
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CostConfig {
+    /// The weight of a field returning an object, interface or union
+    pub default_field_cost: u64,
+    /// The weight of a field returning a scalar or enum
+    pub scalar_field_cost: u64,
+    /// How many items to expect from a list without a size argument
+    pub default_list_size: u64,
+    /// Arguments that limit how many items a field returns
+    pub list_size_arguments: Vec<String>,
+    /// List fields that hold the items of the field above them, like a connection's `edges`.
+    /// Directly under a field with a size argument they're already counted by it.
+    pub connection_fields: Vec<String>,
+    /// The directive giving a field its weight, ie: `@cost(weight: 5)`. Only schemas parsed from
+    /// SDL have it, introspection doesn't report applied directives.
+    pub cost_directive: String,
+    /// Weights by coordinate, ie: `Query.search`, these take precedence over the directive
+    pub field_costs: HashMap<String, u64>,
+    /// Values for the operation's variables, used when a size argument is a variable
+    pub variables: HashMap<String, serde_json::Value>,
+}
+
+impl Default for CostConfig {
+    fn default() -> Self {
+        CostConfig {
+            default_field_cost: 1,
+            scalar_field_cost: 0,
+            default_list_size: 10,
+            list_size_arguments: vec!["first".to_string(), "last".to_string(), "limit".to_string()],
+            connection_fields: vec!["edges".to_string(), "nodes".to_string()],
+            cost_directive: "cost".to_string(),
+            field_costs: HashMap::new(),
+            variables: HashMap::new(),
+        }
+    }
+}
+
+impl CostConfig {
+    pub fn from_toml(raw_config: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(raw_config)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CostError {
+    MissingRootType(OperationKind),
+    UnknownField { type_name: String, field: String },
+    UnknownFragment(String),
+}
+
+impl fmt::Display for CostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CostError::MissingRootType(kind) => {
+                write!(f, "the schema has no {:?} root type", kind)
+            }
+            CostError::UnknownField { type_name, field } => {
+                write!(f, "unknown field '{}' on type '{}'", field, type_name)
+            }
+            CostError::UnknownFragment(name) => write!(f, "unknown fragment '{}'", name),
+        }
+    }
+}
+
+impl Error for CostError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldCost {
+    /// Response keys from the root, ie: `users.edges.node.name`
+    pub path: String,
+    /// `Type.field`
+    pub coordinate: String,
+    /// The cost of resolving the field once
+    pub weight: u64,
+    /// How many times the field is expected to resolve, the product of the list sizes above it
+    pub multiplier: u64,
+    pub cost: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationCost {
+    pub name: Option<String>,
+    /// The deepest nesting of fields, root fields are at depth 1
+    pub depth: usize,
+    /// The most fields selected in one selection set, with fragments spread into it
+    pub breadth: usize,
+    pub aliases: usize,
+    pub cost: u64,
+    pub fields: Vec<FieldCost>,
+}
+
+/// Estimate the cost of every operation in `operations`.
+///
+/// A field costs its weight times the number of times it's expected to resolve. Fields under a
+/// list resolve once per item: the value of a size argument like `first` if the field has one,
+/// or `default_list_size`. A connection's `edges` or `nodes` directly inside a field with a size
+/// argument are already counted by it, any other list multiplies again. Selections on each
+/// specific type are all counted, even where they share a response key, so abstract types are
+/// priced as if every fragment applied.
+///
+/// Introspection doesn't include applied directives, so a schema parsed from an introspection
+/// result never has the `cost_directive` on its fields. Weights for those schemas have to come
+/// from `config.field_costs`.
+pub fn analyze_cost<'s, 'q, T, Q>(
+    schema: &Schema<'s, T>,
+    operations: &query::Document<'q, Q>,
+    config: &CostConfig,
+) -> Result<Vec<OperationCost>, CostError>
+where
+    T: Text<'s>,
+    Q: Text<'q>,
+{
+    let analyzer = Analyzer {
+        schema,
+        config,
+        fragments: fragments(&operations.definitions),
+    };
+
+    operations
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(analyzer.operation(operation)),
+            _ => None,
+        })
+        .collect()
+}
+
+struct Analyzer<'c, 's, 'q, T: Text<'s>, Q: Text<'q>> {
+    schema: &'c Schema<'s, T>,
+    config: &'c CostConfig,
+    fragments: HashMap<&'c str, &'c FragmentDefinition<'q, Q>>,
+}
+
+/// A field with every selection set made on it, merged across fragments
+struct CollectedField<'c, 'q, Q: Text<'q>> {
+    /// The type the field is selected on, which may be a fragment's type condition
+    type_name: String,
+    response_key: &'c str,
+    field: &'c query::Field<'q, Q>,
+    selection_sets: Vec<&'c SelectionSet<'q, Q>>,
+}
+
+/// Where a selection set is in the operation
+struct Parent<'p> {
+    type_name: &'p str,
+    path: &'p str,
+    depth: usize,
+    multiplier: u64,
+    /// Whether the field the selection set is on took a size argument
+    sized: bool,
+}
+
+impl<'c, 's, 'q, T: Text<'s>, Q: Text<'q>> Analyzer<'c, 's, 'q, T, Q> {
+    fn operation(
+        &self,
+        operation: &'c OperationDefinition<'q, Q>,
+    ) -> Result<OperationCost, CostError> {
+        let OperationParts {
+            kind,
+            name,
+            selection_set,
+            ..
+        } = operation_parts(operation);
+        let root_type = self
+            .schema
+            .root_type_name(kind)
+            .ok_or(CostError::MissingRootType(kind))?;
+
+        let mut cost = OperationCost {
+            name: name.map(str::to_string),
+            depth: 0,
+            breadth: 0,
+            aliases: 0,
+            cost: 0,
+            fields: vec![],
+        };
+        let root = Parent {
+            type_name: root_type,
+            path: "",
+            depth: 0,
+            multiplier: 1,
+            sized: false,
+        };
+        self.selection_sets(&root, &[selection_set], &mut cost)?;
+        cost.cost = cost.fields.iter().map(|field| field.cost).sum();
+
+        Ok(cost)
+    }
+
+    fn selection_sets(
+        &self,
+        parent: &Parent,
+        selection_sets: &[&'c SelectionSet<'q, Q>],
+        cost: &mut OperationCost,
+    ) -> Result<(), CostError> {
+        let mut fields = vec![];
+        for selection_set in selection_sets {
+            self.collect_fields(
+                parent.type_name,
+                selection_set,
+                &mut fields,
+                &mut Vec::new(),
+            )?;
+        }
+        cost.breadth = cost.breadth.max(fields.len());
+
+        for collected in fields {
+            let name = collected.field.name.as_ref();
+            if collected.field.alias.is_some() {
+                cost.aliases += 1;
+            }
+            // __typename costs nothing and __schema or __type aren't priced
+            if name.starts_with("__") {
+                continue;
+            }
+
+            let type_name = collected.type_name.as_str();
+            let schema_field =
+                self.schema
+                    .field(type_name, name)
+                    .ok_or_else(|| CostError::UnknownField {
+                        type_name: type_name.to_string(),
+                        field: name.to_string(),
+                    })?;
+            let field_type = named_type(&schema_field.field_type);
+            let coordinate = format!("{}.{}", type_name, name);
+            let path = if parent.path.is_empty() {
+                collected.response_key.to_string()
+            } else {
+                format!("{}.{}", parent.path, collected.response_key)
+            };
+            let depth = parent.depth + 1;
+            cost.depth = cost.depth.max(depth);
+
+            let weight = self.weight(&coordinate, schema_field, field_type);
+            cost.fields.push(FieldCost {
+                path: path.clone(),
+                coordinate,
+                weight,
+                multiplier: parent.multiplier,
+                cost: weight.saturating_mul(parent.multiplier),
+            });
+
+            let size = self.list_size(collected.field);
+            let items = match size {
+                Some(size) => size,
+                None if !is_list(&schema_field.field_type) => 1,
+                None if parent.sized && self.config.connection_fields.iter().any(|f| f == name) => {
+                    1
+                }
+                None => self.config.default_list_size,
+            };
+            if !self.schema.is_leaf(field_type) {
+                let child = Parent {
+                    type_name: field_type,
+                    path: &path,
+                    depth,
+                    multiplier: parent.multiplier.saturating_mul(items),
+                    sized: size.is_some(),
+                };
+                self.selection_sets(&child, &collected.selection_sets, cost)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Merge the fields of a selection set by the type they're selected on and response key,
+    /// spreading fragments into it
+    fn collect_fields(
+        &self,
+        type_name: &str,
+        selection_set: &'c SelectionSet<'q, Q>,
+        fields: &mut Vec<CollectedField<'c, 'q, Q>>,
+        spreading: &mut Vec<&'c str>,
+    ) -> Result<(), CostError> {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let response_key = field.alias.as_ref().unwrap_or(&field.name).as_ref();
+                    // fragments on different types can select different fields with one key
+                    match fields
+                        .iter_mut()
+                        .find(|f| f.type_name == type_name && f.response_key == response_key)
+                    {
+                        Some(existing) => existing.selection_sets.push(&field.selection_set),
+                        None => fields.push(CollectedField {
+                            type_name: type_name.to_string(),
+                            response_key,
+                            field,
+                            selection_sets: vec![&field.selection_set],
+                        }),
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.fragment_name.as_ref();
+                    let fragment = self
+                        .fragments
+                        .get(name)
+                        .ok_or_else(|| CostError::UnknownFragment(name.to_string()))?;
+                    if !spreading.contains(&name) {
+                        let TypeCondition::On(condition) = &fragment.type_condition;
+                        spreading.push(name);
+                        self.collect_fields(
+                            condition.as_ref(),
+                            &fragment.selection_set,
+                            fields,
+                            spreading,
+                        )?;
+                        spreading.pop();
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    let condition = match &fragment.type_condition {
+                        Some(TypeCondition::On(condition)) => condition.as_ref(),
+                        None => type_name,
+                    };
+                    self.collect_fields(condition, &fragment.selection_set, fields, spreading)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn weight(&self, coordinate: &str, field: &Field<'s, T>, field_type: &str) -> u64 {
+        if let Some(weight) = self.config.field_costs.get(coordinate) {
+            return *weight;
+        }
+
+        let directive_weight = field
+            .directives
+            .iter()
+            .find(|directive| directive.name.as_ref() == self.config.cost_directive)
+            .and_then(|directive| {
+                directive
+                    .arguments
+                    .iter()
+                    .find(|(name, _)| name.as_ref() == "weight")
+            })
+            .and_then(|(_, value)| match value {
+                Value::Int(weight) => weight
+                    .as_i64()
+                    .and_then(|weight| u64::try_from(weight).ok()),
+                Value::String(weight) => weight.parse().ok(),
+                _ => None,
+            });
+
+        directive_weight.unwrap_or(if self.schema.is_leaf(field_type) {
+            self.config.scalar_field_cost
+        } else {
+            self.config.default_field_cost
+        })
+    }
+
+    /// The value of the field's size argument, if it has one we can work out
+    fn list_size(&self, field: &query::Field<'q, Q>) -> Option<u64> {
+        field
+            .arguments
+            .iter()
+            .filter(|(name, _)| {
+                self.config
+                    .list_size_arguments
+                    .iter()
+                    .any(|argument| argument == name.as_ref())
+            })
+            .filter_map(|(_, value)| match value {
+                Value::Int(size) => size.as_i64().and_then(|size| u64::try_from(size).ok()),
+                Value::Variable(variable) => self
+                    .config
+                    .variables
+                    .get(variable.as_ref())
+                    .and_then(serde_json::Value::as_u64)
+                    .or(Some(self.config.default_list_size)),
+                _ => None,
+            })
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        schema { query: Query }
+        type Query {
+            users(first: Int): [User!]!
+            userConnection(first: Int): UserConnection!
+            search: [Result!]!
+        }
+        type UserConnection { edges: [UserEdge!]! nodes: [User!]! }
+        type UserEdge { node: User! }
+        type User { name: String friends: [User!]! }
+        type Post { title: String author: User }
+        union Result = User | Post
+    "#;
+
+    fn cost_of(operation: &str, config: &CostConfig) -> OperationCost {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap());
+        let operations = query::parse_query::<String>(operation).unwrap();
+        analyze_cost(&schema, &operations, config)
+            .unwrap()
+            .remove(0)
+    }
+
+    #[test]
+    fn lists_multiply_the_fields_under_them() {
+        let cost = cost_of(
+            "query Users { users(first: 5) { name friends { friends { name } } } }",
+            &CostConfig::default(),
+        );
+
+        let fields: Vec<(&str, u64, u64)> = cost
+            .fields
+            .iter()
+            .map(|field| (field.path.as_str(), field.multiplier, field.cost))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("users", 1, 1),
+                ("users.name", 5, 0),
+                ("users.friends", 5, 5),
+                ("users.friends.friends", 50, 50),
+                ("users.friends.friends.name", 500, 0),
+            ]
+        );
+        assert_eq!(cost.name.as_deref(), Some("Users"));
+        assert_eq!((cost.cost, cost.depth, cost.breadth), (56, 4, 2));
+    }
+
+    #[test]
+    fn connection_lists_are_counted_by_their_size_argument() {
+        let cost = cost_of(
+            "{ userConnection(first: 5) { edges { node { friends { name } } } nodes { name } } }",
+            &CostConfig::default(),
+        );
+
+        let fields: Vec<(&str, u64)> = cost
+            .fields
+            .iter()
+            .map(|field| (field.path.as_str(), field.multiplier))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("userConnection", 1),
+                // edges and nodes are the 5 items `first` asked for
+                ("userConnection.edges", 5),
+                ("userConnection.edges.node", 5),
+                // friends isn't a connection field, so it's a list of its own
+                ("userConnection.edges.node.friends", 5),
+                ("userConnection.edges.node.friends.name", 50),
+                ("userConnection.nodes", 5),
+                ("userConnection.nodes.name", 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn configured_weights_and_variables() {
+        let mut config = CostConfig::default();
+        config.field_costs.insert("User.friends".to_string(), 3);
+        config
+            .variables
+            .insert("count".to_string(), serde_json::json!(2));
+
+        let cost = cost_of(
+            "query($count: Int) { users(first: $count) { friends { friends { name } } } }",
+            &config,
+        );
+        assert_eq!(cost.cost, 1 + 2 * 3 + 2 * 10 * 3);
+    }
+
+    #[test]
+    fn a_response_key_is_counted_for_each_type() {
+        // `node` is a different field on each member, so neither selection absorbs the other
+        let cost = cost_of(
+            "{ search { ... on User { node: friends { name } } ... on Post { node: author { name } } } }",
+            &CostConfig::default(),
+        );
+
+        let coordinates: Vec<(&str, &str, u64)> = cost
+            .fields
+            .iter()
+            .map(|field| (field.path.as_str(), field.coordinate.as_str(), field.cost))
+            .collect();
+        assert_eq!(
+            coordinates,
+            vec![
+                ("search", "Query.search", 1),
+                ("search.node", "User.friends", 10),
+                ("search.node.name", "User.name", 0),
+                ("search.node", "Post.author", 10),
+                ("search.node.name", "User.name", 0),
+            ]
+        );
+        assert_eq!(cost.aliases, 2);
+    }
+}
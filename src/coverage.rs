@@ -0,0 +1,651 @@
+use crate::schema::{
+    deprecation, named_type, operation_parts, type_definition_name, OperationKind, OperationParts,
+    Schema, BUILT_IN_SCALARS,
+};
+use graphql_parser::query::{
+    self, OperationDefinition, Selection, SelectionSet, TypeCondition, VariableDefinition,
+};
+use graphql_parser::schema::{Text, TypeDefinition, Value};
+use graphql_parser::Pos;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Write};
+
+/// Where an operation references part of the schema
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Location {
+    /// The name the operations were given under, usually their file's path
+    pub source: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    fn new(source: &str, position: Pos) -> Self {
+        Location {
+            source: source.to_string(),
+            line: position.line,
+            column: position.column,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.source, self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoverageError {
+    MissingRootType {
+        kind: OperationKind,
+        location: Location,
+    },
+    UnknownType {
+        name: String,
+        location: Location,
+    },
+    UnknownField {
+        type_name: String,
+        field: String,
+        location: Location,
+    },
+    UnknownArgument {
+        type_name: String,
+        field: String,
+        argument: String,
+        location: Location,
+    },
+}
+
+impl fmt::Display for CoverageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoverageError::MissingRootType { kind, location } => {
+                write!(f, "{}: the schema has no {:?} root type", location, kind)
+            }
+            CoverageError::UnknownType { name, location } => {
+                write!(f, "{}: unknown type '{}'", location, name)
+            }
+            CoverageError::UnknownField {
+                type_name,
+                field,
+                location,
+            } => write!(
+                f,
+                "{}: unknown field '{}' on type '{}'",
+                location, field, type_name
+            ),
+            CoverageError::UnknownArgument {
+                type_name,
+                field,
+                argument,
+                location,
+            } => write!(
+                f,
+                "{}: unknown argument '{}' on field '{}.{}'",
+                location, argument, type_name, field
+            ),
+        }
+    }
+}
+
+impl Error for CoverageError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MemberKind {
+    Field,
+    Argument,
+    InputField,
+    EnumValue,
+}
+
+/// A field, argument, input field or enum value and where the operations reference it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemberCoverage {
+    /// `User.name`, `Query.user(id:)`, `Role.ADMIN`
+    pub coordinate: String,
+    pub kind: MemberKind,
+    pub deprecated: bool,
+    pub references: Vec<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeCoverage {
+    pub name: String,
+    /// `object`, `interface`, `union`, `enum`, `input-object` or `scalar`
+    pub kind: &'static str,
+    pub references: Vec<Location>,
+    pub members: Vec<MemberCoverage>,
+    /// Members referenced at least once, or for scalars and unions, 1 if the type is
+    pub covered: usize,
+    pub total: usize,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoverageReport {
+    /// Every type but the built-in scalars and introspection's own `__` types, in the order
+    /// the server reported them
+    pub types: Vec<TypeCoverage>,
+    pub covered: usize,
+    pub total: usize,
+    pub percentage: f64,
+}
+
+impl CoverageReport {
+    /// An LCOV tracefile with a record per type. Each member is a function and a line, numbered
+    /// from 1 in the order the schema defines them, hit once per reference, so coverage tools
+    /// can show percentages per type. Scalars and unions have a single line for the type.
+    pub fn to_lcov(&self) -> String {
+        let mut out = String::from("TN:schema\n");
+
+        for type_coverage in &self.types {
+            writeln!(out, "SF:{}", type_coverage.name).unwrap();
+
+            let lines: Vec<(&str, usize)> = if type_coverage.members.is_empty() {
+                vec![(&type_coverage.name, type_coverage.references.len())]
+            } else {
+                type_coverage
+                    .members
+                    .iter()
+                    .map(|member| (member.coordinate.as_str(), member.references.len()))
+                    .collect()
+            };
+            for (line, (name, _)) in lines.iter().enumerate() {
+                writeln!(out, "FN:{},{}", line + 1, name).unwrap();
+            }
+            for (name, hits) in &lines {
+                writeln!(out, "FNDA:{},{}", hits, name).unwrap();
+            }
+            writeln!(out, "FNF:{}", type_coverage.total).unwrap();
+            writeln!(out, "FNH:{}", type_coverage.covered).unwrap();
+            for (line, (_, hits)) in lines.iter().enumerate() {
+                writeln!(out, "DA:{},{}", line + 1, hits).unwrap();
+            }
+            writeln!(out, "LF:{}", type_coverage.total).unwrap();
+            writeln!(out, "LH:{}", type_coverage.covered).unwrap();
+            out.push_str("end_of_record\n");
+        }
+
+        out
+    }
+}
+
+/// Find which types, fields, arguments, input fields and enum values `operations` reference,
+/// each given with the name of its source so references can point back into it.
+///
+/// Input fields and enum values count when a literal spells them out, in an argument or a
+/// variable's default value. An input type given as a variable counts as wholly referenced,
+/// since any of its fields could be set. Enums a field returns aren't counted, as the
+/// operation doesn't name their values.
+///
+/// Every fragment definition is walked, whether or not an operation spreads it.
+pub fn coverage<'a, 'q, T, Q>(
+    schema: &Schema<'a, T>,
+    operations: &[(&str, &query::Document<'q, Q>)],
+) -> Result<CoverageReport, CoverageError>
+where
+    T: Text<'a>,
+    Q: Text<'q>,
+{
+    let mut walker = Walker {
+        schema,
+        source: "",
+        references: HashMap::new(),
+        whole_inputs: HashSet::new(),
+    };
+
+    for (source, document) in operations {
+        walker.source = source;
+        for definition in &document.definitions {
+            match definition {
+                query::Definition::Operation(operation) => walker.operation(operation)?,
+                query::Definition::Fragment(fragment) => {
+                    let TypeCondition::On(condition) = &fragment.type_condition;
+                    walker.use_type(condition.as_ref(), fragment.position)?;
+                    walker.selection_set(condition.as_ref(), &fragment.selection_set)?;
+                }
+            }
+        }
+    }
+
+    Ok(walker.report())
+}
+
+struct Walker<'w, 'a, T: Text<'a>> {
+    schema: &'w Schema<'a, T>,
+    source: &'w str,
+    /// Where each type name or member coordinate is referenced
+    references: HashMap<String, Vec<Location>>,
+    /// Input types already counted as wholly referenced from the current variable, so
+    /// recursive input types don't recurse forever
+    whole_inputs: HashSet<String>,
+}
+
+impl<'w, 'a, T: Text<'a>> Walker<'w, 'a, T> {
+    fn operation<'q, Q: Text<'q>>(
+        &mut self,
+        operation: &OperationDefinition<'q, Q>,
+    ) -> Result<(), CoverageError> {
+        let OperationParts {
+            kind,
+            position,
+            variable_definitions,
+            selection_set,
+            ..
+        } = operation_parts(operation);
+        let schema = self.schema;
+        let root_type =
+            schema
+                .root_type_name(kind)
+                .ok_or_else(|| CoverageError::MissingRootType {
+                    kind,
+                    location: Location::new(self.source, position),
+                })?;
+
+        for variable in variable_definitions {
+            self.variable(variable)?;
+        }
+        self.use_type(root_type, position)?;
+        self.selection_set(root_type, selection_set)
+    }
+
+    fn variable<'q, Q: Text<'q>>(
+        &mut self,
+        variable: &VariableDefinition<'q, Q>,
+    ) -> Result<(), CoverageError> {
+        let type_name = named_type(&variable.var_type);
+        self.whole_inputs.clear();
+        self.whole_input_type(type_name, variable.position)?;
+
+        if let Some(default_value) = &variable.default_value {
+            self.input_value(type_name, default_value, variable.position)?;
+        }
+
+        Ok(())
+    }
+
+    fn selection_set<'q, Q: Text<'q>>(
+        &mut self,
+        type_name: &str,
+        selection_set: &SelectionSet<'q, Q>,
+    ) -> Result<(), CoverageError> {
+        let schema = self.schema;
+
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let name = field.name.as_ref();
+                    // __typename, __schema and __type belong to every schema
+                    if name.starts_with("__") {
+                        continue;
+                    }
+
+                    let position = field.position;
+                    let schema_field = schema.field(type_name, name).ok_or_else(|| {
+                        CoverageError::UnknownField {
+                            type_name: type_name.to_string(),
+                            field: name.to_string(),
+                            location: Location::new(self.source, position),
+                        }
+                    })?;
+                    self.reference(format!("{}.{}", type_name, name), position);
+
+                    for (argument, value) in &field.arguments {
+                        let argument = argument.as_ref();
+                        let schema_argument = schema_field
+                            .arguments
+                            .iter()
+                            .find(|arg| arg.name.as_ref() == argument)
+                            .ok_or_else(|| CoverageError::UnknownArgument {
+                                type_name: type_name.to_string(),
+                                field: name.to_string(),
+                                argument: argument.to_string(),
+                                location: Location::new(self.source, position),
+                            })?;
+                        self.reference(format!("{}.{}({}:)", type_name, name, argument), position);
+                        let argument_type = named_type(&schema_argument.value_type);
+                        self.use_type(argument_type, position)?;
+                        self.input_value(argument_type, value, position)?;
+                    }
+
+                    let field_type = named_type(&schema_field.field_type);
+                    self.use_type(field_type, position)?;
+                    self.selection_set(field_type, &field.selection_set)?;
+                }
+                // fragment definitions are walked on their own
+                Selection::FragmentSpread(_) => {}
+                Selection::InlineFragment(fragment) => {
+                    let condition = match &fragment.type_condition {
+                        Some(TypeCondition::On(condition)) => {
+                            self.use_type(condition.as_ref(), fragment.position)?;
+                            condition.as_ref()
+                        }
+                        None => type_name,
+                    };
+                    self.selection_set(condition, &fragment.selection_set)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reference the input fields and enum values a literal spells out
+    fn input_value<'v, V: Text<'v>>(
+        &mut self,
+        type_name: &str,
+        value: &Value<'v, V>,
+        position: Pos,
+    ) -> Result<(), CoverageError> {
+        let schema = self.schema;
+
+        match value {
+            Value::List(items) => {
+                for item in items {
+                    self.input_value(type_name, item, position)?;
+                }
+            }
+            Value::Enum(enum_value) => {
+                self.reference(format!("{}.{}", type_name, enum_value.as_ref()), position);
+            }
+            Value::Object(fields) => {
+                for (name, value) in fields {
+                    let input_field = schema
+                        .input_fields(type_name)
+                        .iter()
+                        .find(|input_field| input_field.name.as_ref() == name.as_ref())
+                        .ok_or_else(|| CoverageError::UnknownField {
+                            type_name: type_name.to_string(),
+                            field: name.as_ref().to_string(),
+                            location: Location::new(self.source, position),
+                        })?;
+                    self.reference(format!("{}.{}", type_name, name.as_ref()), position);
+
+                    let field_type = named_type(&input_field.value_type);
+                    self.use_type(field_type, position)?;
+                    self.input_value(field_type, value, position)?;
+                }
+            }
+            // variables are counted where they're defined
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Reference every input field and enum value reachable from an input type
+    fn whole_input_type(&mut self, type_name: &str, position: Pos) -> Result<(), CoverageError> {
+        if !self.whole_inputs.insert(type_name.to_string()) {
+            return Ok(());
+        }
+        self.use_type(type_name, position)?;
+
+        let schema = self.schema;
+        match schema.get_type(type_name) {
+            Some(TypeDefinition::InputObject(input_object)) => {
+                for input_field in &input_object.fields {
+                    self.reference(
+                        format!("{}.{}", type_name, input_field.name.as_ref()),
+                        position,
+                    );
+                    self.whole_input_type(named_type(&input_field.value_type), position)?;
+                }
+            }
+            Some(TypeDefinition::Enum(enum_type)) => {
+                for value in &enum_type.values {
+                    self.reference(format!("{}.{}", type_name, value.name.as_ref()), position);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn use_type(&mut self, type_name: &str, position: Pos) -> Result<(), CoverageError> {
+        if self.schema.get_type(type_name).is_none() && !BUILT_IN_SCALARS.contains(&type_name) {
+            return Err(CoverageError::UnknownType {
+                name: type_name.to_string(),
+                location: Location::new(self.source, position),
+            });
+        }
+
+        self.reference(type_name.to_string(), position);
+        Ok(())
+    }
+
+    fn reference(&mut self, key: String, position: Pos) {
+        let location = Location::new(self.source, position);
+        let locations = self.references.entry(key).or_default();
+        // a field with several literal arguments would otherwise reference a type many times
+        // from the same spot
+        if !locations.contains(&location) {
+            locations.push(location);
+        }
+    }
+
+    fn report(self) -> CoverageReport {
+        let schema = self.schema;
+        let mut references = self.references;
+        let mut types = vec![];
+
+        for type_definition in schema.types() {
+            let name = type_definition_name(type_definition);
+            if name.starts_with("__") || BUILT_IN_SCALARS.contains(&name) {
+                continue;
+            }
+
+            let mut members = vec![];
+            let mut member =
+                |coordinate: String, kind: MemberKind, deprecated: bool| MemberCoverage {
+                    references: references.remove(&coordinate).unwrap_or_default(),
+                    coordinate,
+                    kind,
+                    deprecated,
+                };
+            let kind = match type_definition {
+                TypeDefinition::Scalar(_) => "scalar",
+                TypeDefinition::Union(_) => "union",
+                TypeDefinition::Object(_) | TypeDefinition::Interface(_) => {
+                    for field in schema.fields(name) {
+                        let field_name = field.name.as_ref();
+                        members.push(member(
+                            format!("{}.{}", name, field_name),
+                            MemberKind::Field,
+                            deprecation(&field.directives).is_some(),
+                        ));
+                        for argument in &field.arguments {
+                            members.push(member(
+                                format!("{}.{}({}:)", name, field_name, argument.name.as_ref()),
+                                MemberKind::Argument,
+                                deprecation(&argument.directives).is_some(),
+                            ));
+                        }
+                    }
+                    match type_definition {
+                        TypeDefinition::Object(_) => "object",
+                        _ => "interface",
+                    }
+                }
+                TypeDefinition::Enum(enum_type) => {
+                    for value in &enum_type.values {
+                        members.push(member(
+                            format!("{}.{}", name, value.name.as_ref()),
+                            MemberKind::EnumValue,
+                            deprecation(&value.directives).is_some(),
+                        ));
+                    }
+                    "enum"
+                }
+                TypeDefinition::InputObject(input_object) => {
+                    for input_field in &input_object.fields {
+                        members.push(member(
+                            format!("{}.{}", name, input_field.name.as_ref()),
+                            MemberKind::InputField,
+                            deprecation(&input_field.directives).is_some(),
+                        ));
+                    }
+                    "input-object"
+                }
+            };
+
+            let type_references = references.remove(name).unwrap_or_default();
+            let (covered, total) = if members.is_empty() {
+                (usize::from(!type_references.is_empty()), 1)
+            } else {
+                (
+                    members
+                        .iter()
+                        .filter(|member| !member.references.is_empty())
+                        .count(),
+                    members.len(),
+                )
+            };
+            types.push(TypeCoverage {
+                name: name.to_string(),
+                kind,
+                references: type_references,
+                members,
+                covered,
+                total,
+                percentage: percentage(covered, total),
+            });
+        }
+
+        let covered = types
+            .iter()
+            .map(|type_coverage| type_coverage.covered)
+            .sum();
+        let total = types.iter().map(|type_coverage| type_coverage.total).sum();
+        CoverageReport {
+            types,
+            covered,
+            total,
+            percentage: percentage(covered, total),
+        }
+    }
+}
+
+fn percentage(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::{parse_query, parse_schema};
+    use serde_json::json;
+
+    const SCHEMA: &str = r#"
+        enum Role { ADMIN MEMBER }
+        type User { name: String role: Role }
+        union Result = User
+        type Query { user(id: ID!): User search: [Result] }
+    "#;
+
+    fn report() -> CoverageReport {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap());
+        let operations = parse_query::<String>("{ user(id: \"1\") { name } }").unwrap();
+        coverage(&schema, &[("ops.graphql", &operations)]).unwrap()
+    }
+
+    #[test]
+    fn writes_lcov() {
+        assert_eq!(
+            report().to_lcov(),
+            "TN:schema\n\
+             SF:Role\n\
+             FN:1,Role.ADMIN\n\
+             FN:2,Role.MEMBER\n\
+             FNDA:0,Role.ADMIN\n\
+             FNDA:0,Role.MEMBER\n\
+             FNF:2\n\
+             FNH:0\n\
+             DA:1,0\n\
+             DA:2,0\n\
+             LF:2\n\
+             LH:0\n\
+             end_of_record\n\
+             SF:User\n\
+             FN:1,User.name\n\
+             FN:2,User.role\n\
+             FNDA:1,User.name\n\
+             FNDA:0,User.role\n\
+             FNF:2\n\
+             FNH:1\n\
+             DA:1,1\n\
+             DA:2,0\n\
+             LF:2\n\
+             LH:1\n\
+             end_of_record\n\
+             SF:Result\n\
+             FN:1,Result\n\
+             FNDA:0,Result\n\
+             FNF:1\n\
+             FNH:0\n\
+             DA:1,0\n\
+             LF:1\n\
+             LH:0\n\
+             end_of_record\n\
+             SF:Query\n\
+             FN:1,Query.user\n\
+             FN:2,Query.user(id:)\n\
+             FN:3,Query.search\n\
+             FNDA:1,Query.user\n\
+             FNDA:1,Query.user(id:)\n\
+             FNDA:0,Query.search\n\
+             FNF:3\n\
+             FNH:2\n\
+             DA:1,1\n\
+             DA:2,1\n\
+             DA:3,0\n\
+             LF:3\n\
+             LH:2\n\
+             end_of_record\n"
+        );
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let report = serde_json::to_value(report()).unwrap();
+
+        assert_eq!(report["covered"], 3);
+        assert_eq!(report["total"], 8);
+        assert_eq!(report["percentage"], 37.5);
+        assert_eq!(
+            report["types"][1],
+            json!({
+                "name": "User",
+                "kind": "object",
+                "references": [{ "source": "ops.graphql", "line": 1, "column": 3 }],
+                "members": [
+                    {
+                        "coordinate": "User.name",
+                        "kind": "field",
+                        "deprecated": false,
+                        "references": [{ "source": "ops.graphql", "line": 1, "column": 19 }],
+                    },
+                    {
+                        "coordinate": "User.role",
+                        "kind": "field",
+                        "deprecated": false,
+                        "references": [],
+                    },
+                ],
+                "covered": 1,
+                "total": 2,
+                "percentage": 50.0,
+            })
+        );
+        assert_eq!(report["types"][3]["members"][1]["kind"], "argument");
+    }
+}
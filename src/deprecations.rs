@@ -0,0 +1,315 @@
+use crate::coverage::{coverage, CoverageError, Location, MemberKind};
+use crate::schema::{deprecation, named_type, type_definition_name, Schema};
+use graphql_parser::query;
+use graphql_parser::schema::{Directive, Text, TypeDefinition};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A deprecated field, argument, input field or enum value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeprecatedElement {
+    /// `User.name`, `Query.user(id:)`, `Role.ADMIN`
+    pub coordinate: String,
+    pub kind: MemberKind,
+    pub reason: Option<String>,
+    /// Where operations still use it, empty for elements that can be removed
+    pub references: Vec<Location>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DeprecationPlan {
+    /// Deprecated elements operations still use, which need those operations changed first
+    pub still_used: Vec<DeprecatedElement>,
+    /// Deprecated elements no operation uses, which can be removed
+    pub removable: Vec<DeprecatedElement>,
+    /// Fields that aren't deprecated but no operation uses, which could be deprecated next
+    pub unused_fields: Vec<String>,
+}
+
+/// Plan which deprecated parts of the schema can go, from where `operations` use them.
+///
+/// An object's field counts as used wherever a field of an interface it implements is, as the
+/// object can't drop a field its interface declares. Enum values count as used wherever a field
+/// returning the enum is selected, since the server could send any of them, as well as where a
+/// literal names them. Everything else is counted as `coverage` counts it.
+pub fn plan<'a, 'q, T, Q>(
+    schema: &Schema<'a, T>,
+    operations: &[(&str, &query::Document<'q, Q>)],
+) -> Result<DeprecationPlan, CoverageError>
+where
+    T: Text<'a>,
+    Q: Text<'q>,
+{
+    let report = coverage(schema, operations)?;
+    let mut references: HashMap<String, Vec<Location>> = report
+        .types
+        .iter()
+        .flat_map(|type_coverage| &type_coverage.members)
+        .map(|member| (member.coordinate.clone(), member.references.clone()))
+        .collect();
+
+    // fields used through an interface, or through the interfaces that interface implements
+    let direct = references.clone();
+    for type_definition in schema.types() {
+        let (name, fields) = match type_definition {
+            TypeDefinition::Object(object) => (object.name.as_ref(), &object.fields),
+            TypeDefinition::Interface(interface) => (interface.name.as_ref(), &interface.fields),
+            _ => continue,
+        };
+        for interface in all_interfaces(schema, name) {
+            for field in fields {
+                let used = direct
+                    .get(&format!("{}.{}", interface, field.name.as_ref()))
+                    .cloned()
+                    .unwrap_or_default();
+                extend(
+                    &mut references,
+                    format!("{}.{}", name, field.name.as_ref()),
+                    used,
+                );
+            }
+        }
+    }
+
+    // enum values a selected field could return
+    for type_definition in schema.types() {
+        let (name, fields) = match type_definition {
+            TypeDefinition::Object(object) => (&object.name, &object.fields),
+            TypeDefinition::Interface(interface) => (&interface.name, &interface.fields),
+            _ => continue,
+        };
+        for field in fields {
+            let field_type = named_type(&field.field_type);
+            let enum_type = match schema.get_type(field_type) {
+                Some(TypeDefinition::Enum(enum_type)) => enum_type,
+                _ => continue,
+            };
+            let used = references
+                .get(&format!("{}.{}", name.as_ref(), field.name.as_ref()))
+                .cloned()
+                .unwrap_or_default();
+            for value in &enum_type.values {
+                extend(
+                    &mut references,
+                    format!("{}.{}", field_type, value.name.as_ref()),
+                    used.clone(),
+                );
+            }
+        }
+    }
+
+    let reasons = deprecation_reasons(schema);
+    let mut plan = DeprecationPlan::default();
+    for member in report
+        .types
+        .iter()
+        .flat_map(|type_coverage| &type_coverage.members)
+    {
+        let references = references.remove(&member.coordinate).unwrap_or_default();
+        match reasons.get(&member.coordinate) {
+            Some(reason) => {
+                let element = DeprecatedElement {
+                    coordinate: member.coordinate.clone(),
+                    kind: member.kind,
+                    reason: reason.clone(),
+                    references,
+                };
+                if element.references.is_empty() {
+                    plan.removable.push(element);
+                } else {
+                    plan.still_used.push(element);
+                }
+            }
+            None if member.kind == MemberKind::Field && references.is_empty() => {
+                plan.unused_fields.push(member.coordinate.clone());
+            }
+            None => {}
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Every interface `type_name` implements, directly or through another interface
+fn all_interfaces<'s, 'a, T: Text<'a>>(schema: &'s Schema<'a, T>, type_name: &str) -> Vec<&'s str> {
+    let mut interfaces: Vec<&str> = vec![];
+    let mut pending = vec![type_name];
+
+    while let Some(name) = pending.pop() {
+        let implements = match schema.get_type(name) {
+            Some(TypeDefinition::Object(object)) => &object.implements_interfaces,
+            Some(TypeDefinition::Interface(interface)) => &interface.implements_interfaces,
+            _ => continue,
+        };
+        for interface in implements {
+            let interface = interface.as_ref();
+            if interface != type_name && !interfaces.contains(&interface) {
+                interfaces.push(interface);
+                pending.push(interface);
+            }
+        }
+    }
+
+    interfaces
+}
+
+/// Add `locations` to those of `coordinate`, skipping any it already has
+fn extend(
+    references: &mut HashMap<String, Vec<Location>>,
+    coordinate: String,
+    locations: Vec<Location>,
+) {
+    let existing = references.entry(coordinate).or_default();
+    for location in locations {
+        if !existing.contains(&location) {
+            existing.push(location);
+        }
+    }
+}
+
+/// The deprecation reason of every deprecated member, by coordinate
+fn deprecation_reasons<'a, T: Text<'a>>(schema: &Schema<'a, T>) -> HashMap<String, Option<String>> {
+    let mut reasons = HashMap::new();
+    let mut add = |coordinate: String, directives: &[Directive<'a, T>]| {
+        if let Some(deprecation) = deprecation(directives) {
+            reasons.insert(coordinate, deprecation.reason.map(str::to_string));
+        }
+    };
+
+    for type_definition in schema.types() {
+        match type_definition {
+            TypeDefinition::Object(_) | TypeDefinition::Interface(_) => {
+                let name = type_definition_name(type_definition);
+                for field in schema.fields(name) {
+                    add(
+                        format!("{}.{}", name, field.name.as_ref()),
+                        &field.directives,
+                    );
+                    for argument in &field.arguments {
+                        add(
+                            format!(
+                                "{}.{}({}:)",
+                                name,
+                                field.name.as_ref(),
+                                argument.name.as_ref()
+                            ),
+                            &argument.directives,
+                        );
+                    }
+                }
+            }
+            TypeDefinition::Enum(enum_type) => {
+                for value in &enum_type.values {
+                    add(
+                        format!("{}.{}", enum_type.name.as_ref(), value.name.as_ref()),
+                        &value.directives,
+                    );
+                }
+            }
+            TypeDefinition::InputObject(input_object) => {
+                for input_field in &input_object.fields {
+                    add(
+                        format!(
+                            "{}.{}",
+                            input_object.name.as_ref(),
+                            input_field.name.as_ref()
+                        ),
+                        &input_field.directives,
+                    );
+                }
+            }
+            TypeDefinition::Scalar(_) | TypeDefinition::Union(_) => {}
+        }
+    }
+
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::to_introspection;
+    use graphql_parser::parse_schema;
+    use serde_json::json;
+
+    fn plan_for(sdl: &str, operations: &str) -> DeprecationPlan {
+        let schema = Schema::new(parse_schema::<String>(sdl).unwrap());
+        let operations = query::parse_query::<String>(operations).unwrap();
+        plan(&schema, &[("ops.graphql", &operations)]).unwrap()
+    }
+
+    fn coordinates(elements: &[DeprecatedElement]) -> Vec<&str> {
+        elements
+            .iter()
+            .map(|element| element.coordinate.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn fields_used_through_an_interface_chain_are_used() {
+        // the object comes before the interface between it and the one that's selected
+        let plan = plan_for(
+            r#"
+            schema { query: Query }
+            type Query { node: Base }
+            type Leaf implements Middle { f: String @deprecated(reason: "old") g: String }
+            interface Middle implements Base { f: String @deprecated(reason: "old") g: String }
+            interface Base { f: String @deprecated(reason: "old") g: String }
+            "#,
+            "{ node { f } }",
+        );
+
+        assert_eq!(
+            coordinates(&plan.still_used),
+            vec!["Leaf.f", "Middle.f", "Base.f"]
+        );
+        assert!(plan.removable.is_empty());
+        assert_eq!(plan.unused_fields, vec!["Leaf.g", "Middle.g", "Base.g"]);
+        assert_eq!(
+            plan.still_used[0].references[0].to_string(),
+            "ops.graphql:1:10"
+        );
+    }
+
+    #[test]
+    fn returned_enum_values_are_used() {
+        let plan = plan_for(
+            r#"
+            schema { query: Query }
+            type Query { role: Role other: Other }
+            enum Role { ADMIN GUEST @deprecated }
+            enum Other { A B @deprecated(reason: "gone") }
+            "#,
+            "{ role }",
+        );
+
+        assert_eq!(coordinates(&plan.still_used), vec!["Role.GUEST"]);
+        assert_eq!(coordinates(&plan.removable), vec!["Other.B"]);
+        assert_eq!(plan.removable[0].reason.as_deref(), Some("gone"));
+        assert_eq!(plan.unused_fields, vec!["Query.other"]);
+    }
+
+    #[test]
+    fn deprecated_arguments_and_input_fields_come_from_introspection() {
+        let sdl = r#"
+            schema { query: Query }
+            type Query { users(first: Int, limit: Int @deprecated(reason: "use first")): Int
+                         find(filter: Filter): Int }
+            input Filter { name: String old: String @deprecated }
+        "#;
+        let introspection = json!({
+            "data": to_introspection(&parse_schema::<String>(sdl).unwrap())
+        })
+        .to_string();
+        let schema = Schema::new(crate::parse::<String>(&introspection).unwrap());
+        let operations =
+            query::parse_query::<String>("{ users(limit: 1) find(filter: { name: \"a\" }) }")
+                .unwrap();
+
+        let plan = plan(&schema, &[("ops.graphql", &operations)]).unwrap();
+        assert_eq!(coordinates(&plan.still_used), vec!["Query.users(limit:)"]);
+        assert_eq!(plan.still_used[0].kind, MemberKind::Argument);
+        assert_eq!(coordinates(&plan.removable), vec!["Filter.old"]);
+        assert_eq!(plan.removable[0].kind, MemberKind::InputField);
+    }
+}
@@ -0,0 +1,497 @@
+use crate::schema::{named_type, type_definition_name};
+use graphql_parser::schema::{
+    Definition, Directive, Document, Field, InputValue, Text, TypeDefinition, Value,
+};
+use std::collections::HashSet;
+
+const SERVICE_TYPE: &str = "_Service";
+const ENTITY_UNION: &str = "_Entity";
+const ANY_SCALAR: &str = "_Any";
+const GRAPH_ENUM: &str = "join__Graph";
+
+/// Types that only exist so a gateway can talk to a subgraph
+const PLUMBING_TYPES: &[&str] = &[SERVICE_TYPE, ENTITY_UNION, ANY_SCALAR, "_FieldSet"];
+/// The field set scalar federation 2 subgraphs import from the spec without a prefix, which is
+/// only plumbing when the schema is federated
+const LINKED_FIELD_SET: &str = "FieldSet";
+/// Root fields that only exist so a gateway can talk to a subgraph
+const PLUMBING_FIELDS: &[&str] = &["_service", "_entities"];
+/// Directives from the federation specs, which say nothing to clients of the API. They're only
+/// plumbing when the schema is federated, as a plain schema can have its own `@tag` or `@key`
+const PLUMBING_DIRECTIVES: &[&str] = &[
+    "key",
+    "external",
+    "requires",
+    "provides",
+    "extends",
+    "shareable",
+    "inaccessible",
+    "override",
+    "tag",
+    "link",
+    "composeDirective",
+    "interfaceObject",
+    "authenticated",
+    "requiresScopes",
+    "policy",
+];
+/// Namespaces federation puts its supergraph and link types and directives in
+const PLUMBING_PREFIXES: &[&str] = &["join__", "link__", "federation__"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FederationRole {
+    /// A single service's schema, with `_Service`, `_Entity` and `@key`
+    Subgraph,
+    /// The composed schema a gateway serves, with `@join__*` directives
+    Supergraph,
+}
+
+/// A service composed into a supergraph, from the values of the `join__Graph` enum
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subgraph {
+    /// The enum value `@join__type` and friends refer to the subgraph by, ie: `ACCOUNTS`
+    pub graph: String,
+    /// The subgraph's own name, from `@join__graph(name:)`
+    pub name: String,
+    pub url: Option<String>,
+}
+
+/// A type that can be resolved across subgraphs by its keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub name: String,
+    /// The field sets from each `@key`, or `@join__type(key:)`, ie: `"id"` or `"sku package"`
+    pub keys: Vec<String>,
+    /// The names of the subgraphs that define the entity, only known for supergraphs
+    pub subgraphs: Vec<String>,
+}
+
+/// Whether the document is a federation subgraph or supergraph.
+///
+/// Introspection only returns directive definitions, not where they're applied, so documents
+/// parsed from introspection are recognised by their plumbing types and directive definitions.
+/// Keys and owning subgraphs need the applied directives, which are in the SDL a subgraph
+/// returns from `_service { sdl }`, or in a supergraph's SDL, either parsed with
+/// `graphql_parser::parse_schema`.
+pub fn role<'a, T: Text<'a>>(document: &Document<'a, T>) -> Option<FederationRole> {
+    let names: Vec<&str> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(type_definition) => {
+                Some(type_definition_name(type_definition))
+            }
+            Definition::DirectiveDefinition(directive) => Some(directive.name.as_ref()),
+            _ => None,
+        })
+        .collect();
+
+    if names.iter().any(|name| name.starts_with("join__")) {
+        Some(FederationRole::Supergraph)
+    } else if names
+        .iter()
+        .any(|name| [SERVICE_TYPE, ENTITY_UNION, ANY_SCALAR, "key"].contains(name))
+    {
+        Some(FederationRole::Subgraph)
+    } else {
+        None
+    }
+}
+
+/// The subgraphs of a supergraph, empty for anything else
+pub fn subgraphs<'a, T: Text<'a>>(document: &Document<'a, T>) -> Vec<Subgraph> {
+    let graph_enum = document
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::TypeDefinition(TypeDefinition::Enum(enum_type))
+                if enum_type.name.as_ref() == GRAPH_ENUM =>
+            {
+                Some(enum_type)
+            }
+            _ => None,
+        });
+
+    graph_enum
+        .map(|graph_enum| {
+            graph_enum
+                .values
+                .iter()
+                .map(|value| {
+                    let join_graph = find_directive(&value.directives, "join__graph");
+                    let graph = value.name.as_ref().to_string();
+
+                    Subgraph {
+                        name: join_graph
+                            .and_then(|directive| string_argument(directive, "name"))
+                            .map(str::to_string)
+                            .unwrap_or_else(|| graph.to_lowercase()),
+                        url: join_graph
+                            .and_then(|directive| string_argument(directive, "url"))
+                            .map(str::to_string),
+                        graph,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Every entity in the document, in definition order. Types count as entities if they have a
+/// `@key` or `@join__type(key:)`, or are members of the `_Entity` union.
+pub fn entities<'a, T: Text<'a>>(document: &Document<'a, T>) -> Vec<Entity> {
+    let subgraphs = subgraphs(document);
+    let subgraph_name = |graph: &str| {
+        subgraphs
+            .iter()
+            .find(|subgraph| subgraph.graph == graph)
+            .map(|subgraph| subgraph.name.clone())
+            .unwrap_or_else(|| graph.to_lowercase())
+    };
+
+    let entity_union: HashSet<&str> = document
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::TypeDefinition(TypeDefinition::Union(union))
+                if union.name.as_ref() == ENTITY_UNION =>
+            {
+                Some(union.types.iter().map(|name| name.as_ref()).collect())
+            }
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let mut entities = vec![];
+    for definition in &document.definitions {
+        let (name, directives) = match definition {
+            Definition::TypeDefinition(TypeDefinition::Object(object)) => {
+                (object.name.as_ref(), &object.directives)
+            }
+            Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+                (interface.name.as_ref(), &interface.directives)
+            }
+            _ => continue,
+        };
+
+        let mut keys: Vec<String> = vec![];
+        let mut owners: Vec<String> = vec![];
+        for directive in directives {
+            match directive.name.as_ref() {
+                "key" => keys.extend(string_argument(directive, "fields").map(str::to_string)),
+                "join__type" | "join__owner" => {
+                    if let Some(graph) = enum_argument(directive, "graph") {
+                        let subgraph = subgraph_name(graph);
+                        if !owners.contains(&subgraph) {
+                            owners.push(subgraph);
+                        }
+                    }
+                    if let Some(key) = string_argument(directive, "key") {
+                        if !keys.iter().any(|existing| existing == key) {
+                            keys.push(key.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !keys.is_empty() || entity_union.contains(name) {
+            entities.push(Entity {
+                name: name.to_string(),
+                keys,
+                subgraphs: owners,
+            });
+        }
+    }
+
+    entities
+}
+
+/// Strip federation plumbing to get the schema clients of the gateway see: the `_Service`,
+/// `_Entity` and `_Any` types, the `_service` and `_entities` fields, `join__`, `link__` and
+/// `federation__` types and directives, and anything marked `@inaccessible`. Fields, arguments
+/// and input fields of a removed type go with it.
+///
+/// Unprefixed directives like `@key`, `@tag` or `@inaccessible` are only treated as federation
+/// ones if the document is federated, otherwise they're the schema's own and are kept.
+pub fn api_schema<'a, T: Text<'a>>(mut document: Document<'a, T>) -> Document<'a, T> {
+    let federated = is_federated(&document);
+    let mut plumbing = Plumbing {
+        removed_types: HashSet::new(),
+        federated,
+    };
+    plumbing.removed_types = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(type_definition) => {
+                let name = type_definition_name(type_definition);
+                let inaccessible = plumbing.is_inaccessible(type_directives(type_definition));
+                let plumbing_type =
+                    is_plumbing_type(name) || (federated && name == LINKED_FIELD_SET);
+                if inaccessible || plumbing_type {
+                    Some(name.to_string())
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+        .collect();
+
+    document.definitions.retain(|definition| match definition {
+        Definition::TypeDefinition(type_definition) => !plumbing
+            .removed_types
+            .contains(type_definition_name(type_definition)),
+        Definition::DirectiveDefinition(directive) => {
+            !plumbing.is_directive(directive.name.as_ref())
+        }
+        _ => true,
+    });
+
+    for definition in &mut document.definitions {
+        match definition {
+            Definition::SchemaDefinition(schema) => {
+                plumbing.strip_directives(&mut schema.directives)
+            }
+            Definition::TypeDefinition(type_definition) => {
+                plumbing.strip_type_definition(type_definition)
+            }
+            Definition::DirectiveDefinition(directive) => {
+                plumbing.strip_input_values(&mut directive.arguments)
+            }
+            _ => {}
+        }
+    }
+
+    document
+}
+
+/// What `api_schema` strips from a document
+struct Plumbing {
+    removed_types: HashSet<String>,
+    /// Whether unprefixed federation directives are plumbing rather than the schema's own
+    federated: bool,
+}
+
+impl Plumbing {
+    fn is_directive(&self, name: &str) -> bool {
+        has_plumbing_prefix(name) || (self.federated && PLUMBING_DIRECTIVES.contains(&name))
+    }
+
+    fn is_inaccessible<'a, T: Text<'a>>(&self, directives: &[Directive<'a, T>]) -> bool {
+        self.federated && find_directive(directives, "inaccessible").is_some()
+    }
+
+    fn strip_type_definition<'a, T: Text<'a>>(&self, type_definition: &mut TypeDefinition<'a, T>) {
+        match type_definition {
+            TypeDefinition::Scalar(scalar) => self.strip_directives(&mut scalar.directives),
+            TypeDefinition::Object(object) => {
+                self.strip_directives(&mut object.directives);
+                object
+                    .implements_interfaces
+                    .retain(|name| !self.removed_types.contains(name.as_ref()));
+                self.strip_fields(&mut object.fields);
+            }
+            TypeDefinition::Interface(interface) => {
+                self.strip_directives(&mut interface.directives);
+                interface
+                    .implements_interfaces
+                    .retain(|name| !self.removed_types.contains(name.as_ref()));
+                self.strip_fields(&mut interface.fields);
+            }
+            TypeDefinition::Union(union) => {
+                self.strip_directives(&mut union.directives);
+                union
+                    .types
+                    .retain(|name| !self.removed_types.contains(name.as_ref()));
+            }
+            TypeDefinition::Enum(enum_type) => {
+                self.strip_directives(&mut enum_type.directives);
+                enum_type
+                    .values
+                    .retain(|value| !self.is_inaccessible(&value.directives));
+                for value in &mut enum_type.values {
+                    self.strip_directives(&mut value.directives);
+                }
+            }
+            TypeDefinition::InputObject(input_object) => {
+                self.strip_directives(&mut input_object.directives);
+                self.strip_input_values(&mut input_object.fields);
+            }
+        }
+    }
+
+    fn strip_fields<'a, T: Text<'a>>(&self, fields: &mut Vec<Field<'a, T>>) {
+        fields.retain(|field| {
+            !PLUMBING_FIELDS.contains(&field.name.as_ref())
+                && !self.is_inaccessible(&field.directives)
+                && !self.removed_types.contains(named_type(&field.field_type))
+        });
+        for field in fields {
+            self.strip_directives(&mut field.directives);
+            self.strip_input_values(&mut field.arguments);
+        }
+    }
+
+    fn strip_input_values<'a, T: Text<'a>>(&self, input_values: &mut Vec<InputValue<'a, T>>) {
+        input_values.retain(|input_value| {
+            !self.is_inaccessible(&input_value.directives)
+                && !self
+                    .removed_types
+                    .contains(named_type(&input_value.value_type))
+        });
+        for input_value in input_values {
+            self.strip_directives(&mut input_value.directives);
+        }
+    }
+
+    fn strip_directives<'a, T: Text<'a>>(&self, directives: &mut Vec<Directive<'a, T>>) {
+        directives.retain(|directive| !self.is_directive(directive.name.as_ref()));
+    }
+}
+
+fn type_directives<'s, 'a, T: Text<'a>>(
+    type_definition: &'s TypeDefinition<'a, T>,
+) -> &'s [Directive<'a, T>] {
+    match type_definition {
+        TypeDefinition::Scalar(scalar) => &scalar.directives,
+        TypeDefinition::Object(object) => &object.directives,
+        TypeDefinition::Interface(interface) => &interface.directives,
+        TypeDefinition::Union(union) => &union.directives,
+        TypeDefinition::Enum(enum_type) => &enum_type.directives,
+        TypeDefinition::InputObject(input_object) => &input_object.directives,
+    }
+}
+
+/// Whether the document has any federation types or directives, counting the `link__` and
+/// `federation__` ones `role` doesn't look at
+fn is_federated<'a, T: Text<'a>>(document: &Document<'a, T>) -> bool {
+    role(document).is_some()
+        || document
+            .definitions
+            .iter()
+            .any(|definition| match definition {
+                Definition::TypeDefinition(type_definition) => {
+                    has_plumbing_prefix(type_definition_name(type_definition))
+                }
+                Definition::DirectiveDefinition(directive) => {
+                    has_plumbing_prefix(directive.name.as_ref())
+                }
+                _ => false,
+            })
+}
+
+fn is_plumbing_type(name: &str) -> bool {
+    PLUMBING_TYPES.contains(&name) || has_plumbing_prefix(name)
+}
+
+fn has_plumbing_prefix(name: &str) -> bool {
+    PLUMBING_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+fn find_directive<'s, 'a, T: Text<'a>>(
+    directives: &'s [Directive<'a, T>],
+    name: &str,
+) -> Option<&'s Directive<'a, T>> {
+    directives
+        .iter()
+        .find(|directive| directive.name.as_ref() == name)
+}
+
+fn argument<'s, 'a, T: Text<'a>>(
+    directive: &'s Directive<'a, T>,
+    name: &str,
+) -> Option<&'s Value<'a, T>> {
+    directive
+        .arguments
+        .iter()
+        .find(|(argument, _)| argument.as_ref() == name)
+        .map(|(_, value)| value)
+}
+
+fn string_argument<'s, 'a, T: Text<'a>>(
+    directive: &'s Directive<'a, T>,
+    name: &str,
+) -> Option<&'s str> {
+    match argument(directive, name) {
+        Some(Value::String(value)) => Some(value),
+        _ => None,
+    }
+}
+
+fn enum_argument<'s, 'a, T: Text<'a>>(
+    directive: &'s Directive<'a, T>,
+    name: &str,
+) -> Option<&'s str> {
+    match argument(directive, name) {
+        Some(Value::Enum(value)) => Some(value.as_ref()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    fn api_sdl(sdl: &str) -> String {
+        api_schema(parse_schema::<String>(sdl).unwrap()).to_string()
+    }
+
+    #[test]
+    fn references_to_removed_types_are_dropped() {
+        let api = api_sdl(
+            r#"
+            scalar _Any
+            type Query { user(id: ID, hint: Secret): User _entities(representations: [_Any!]!): [User]! }
+            type User @key(fields: "id") { id: ID! secret: Secret internal: String @inaccessible }
+            type Secret @inaccessible { value: String }
+            input Filter { name: String secret: Secret }
+            directive @hidden(by: Secret) on FIELD_DEFINITION
+            "#,
+        );
+
+        assert_eq!(
+            api,
+            "type Query {\n  user(id: ID): User\n}\n\n\
+             type User {\n  id: ID!\n}\n\n\
+             input Filter {\n  name: String\n}\n\n\
+             directive @hidden on FIELD_DEFINITION\n"
+        );
+    }
+
+    #[test]
+    fn field_set_is_only_plumbing_when_federated() {
+        let plain = "type Query {\n  fields: FieldSet\n}\n\nscalar FieldSet\n";
+        assert_eq!(api_sdl(plain), plain);
+
+        let linked = api_sdl(
+            r#"
+            scalar link__Import
+            scalar FieldSet
+            type Query { fields: FieldSet name: String }
+            "#,
+        );
+        assert_eq!(linked, "type Query {\n  name: String\n}\n");
+    }
+
+    #[test]
+    fn federation_directives_are_only_plumbing_when_federated() {
+        let plain = "type Query {\n  user: User @tag(name: \"public\")\n}\n\n\
+                     type User @authenticated {\n  id: ID!\n  internal: String @inaccessible\n}\n\n\
+                     directive @tag(name: String!) repeatable on FIELD_DEFINITION\n\n\
+                     directive @authenticated on OBJECT\n\n\
+                     directive @inaccessible on FIELD_DEFINITION\n";
+        assert_eq!(api_sdl(plain), plain);
+
+        let subgraph = api_sdl(&format!("{}\nscalar _Any\n", plain));
+        assert_eq!(
+            subgraph,
+            "type Query {\n  user: User\n}\n\ntype User {\n  id: ID!\n}\n"
+        );
+    }
+}
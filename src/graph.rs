@@ -0,0 +1,431 @@
+use crate::schema::{named_type, type_definition_name};
+use graphql_parser::schema::{Definition, Document, Field, Text, TypeDefinition};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+#[derive(Debug, Clone)]
+pub struct GraphOptions {
+    /// Only include types this type leads to, following edges outwards and from interfaces to
+    /// their implementations
+    pub root: Option<String>,
+    /// How many edges away from `root` to go, unlimited if `None`
+    pub depth: Option<usize>,
+    /// Leave out scalars, built in or custom
+    pub hide_scalars: bool,
+    /// Include input objects, and the argument and input field edges leading to them
+    pub input_types: bool,
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        GraphOptions {
+            root: None,
+            depth: None,
+            hide_scalars: false,
+            input_types: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// A field returns the type
+    Field,
+    /// An argument of a field takes the type
+    Argument,
+    /// An input field of an input object takes the type
+    InputField,
+    /// An object or interface implements the interface
+    Implements,
+    /// A union has the type as a member
+    Member,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    /// The fields, arguments or input fields the edge stands for, empty for `Implements` and
+    /// `Member`
+    pub labels: Vec<String>,
+}
+
+/// Which types depend on which, one node per type and at most one edge of each kind between
+/// two types
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TypeGraph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl TypeGraph {
+    /// Build the graph of every type in the document, skipping introspection's own `__` types
+    pub fn new<'a, T: Text<'a>>(document: &Document<'a, T>, options: &GraphOptions) -> Self {
+        let mut graph = TypeGraph::default();
+
+        for definition in &document.definitions {
+            let type_definition = match definition {
+                Definition::TypeDefinition(type_definition) => type_definition,
+                _ => continue,
+            };
+            let name = type_definition_name(type_definition);
+            if name.starts_with("__") {
+                continue;
+            }
+
+            let kind = match type_definition {
+                TypeDefinition::Scalar(_) => NodeKind::Scalar,
+                TypeDefinition::Object(object) => {
+                    graph.implements(name, &object.implements_interfaces);
+                    graph.fields(name, &object.fields);
+                    NodeKind::Object
+                }
+                TypeDefinition::Interface(interface) => {
+                    graph.implements(name, &interface.implements_interfaces);
+                    graph.fields(name, &interface.fields);
+                    NodeKind::Interface
+                }
+                TypeDefinition::Union(union) => {
+                    for member in &union.types {
+                        graph.edge(name, member.as_ref(), EdgeKind::Member, None);
+                    }
+                    NodeKind::Union
+                }
+                TypeDefinition::Enum(_) => NodeKind::Enum,
+                TypeDefinition::InputObject(input_object) => {
+                    for input_field in &input_object.fields {
+                        graph.edge(
+                            name,
+                            named_type(&input_field.value_type),
+                            EdgeKind::InputField,
+                            Some(input_field.name.as_ref().to_string()),
+                        );
+                    }
+                    NodeKind::InputObject
+                }
+            };
+            graph.nodes.push(Node {
+                name: name.to_string(),
+                kind,
+            });
+        }
+
+        graph.nodes.retain(|node| match node.kind {
+            NodeKind::Scalar => !options.hide_scalars,
+            NodeKind::InputObject => options.input_types,
+            _ => true,
+        });
+        if let Some(root) = &options.root {
+            let reached = graph.reached_from(root, options.depth);
+            graph.nodes.retain(|node| reached.contains(&node.name));
+        }
+
+        let names: HashSet<String> = graph.nodes.iter().map(|node| node.name.clone()).collect();
+        graph
+            .edges
+            .retain(|edge| names.contains(&edge.from) && names.contains(&edge.to));
+
+        graph
+    }
+
+    fn implements<V: AsRef<str>>(&mut self, name: &str, interfaces: &[V]) {
+        for interface in interfaces {
+            self.edge(name, interface.as_ref(), EdgeKind::Implements, None);
+        }
+    }
+
+    fn fields<'a, T: Text<'a>>(&mut self, name: &str, fields: &[Field<'a, T>]) {
+        for field in fields {
+            let field_name = field.name.as_ref();
+            self.edge(
+                name,
+                named_type(&field.field_type),
+                EdgeKind::Field,
+                Some(field_name.to_string()),
+            );
+            for argument in &field.arguments {
+                self.edge(
+                    name,
+                    named_type(&argument.value_type),
+                    EdgeKind::Argument,
+                    Some(format!("{}({}:)", field_name, argument.name.as_ref())),
+                );
+            }
+        }
+    }
+
+    fn edge(&mut self, from: &str, to: &str, kind: EdgeKind, label: Option<String>) {
+        let index = match self
+            .edges
+            .iter()
+            .position(|edge| edge.from == from && edge.to == to && edge.kind == kind)
+        {
+            Some(index) => index,
+            None => {
+                self.edges.push(Edge {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    kind,
+                    labels: vec![],
+                });
+                self.edges.len() - 1
+            }
+        };
+        self.edges[index].labels.extend(label);
+    }
+
+    /// The kept types within `depth` edges of `root`
+    fn reached_from(&self, root: &str, depth: Option<usize>) -> HashSet<String> {
+        let kept: HashSet<&str> = self.nodes.iter().map(|node| node.name.as_str()).collect();
+        let mut next: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            next.entry(&edge.from).or_default().push(&edge.to);
+            if edge.kind == EdgeKind::Implements {
+                next.entry(&edge.to).or_default().push(&edge.from);
+            }
+        }
+
+        let mut reached: HashSet<&str> = HashSet::new();
+        let mut pending = VecDeque::new();
+        if let Some(root) = kept.get(root).copied() {
+            reached.insert(root);
+            pending.push_back((root, 0));
+        }
+
+        while let Some((name, distance)) = pending.pop_front() {
+            if matches!(depth, Some(depth) if distance >= depth) {
+                continue;
+            }
+            for to in next.get(name).into_iter().flatten() {
+                if kept.contains(to) && reached.insert(*to) {
+                    pending.push_back((*to, distance + 1));
+                }
+            }
+        }
+
+        reached.into_iter().map(str::to_string).collect()
+    }
+
+    /// Render as a Graphviz `digraph`
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph Schema {\n  rankdir=LR;\n  node [shape=box];\n");
+
+        for node in &self.nodes {
+            let attributes = match node.kind {
+                NodeKind::Object => "",
+                NodeKind::Interface => ", style=rounded",
+                NodeKind::Union => ", shape=hexagon",
+                NodeKind::Enum => ", shape=note",
+                NodeKind::Scalar => ", shape=ellipse",
+                NodeKind::InputObject => ", style=dashed",
+            };
+            writeln!(
+                out,
+                "  {:?} [label={:?}{}];",
+                node.name, node.name, attributes
+            )
+            .unwrap();
+        }
+
+        for edge in &self.edges {
+            let mut attributes = vec![];
+            if !edge.labels.is_empty() {
+                attributes.push(format!("label={:?}", edge.labels.join("\n")));
+            }
+            attributes.extend(
+                match edge.kind {
+                    EdgeKind::Field | EdgeKind::InputField => None,
+                    EdgeKind::Argument => Some("style=dashed"),
+                    EdgeKind::Implements => Some("style=dashed, arrowhead=empty"),
+                    EdgeKind::Member => Some("style=dotted"),
+                }
+                .map(str::to_string),
+            );
+
+            if attributes.is_empty() {
+                writeln!(out, "  {:?} -> {:?};", edge.from, edge.to).unwrap();
+            } else {
+                writeln!(
+                    out,
+                    "  {:?} -> {:?} [{}];",
+                    edge.from,
+                    edge.to,
+                    attributes.join(", ")
+                )
+                .unwrap();
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+
+        for node in &self.nodes {
+            let name = &node.name;
+            match node.kind {
+                NodeKind::Object => writeln!(out, "  {}[{}]", name, name),
+                NodeKind::Interface => writeln!(out, "  {}([{}])", name, name),
+                NodeKind::Union => writeln!(out, "  {}{{{{{}}}}}", name, name),
+                NodeKind::Enum => writeln!(out, "  {}>{}]", name, name),
+                NodeKind::Scalar => writeln!(out, "  {}(({}))", name, name),
+                NodeKind::InputObject => writeln!(out, "  {}[/{}/]", name, name),
+            }
+            .unwrap();
+        }
+
+        for edge in &self.edges {
+            let arrow = match edge.kind {
+                EdgeKind::Field | EdgeKind::InputField => "-->",
+                EdgeKind::Argument | EdgeKind::Implements | EdgeKind::Member => "-.->",
+            };
+            let label = match edge.kind {
+                EdgeKind::Implements => "implements".to_string(),
+                EdgeKind::Member => String::new(),
+                _ => edge.labels.join(", "),
+            };
+            if label.is_empty() {
+                writeln!(out, "  {} {} {}", edge.from, arrow, edge.to).unwrap();
+            } else {
+                writeln!(out, "  {} {}|\"{}\"| {}", edge.from, arrow, label, edge.to).unwrap();
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        interface Node { id: ID! }
+        type User implements Node { id: ID! friends(first: Int): [User] born: Date }
+        union Result = User
+        input Filter { name: String }
+        scalar Date
+        type Query { search(filter: Filter): [Result] }
+    "#;
+
+    fn graph(options: &GraphOptions) -> TypeGraph {
+        TypeGraph::new(&parse_schema::<String>(SCHEMA).unwrap(), options)
+    }
+
+    fn node_names(graph: &TypeGraph) -> Vec<&str> {
+        graph.nodes.iter().map(|node| node.name.as_str()).collect()
+    }
+
+    #[test]
+    fn renders_dot() {
+        assert_eq!(
+            graph(&GraphOptions::default()).to_dot(),
+            r#"digraph Schema {
+  rankdir=LR;
+  node [shape=box];
+  "Node" [label="Node", style=rounded];
+  "User" [label="User"];
+  "Result" [label="Result", shape=hexagon];
+  "Filter" [label="Filter", style=dashed];
+  "Date" [label="Date", shape=ellipse];
+  "Query" [label="Query"];
+  "User" -> "Node" [style=dashed, arrowhead=empty];
+  "User" -> "User" [label="friends"];
+  "User" -> "Date" [label="born"];
+  "Result" -> "User" [style=dotted];
+  "Query" -> "Result" [label="search"];
+  "Query" -> "Filter" [label="search(filter:)", style=dashed];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn renders_mermaid() {
+        assert_eq!(
+            graph(&GraphOptions::default()).to_mermaid(),
+            r#"flowchart LR
+  Node([Node])
+  User[User]
+  Result{{Result}}
+  Filter[/Filter/]
+  Date((Date))
+  Query[Query]
+  User -.->|"implements"| Node
+  User -->|"friends"| User
+  User -->|"born"| Date
+  Result -.-> User
+  Query -->|"search"| Result
+  Query -.->|"search(filter:)"| Filter
+"#
+        );
+    }
+
+    #[test]
+    fn filters_from_a_root() {
+        let from_node = |depth| {
+            graph(&GraphOptions {
+                root: Some("Node".to_string()),
+                depth,
+                ..GraphOptions::default()
+            })
+        };
+
+        // interfaces lead to their implementations
+        assert_eq!(node_names(&from_node(None)), vec!["Node", "User", "Date"]);
+        assert_eq!(node_names(&from_node(Some(1))), vec!["Node", "User"]);
+        assert_eq!(node_names(&from_node(Some(0))), vec!["Node"]);
+        assert!(node_names(&graph(&GraphOptions {
+            root: Some("Missing".to_string()),
+            ..GraphOptions::default()
+        }))
+        .is_empty());
+    }
+
+    #[test]
+    fn hides_scalars_and_input_types() {
+        let without_scalars = graph(&GraphOptions {
+            hide_scalars: true,
+            ..GraphOptions::default()
+        });
+        assert_eq!(
+            node_names(&without_scalars),
+            vec!["Node", "User", "Result", "Filter", "Query"]
+        );
+        assert!(without_scalars.edges.iter().all(|edge| edge.to != "Date"));
+
+        let without_inputs = graph(&GraphOptions {
+            input_types: false,
+            ..GraphOptions::default()
+        });
+        assert_eq!(
+            node_names(&without_inputs),
+            vec!["Node", "User", "Result", "Date", "Query"]
+        );
+        assert!(without_inputs
+            .edges
+            .iter()
+            .all(|edge| edge.kind != EdgeKind::Argument));
+    }
+}
@@ -0,0 +1,727 @@
+use crate::schema::{
+    default_value_source, deprecation, kind_name, named_type, type_definition_name,
+    type_description, OperationKind, Schema,
+};
+use graphql_parser::schema::{
+    Definition, Directive, DirectiveDefinition, Field, InputValue, Text, Type, TypeDefinition,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 0; color: #1f2328; line-height: 1.5; }
+header { background: #24292f; padding: 0.75em 2em; display: flex; gap: 2em; align-items: center; \
+position: relative; }
+header a { color: #fff; font-weight: bold; text-decoration: none; }
+header input { padding: 0.3em 0.6em; width: 20em; }
+#search-results { position: absolute; top: 3em; background: #fff; border: 1px solid #d0d7de; \
+list-style: none; margin: 0; padding: 0.5em 1em; max-height: 60vh; overflow-y: auto; }
+#search-results:empty { display: none; }
+main { padding: 1em 2em; max-width: 70em; }
+a { color: #0969da; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1.5em; }
+th, td { border: 1px solid #d0d7de; padding: 0.4em 0.75em; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+code { font-family: ui-monospace, monospace; }
+.kind { color: #57606a; font-size: 0.6em; font-weight: normal; text-transform: uppercase; }
+.deprecated { background: #fff8c5; border: 1px solid #d4a72c; border-radius: 1em; \
+padding: 0 0.5em; font-size: 0.8em; }
+.arguments { margin: 0.25em 0 0; padding-left: 1.25em; }
+.deprecation-reason { font-style: italic; }
+";
+
+const SEARCH_SCRIPT: &str = "\
+(function () {
+  var script = document.currentScript;
+  var root = script.getAttribute('data-root');
+  var input = document.getElementById('search');
+  var results = document.getElementById('search-results');
+  input.addEventListener('input', function () {
+    var query = input.value.trim().toLowerCase();
+    results.innerHTML = '';
+    if (!query) return;
+    window.SEARCH_INDEX
+      .filter(function (entry) { return entry.name.toLowerCase().indexOf(query) !== -1; })
+      .slice(0, 50)
+      .forEach(function (entry) {
+        var link = document.createElement('a');
+        link.href = root + entry.url;
+        link.textContent = entry.name;
+        var item = document.createElement('li');
+        item.appendChild(link);
+        item.appendChild(document.createTextNode(' ' + entry.kind));
+        results.appendChild(item);
+      });
+  });
+})();
+";
+
+#[derive(Debug, Clone)]
+pub struct HtmlOptions {
+    /// Shown in the header and the title of every page
+    pub title: String,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        HtmlOptions {
+            title: "GraphQL Schema".to_string(),
+        }
+    }
+}
+
+/// A file of the generated site
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlFile {
+    /// Relative to the root of the site, ie: `types/User.html`
+    pub path: String,
+    pub contents: String,
+}
+
+/// Generate a static documentation site: an index of every type and directive, a page per type
+/// under `types/`, a stylesheet and a search index. Pages only link to each other with relative
+/// URLs and the search runs in the browser, so the site works straight from the filesystem.
+///
+/// Introspection types (`__Type`, `__Schema`, ...) don't get pages.
+pub fn generate<'a, T: Text<'a>>(schema: &Schema<'a, T>, options: &HtmlOptions) -> Vec<HtmlFile> {
+    let generator = Generator {
+        schema,
+        options,
+        used_by: used_by(schema),
+    };
+
+    let mut files = vec![
+        HtmlFile {
+            path: "index.html".to_string(),
+            contents: generator.index(),
+        },
+        HtmlFile {
+            path: "style.css".to_string(),
+            contents: STYLE.to_string(),
+        },
+        HtmlFile {
+            path: "search.js".to_string(),
+            contents: format!(
+                "window.SEARCH_INDEX = {};\n{}",
+                generator.search_index(),
+                SEARCH_SCRIPT
+            ),
+        },
+    ];
+    for type_definition in documented_types(schema) {
+        files.push(HtmlFile {
+            path: format!("types/{}.html", type_definition_name(type_definition)),
+            contents: generator.type_page(type_definition),
+        });
+    }
+
+    files
+}
+
+struct Generator<'g, 'a, T: Text<'a>> {
+    schema: &'g Schema<'a, T>,
+    options: &'g HtmlOptions,
+    /// The fields, arguments, input fields and unions that refer to each type, as their
+    /// coordinate and a link relative to the `types` directory
+    used_by: HashMap<&'g str, Vec<(String, String)>>,
+}
+
+impl<'g, 'a, T: Text<'a>> Generator<'g, 'a, T> {
+    /// The page around `body`, `root` is the relative path back to the root of the site
+    fn page(&self, title: &str, root: &str, body: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n\
+             <html lang=\"en\">\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <title>{title} - {site}</title>\n\
+             <link rel=\"stylesheet\" href=\"{root}style.css\">\n\
+             </head>\n\
+             <body>\n\
+             <header>\n\
+             <a href=\"{root}index.html\">{site}</a>\n\
+             <input id=\"search\" type=\"search\" placeholder=\"Search types and fields\" autocomplete=\"off\">\n\
+             <ul id=\"search-results\"></ul>\n\
+             </header>\n\
+             <main>\n\
+             {body}\
+             </main>\n\
+             <script src=\"{root}search.js\" data-root=\"{root}\"></script>\n\
+             </body>\n\
+             </html>\n",
+            title = escape(title),
+            site = escape(&self.options.title),
+            root = root,
+            body = body,
+        )
+    }
+
+    fn index(&self) -> String {
+        let mut body = format!("<h1>{}</h1>\n", escape(&self.options.title));
+
+        let roots: Vec<(&str, &str)> = [
+            ("Query", OperationKind::Query),
+            ("Mutation", OperationKind::Mutation),
+            ("Subscription", OperationKind::Subscription),
+        ]
+        .iter()
+        .filter_map(|(label, kind)| Some((*label, self.schema.root_type_name(*kind)?)))
+        .collect();
+        if !roots.is_empty() {
+            body.push_str("<h2>Operations</h2>\n<ul>\n");
+            for (label, name) in &roots {
+                writeln!(
+                    body,
+                    "<li>{}: <a href=\"types/{name}.html\">{name}</a></li>",
+                    label,
+                    name = name
+                )
+                .unwrap();
+            }
+            body.push_str("</ul>\n");
+        }
+
+        let sections = [
+            ("Objects", "object"),
+            ("Interfaces", "interface"),
+            ("Unions", "union"),
+            ("Enums", "enum"),
+            ("Input objects", "input object"),
+            ("Scalars", "scalar"),
+        ];
+        for (heading, kind) in &sections {
+            let types: Vec<_> = documented_types(self.schema)
+                .filter(|type_definition| kind_name(type_definition) == *kind)
+                .collect();
+            if types.is_empty() {
+                continue;
+            }
+
+            writeln!(body, "<h2>{}</h2>\n<table>", heading).unwrap();
+            for type_definition in types {
+                let name = type_definition_name(type_definition);
+                writeln!(
+                    body,
+                    "<tr><td><a href=\"types/{name}.html\"><code>{name}</code></a></td><td>{}</td></tr>",
+                    summary(type_description(type_definition)),
+                    name = name
+                )
+                .unwrap();
+            }
+            body.push_str("</table>\n");
+        }
+
+        let directives: Vec<&DirectiveDefinition<'a, T>> = self
+            .schema
+            .document()
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::DirectiveDefinition(directive) => Some(directive),
+                _ => None,
+            })
+            .collect();
+        if !directives.is_empty() {
+            body.push_str("<h2>Directives</h2>\n<table>\n<tr><th>Directive</th><th>Arguments</th><th>Locations</th><th>Description</th></tr>\n");
+            for directive in directives {
+                let locations: Vec<&str> = directive.locations.iter().map(|l| l.as_str()).collect();
+                writeln!(
+                    body,
+                    "<tr id=\"directive-{name}\"><td><code>@{name}</code>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    if directive.repeatable { " repeatable" } else { "" },
+                    self.arguments(&directive.arguments, "types/"),
+                    locations.join(", "),
+                    paragraphs(&directive.description),
+                    name = directive.name.as_ref()
+                )
+                .unwrap();
+            }
+            body.push_str("</table>\n");
+        }
+
+        self.page("Index", "", &body)
+    }
+
+    fn type_page(&self, type_definition: &TypeDefinition<'a, T>) -> String {
+        let name = type_definition_name(type_definition);
+        let mut body = format!(
+            "<h1><code>{}</code> <span class=\"kind\">{}</span></h1>\n{}",
+            name,
+            kind_name(type_definition),
+            paragraphs(type_description(type_definition))
+        );
+
+        match type_definition {
+            TypeDefinition::Scalar(_) => {}
+            TypeDefinition::Object(object) => {
+                self.type_list(&mut body, "Implements", &object.implements_interfaces);
+                self.fields(&mut body, &object.fields);
+            }
+            TypeDefinition::Interface(interface) => {
+                self.type_list(&mut body, "Implements", &interface.implements_interfaces);
+                self.fields(&mut body, &interface.fields);
+                let implementations: Vec<&str> = self
+                    .schema
+                    .types()
+                    .filter_map(|type_definition| match type_definition {
+                        TypeDefinition::Object(object)
+                            if object.implements_interfaces.contains(&interface.name) =>
+                        {
+                            Some(object.name.as_ref())
+                        }
+                        TypeDefinition::Interface(other)
+                            if other.implements_interfaces.contains(&interface.name) =>
+                        {
+                            Some(other.name.as_ref())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                self.type_list(&mut body, "Implemented by", &implementations);
+            }
+            TypeDefinition::Union(union) => {
+                self.type_list(&mut body, "Possible types", &union.types);
+            }
+            TypeDefinition::Enum(enum_type) => {
+                body.push_str(
+                    "<h2>Values</h2>\n<table>\n<tr><th>Value</th><th>Description</th></tr>\n",
+                );
+                for value in &enum_type.values {
+                    writeln!(
+                        body,
+                        "<tr id=\"value-{name}\"><td><code>{name}</code>{}</td><td>{}</td></tr>",
+                        deprecated_badge(&value.directives),
+                        deprecation_reason(&value.directives) + &paragraphs(&value.description),
+                        name = value.name.as_ref()
+                    )
+                    .unwrap();
+                }
+                body.push_str("</table>\n");
+            }
+            TypeDefinition::InputObject(input_object) => {
+                body.push_str("<h2>Input fields</h2>\n<table>\n<tr><th>Name</th><th>Type</th><th>Default</th><th>Description</th></tr>\n");
+                for input_field in &input_object.fields {
+                    writeln!(
+                        body,
+                        "<tr id=\"field-{name}\"><td><code>{name}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        self.type_link(&input_field.value_type, ""),
+                        default_value(input_field),
+                        paragraphs(&input_field.description),
+                        name = input_field.name.as_ref()
+                    )
+                    .unwrap();
+                }
+                body.push_str("</table>\n");
+            }
+        }
+
+        if let Some(used_by) = self.used_by.get(name) {
+            body.push_str("<h2>Used by</h2>\n<ul>\n");
+            for (coordinate, link) in used_by {
+                writeln!(
+                    body,
+                    "<li><a href=\"{}\"><code>{}</code></a></li>",
+                    link, coordinate
+                )
+                .unwrap();
+            }
+            body.push_str("</ul>\n");
+        }
+
+        self.page(name, "../", &body)
+    }
+
+    fn fields(&self, body: &mut String, fields: &[Field<'a, T>]) {
+        body.push_str(
+            "<h2>Fields</h2>\n<table>\n<tr><th>Name</th><th>Type</th><th>Description</th></tr>\n",
+        );
+        for field in fields {
+            writeln!(
+                body,
+                "<tr id=\"field-{name}\"><td><code>{name}</code>{}{}</td><td>{}</td><td>{}</td></tr>",
+                deprecated_badge(&field.directives),
+                self.arguments(&field.arguments, ""),
+                self.type_link(&field.field_type, ""),
+                deprecation_reason(&field.directives) + &paragraphs(&field.description),
+                name = field.name.as_ref()
+            )
+            .unwrap();
+        }
+        body.push_str("</table>\n");
+    }
+
+    /// A list of arguments with their types and defaults, `prefix` leads from the page to the
+    /// `types` directory
+    fn arguments(&self, arguments: &[InputValue<'a, T>], prefix: &str) -> String {
+        if arguments.is_empty() {
+            return String::new();
+        }
+
+        let mut out = String::from("<ul class=\"arguments\">");
+        for argument in arguments {
+            write!(
+                out,
+                "<li><code>{}</code>: {}",
+                argument.name.as_ref(),
+                self.type_link(&argument.value_type, prefix)
+            )
+            .unwrap();
+            if argument.default_value.is_some() {
+                write!(out, " = {}", default_value(argument)).unwrap();
+            }
+            if let Some(description) = &argument.description {
+                write!(out, " {}", escape(description)).unwrap();
+            }
+            out.push_str("</li>");
+        }
+        out.push_str("</ul>");
+
+        out
+    }
+
+    fn type_list<V: AsRef<str>>(&self, body: &mut String, heading: &str, names: &[V]) {
+        if names.is_empty() {
+            return;
+        }
+
+        writeln!(body, "<h2>{}</h2>\n<ul>", heading).unwrap();
+        for name in names {
+            writeln!(body, "<li>{}</li>", self.name_link(name.as_ref(), "")).unwrap();
+        }
+        body.push_str("</ul>\n");
+    }
+
+    /// The type as written in SDL, with the named type linking to its page
+    fn type_link(&self, value_type: &Type<'a, T>, prefix: &str) -> String {
+        match value_type {
+            Type::NamedType(name) => self.name_link(name.as_ref(), prefix),
+            Type::ListType(inner) => format!("[{}]", self.type_link(inner, prefix)),
+            Type::NonNullType(inner) => format!("{}!", self.type_link(inner, prefix)),
+        }
+    }
+
+    fn name_link(&self, name: &str, prefix: &str) -> String {
+        if self.schema.get_type(name).is_some() && !name.starts_with("__") {
+            format!(
+                "<a href=\"{}{name}.html\"><code>{name}</code></a>",
+                prefix,
+                name = name
+            )
+        } else {
+            format!("<code>{}</code>", name)
+        }
+    }
+
+    fn search_index(&self) -> serde_json::Value {
+        let mut entries = vec![];
+
+        for type_definition in documented_types(self.schema) {
+            let name = type_definition_name(type_definition);
+            entries.push(json!({
+                "name": name,
+                "kind": kind_name(type_definition),
+                "url": format!("types/{}.html", name),
+            }));
+
+            let members: Vec<(&str, &str)> = match type_definition {
+                TypeDefinition::Object(object) => object
+                    .fields
+                    .iter()
+                    .map(|f| (f.name.as_ref(), "field"))
+                    .collect(),
+                TypeDefinition::Interface(interface) => interface
+                    .fields
+                    .iter()
+                    .map(|f| (f.name.as_ref(), "field"))
+                    .collect(),
+                TypeDefinition::InputObject(input_object) => input_object
+                    .fields
+                    .iter()
+                    .map(|f| (f.name.as_ref(), "input field"))
+                    .collect(),
+                TypeDefinition::Enum(enum_type) => enum_type
+                    .values
+                    .iter()
+                    .map(|v| (v.name.as_ref(), "enum value"))
+                    .collect(),
+                TypeDefinition::Scalar(_) | TypeDefinition::Union(_) => vec![],
+            };
+            for (member, kind) in members {
+                let anchor = if kind == "enum value" {
+                    "value"
+                } else {
+                    "field"
+                };
+                entries.push(json!({
+                    "name": format!("{}.{}", name, member),
+                    "kind": kind,
+                    "url": format!("types/{}.html#{}-{}", name, anchor, member),
+                }));
+            }
+        }
+
+        serde_json::Value::Array(entries)
+    }
+}
+
+fn documented_types<'s, 'a, T: Text<'a>>(
+    schema: &'s Schema<'a, T>,
+) -> impl Iterator<Item = &'s TypeDefinition<'a, T>> {
+    schema
+        .types()
+        .filter(|type_definition| !type_definition_name(type_definition).starts_with("__"))
+}
+
+/// What refers to each type, for the "Used by" section of its page
+fn used_by<'s, 'a, T: Text<'a>>(
+    schema: &'s Schema<'a, T>,
+) -> HashMap<&'s str, Vec<(String, String)>> {
+    let mut used_by: HashMap<&str, Vec<(String, String)>> = HashMap::new();
+
+    for type_definition in documented_types(schema) {
+        let name = type_definition_name(type_definition);
+        let mut add = |used: &'s str, coordinate: String, anchor: String| {
+            let link = format!("{}.html{}", name, anchor);
+            let references = used_by.entry(used).or_default();
+            if !references.iter().any(|(c, _)| *c == coordinate) {
+                references.push((coordinate, link));
+            }
+        };
+
+        let fields = match type_definition {
+            TypeDefinition::Object(object) => &object.fields[..],
+            TypeDefinition::Interface(interface) => &interface.fields[..],
+            TypeDefinition::Union(union) => {
+                for member in &union.types {
+                    add(member.as_ref(), name.to_string(), String::new());
+                }
+                &[]
+            }
+            TypeDefinition::InputObject(input_object) => {
+                for input_field in &input_object.fields {
+                    add(
+                        named_type(&input_field.value_type),
+                        format!("{}.{}", name, input_field.name.as_ref()),
+                        format!("#field-{}", input_field.name.as_ref()),
+                    );
+                }
+                &[]
+            }
+            TypeDefinition::Scalar(_) | TypeDefinition::Enum(_) => &[],
+        };
+        for field in fields {
+            let field_name = field.name.as_ref();
+            add(
+                named_type(&field.field_type),
+                format!("{}.{}", name, field_name),
+                format!("#field-{}", field_name),
+            );
+            for argument in &field.arguments {
+                add(
+                    named_type(&argument.value_type),
+                    format!("{}.{}({}:)", name, field_name, argument.name.as_ref()),
+                    format!("#field-{}", field_name),
+                );
+            }
+        }
+    }
+
+    used_by
+}
+
+fn deprecated_badge<'a, T: Text<'a>>(directives: &[Directive<'a, T>]) -> String {
+    match deprecation(directives) {
+        Some(_) => " <span class=\"deprecated\">deprecated</span>".to_string(),
+        None => String::new(),
+    }
+}
+
+fn deprecation_reason<'a, T: Text<'a>>(directives: &[Directive<'a, T>]) -> String {
+    match deprecation(directives).and_then(|deprecation| deprecation.reason) {
+        Some(reason) => format!(
+            "<p class=\"deprecation-reason\">Deprecated: {}</p>\n",
+            escape(reason)
+        ),
+        None => String::new(),
+    }
+}
+
+fn default_value<'a, T: Text<'a>>(input_value: &InputValue<'a, T>) -> String {
+    default_value_source(input_value)
+        .map(|value| format!("<code>{}</code>", escape(&value)))
+        .unwrap_or_default()
+}
+
+/// The first line of a description
+fn summary(description: &Option<String>) -> String {
+    description
+        .as_deref()
+        .and_then(|description| description.lines().next())
+        .map(escape)
+        .unwrap_or_default()
+}
+
+/// A description with each blank-line separated block as a paragraph
+fn paragraphs(description: &Option<String>) -> String {
+    description
+        .as_deref()
+        .map(|description| {
+            description
+                .split("\n\n")
+                .filter(|paragraph| !paragraph.trim().is_empty())
+                .map(|paragraph| format!("<p>{}</p>\n", escape(paragraph.trim())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        interface Node { id: ID! }
+
+        "Someone using the app"
+        type User implements Node {
+            id: ID!
+            login: String @deprecated(reason: "Use `handle`")
+            handle: String
+            role: Role
+        }
+
+        enum Role { ADMIN MEMBER }
+
+        input UserFilter { role: Role = MEMBER }
+
+        directive @cached(ttl: Int) on FIELD
+
+        type Query { node(id: ID!): Node users(filter: UserFilter): [User!]! }
+    "#;
+
+    fn site() -> Vec<HtmlFile> {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap());
+        generate(&schema, &HtmlOptions::default())
+    }
+
+    fn contents<'f>(files: &'f [HtmlFile], path: &str) -> &'f str {
+        &files
+            .iter()
+            .find(|file| file.path == path)
+            .unwrap_or_else(|| panic!("{} wasn't generated", path))
+            .contents
+    }
+
+    #[test]
+    fn relative_links_resolve() {
+        let files = site();
+
+        for file in &files {
+            let directory = file.path.rfind('/').map_or("", |end| &file.path[..=end]);
+            for reference in file
+                .contents
+                .split(" href=\"")
+                .skip(1)
+                .chain(file.contents.split(" src=\"").skip(1))
+            {
+                let link = &reference[..reference.find('"').unwrap()];
+                let (path, anchor) = match link.find('#') {
+                    Some(hash) => (&link[..hash], Some(&link[hash + 1..])),
+                    None => (link, None),
+                };
+
+                let mut resolved: Vec<&str> =
+                    directory.split('/').filter(|s| !s.is_empty()).collect();
+                for segment in path.split('/') {
+                    match segment {
+                        ".." => {
+                            resolved.pop();
+                        }
+                        segment => resolved.push(segment),
+                    }
+                }
+                let target = contents(&files, &resolved.join("/"));
+                if let Some(anchor) = anchor {
+                    assert!(
+                        target.contains(&format!("id=\"{}\"", anchor)),
+                        "{} links to a missing anchor {}",
+                        file.path,
+                        link
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lists_uses_and_implementations() {
+        let files = site();
+
+        let role = contents(&files, "types/Role.html");
+        assert!(role.contains(
+            "<h2>Used by</h2>\n<ul>\n\
+             <li><a href=\"User.html#field-role\"><code>User.role</code></a></li>\n\
+             <li><a href=\"UserFilter.html#field-role\"><code>UserFilter.role</code></a></li>\n\
+             </ul>\n"
+        ));
+        let node = contents(&files, "types/Node.html");
+        assert!(node.contains(
+            "<h2>Implemented by</h2>\n<ul>\n\
+             <li><a href=\"User.html\"><code>User</code></a></li>\n</ul>\n"
+        ));
+    }
+
+    #[test]
+    fn marks_deprecated_fields() {
+        let files = site();
+        let user = contents(&files, "types/User.html");
+
+        assert!(user.contains(
+            "<tr id=\"field-login\"><td><code>login</code> \
+             <span class=\"deprecated\">deprecated</span></td>"
+        ));
+        assert!(user.contains("<p class=\"deprecation-reason\">Deprecated: Use `handle`</p>"));
+        assert_eq!(user.matches("class=\"deprecated\"").count(), 1);
+    }
+
+    #[test]
+    fn indexes_fields_and_enum_values_for_search() {
+        let files = site();
+        let script = contents(&files, "search.js");
+        let index = &script["window.SEARCH_INDEX = ".len()..script.find(";\n").unwrap()];
+        let entries: Vec<serde_json::Value> = serde_json::from_str(index).unwrap();
+
+        assert!(entries.contains(&json!({
+            "name": "User.handle",
+            "kind": "field",
+            "url": "types/User.html#field-handle",
+        })));
+        assert!(entries.contains(&json!({
+            "name": "Role.ADMIN",
+            "kind": "enum value",
+            "url": "types/Role.html#value-ADMIN",
+        })));
+        assert!(entries.contains(&json!({
+            "name": "UserFilter.role",
+            "kind": "input field",
+            "url": "types/UserFilter.html#field-role",
+        })));
+    }
+}
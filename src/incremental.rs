@@ -0,0 +1,286 @@
+use crate::schema::{type_definition_name, OperationKind};
+use crate::{DeserializeWith, ResponseContainer, SchemaFields};
+use graphql_parser::schema::{Definition, DirectiveDefinition, Document, TypeDefinition};
+use serde_json::value::RawValue;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// A parsed schema that remembers a hash of each type's raw JSON, so parsing a new
+/// introspection response of the same server only rebuilds the types that changed
+#[derive(Debug, Clone)]
+pub struct IncrementalSchema {
+    document: Document<'static, String>,
+    /// The hash of the raw JSON each type in `document` was built from, in the same order
+    hashes: Vec<u64>,
+}
+
+/// What changed between two introspection responses, types and directives by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    /// Root operation types that were added, removed or now name another type
+    pub root_types: Vec<OperationKind>,
+    /// Directives that were added, removed or defined differently
+    pub directives: Vec<String>,
+}
+
+impl SchemaChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.root_types.is_empty()
+            && self.directives.is_empty()
+    }
+}
+
+impl IncrementalSchema {
+    pub fn parse(raw_introspection: &str) -> serde_json::Result<Self> {
+        let mut schema = IncrementalSchema {
+            document: Document {
+                definitions: vec![],
+            },
+            hashes: vec![],
+        };
+        schema.update(raw_introspection)?;
+
+        Ok(schema)
+    }
+
+    pub fn document(&self) -> &Document<'static, String> {
+        &self.document
+    }
+
+    pub fn into_document(self) -> Document<'static, String> {
+        self.document
+    }
+
+    /// Replace the schema with a new introspection response, reusing every type whose raw JSON
+    /// is unchanged. Types that are rebuilt but come out equal, ie: only their whitespace
+    /// changed, aren't reported as modified.
+    ///
+    /// The schema is left as it was if the response fails to parse.
+    pub fn update(&mut self, raw_introspection: &str) -> serde_json::Result<SchemaChanges> {
+        let fields =
+            serde_json::from_str::<ResponseContainer<SchemaFields<String, Vec<&RawValue>>>>(
+                raw_introspection,
+            )?
+            .data
+            .schema;
+
+        let previous_types: Vec<&TypeDefinition<String>> = self
+            .document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::TypeDefinition(type_definition) => Some(type_definition),
+                _ => None,
+            })
+            .collect();
+        let previous_by_hash: HashMap<u64, &TypeDefinition<String>> = self
+            .hashes
+            .iter()
+            .copied()
+            .zip(previous_types.iter().copied())
+            .collect();
+        let previous_by_name: HashMap<&str, &TypeDefinition<String>> = previous_types
+            .iter()
+            .map(|type_definition| (type_definition_name(type_definition), *type_definition))
+            .collect();
+
+        let mut changes = SchemaChanges::default();
+        let mut types = Vec::with_capacity(fields.types.len());
+        let mut hashes = Vec::with_capacity(fields.types.len());
+
+        for raw_type in &fields.types {
+            let type_hash = hash(raw_type.get());
+            hashes.push(type_hash);
+
+            if let Some(type_definition) = previous_by_hash.get(&type_hash) {
+                types.push((*type_definition).clone());
+                continue;
+            }
+
+            let type_definition =
+                serde_json::from_str::<DeserializeWith<TypeDefinition<String>>>(raw_type.get())?
+                    .value;
+            let name = type_definition_name(&type_definition);
+            match previous_by_name.get(name) {
+                Some(previous) if **previous == type_definition => {}
+                Some(_) => changes.modified.push(name.to_string()),
+                None => changes.added.push(name.to_string()),
+            }
+            types.push(type_definition);
+        }
+
+        let names: HashSet<&str> = types.iter().map(type_definition_name).collect();
+        changes.removed = previous_types
+            .iter()
+            .map(|type_definition| type_definition_name(type_definition))
+            .filter(|name| !names.contains(name))
+            .map(str::to_string)
+            .collect();
+
+        let document = fields.root_types.into_document(types, fields.directives);
+        changes.root_types = root_type_changes(&self.document, &document);
+        changes.directives = directive_changes(&self.document, &document);
+        self.document = document;
+        self.hashes = hashes;
+
+        Ok(changes)
+    }
+}
+
+fn root_type_changes(
+    previous: &Document<'static, String>,
+    current: &Document<'static, String>,
+) -> Vec<OperationKind> {
+    let previous = root_type_names(previous);
+    let current = root_type_names(current);
+
+    [
+        OperationKind::Query,
+        OperationKind::Mutation,
+        OperationKind::Subscription,
+    ]
+    .iter()
+    .zip(previous.iter().zip(current.iter()))
+    .filter(|(_, (previous, current))| previous != current)
+    .map(|(kind, _)| *kind)
+    .collect()
+}
+
+/// The query, mutation and subscription type names
+fn root_type_names<'d>(document: &'d Document<'static, String>) -> [Option<&'d str>; 3] {
+    document
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::SchemaDefinition(schema) => Some([
+                schema.query.as_deref(),
+                schema.mutation.as_deref(),
+                schema.subscription.as_deref(),
+            ]),
+            _ => None,
+        })
+        .unwrap_or([None, None, None])
+}
+
+fn directive_changes(
+    previous: &Document<'static, String>,
+    current: &Document<'static, String>,
+) -> Vec<String> {
+    let previous = directive_definitions(previous);
+    let current = directive_definitions(current);
+
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|(name, directive)| previous.get(*name) != Some(*directive))
+        .map(|(name, _)| name.to_string())
+        .collect();
+    changed.extend(
+        previous
+            .keys()
+            .filter(|name| !current.contains_key(*name))
+            .map(|name| name.to_string()),
+    );
+    changed.sort();
+
+    changed
+}
+
+fn directive_definitions<'d>(
+    document: &'d Document<'static, String>,
+) -> HashMap<&'d str, &'d DirectiveDefinition<'static, String>> {
+    document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::DirectiveDefinition(directive) => {
+                Some((directive.name.as_str(), directive))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn hash(raw_type: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    raw_type.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::to_introspection;
+    use graphql_parser::parse_schema;
+    use serde_json::json;
+
+    fn introspection(sdl: &str) -> String {
+        json!({ "data": to_introspection(&parse_schema::<String>(sdl).unwrap()) }).to_string()
+    }
+
+    const BEFORE: &str = r#"
+        schema { query: Query }
+        type Query { user: User team: Team }
+        type User { name: String }
+        type Team { size: Int }
+        directive @cached(ttl: Int) on FIELD
+    "#;
+
+    #[test]
+    fn matches_parsing_from_scratch() {
+        let schema = IncrementalSchema::parse(&introspection(BEFORE)).unwrap();
+        let parsed = crate::parse::<String>(&introspection(BEFORE)).unwrap();
+
+        assert_eq!(schema.document().to_string(), parsed.to_string());
+    }
+
+    #[test]
+    fn reports_added_removed_and_modified_types() {
+        let mut schema = IncrementalSchema::parse(&introspection(BEFORE)).unwrap();
+
+        let changes = schema
+            .update(&introspection(
+                r#"
+                schema { query: Query }
+                type Query { user: User post: Post }
+                type User { name: String email: String }
+                type Post { title: String }
+                directive @cached(ttl: Int) on FIELD
+                "#,
+            ))
+            .unwrap();
+        assert_eq!(changes.added, vec!["Post"]);
+        assert_eq!(changes.removed, vec!["Team"]);
+        assert_eq!(changes.modified, vec!["Query", "User"]);
+        assert!(changes.root_types.is_empty() && changes.directives.is_empty());
+    }
+
+    #[test]
+    fn reports_root_type_and_directive_changes() {
+        let mut schema = IncrementalSchema::parse(&introspection(BEFORE)).unwrap();
+        assert!(schema.update(&introspection(BEFORE)).unwrap().is_empty());
+
+        let changes = schema
+            .update(&introspection(
+                r#"
+                schema { query: Query mutation: Query }
+                type Query { user: User team: Team }
+                type User { name: String }
+                type Team { size: Int }
+                directive @cached(ttl: String) on FIELD
+                directive @live on QUERY
+                "#,
+            ))
+            .unwrap();
+        assert!(!changes.is_empty());
+        assert_eq!(changes.root_types, vec![OperationKind::Mutation]);
+        assert_eq!(changes.directives, vec!["cached", "live"]);
+        assert!(changes.added.is_empty() && changes.modified.is_empty());
+    }
+}
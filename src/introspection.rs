@@ -0,0 +1,572 @@
+use crate::mock::MockValue;
+use crate::schema::{
+    deprecation, is_included, type_definition_name, OperationKind, TYPENAME_FIELD,
+};
+use graphql_parser::query::{
+    self, FragmentDefinition, Selection, SelectionSet, TypeCondition, Value,
+};
+use graphql_parser::schema::{
+    Definition, DirectiveDefinition, DirectiveLocation, Document, EnumValue, Field, InputValue,
+    Text, Type, TypeDefinition,
+};
+use indexmap::IndexMap;
+use serde_json::{self as json, json, Map};
+use std::collections::HashMap;
+
+pub const SCHEMA_FIELD: &str = "__schema";
+pub const TYPE_FIELD: &str = "__type";
+/// Introspection fields with an `includeDeprecated` argument
+const DEPRECATABLE_FIELDS: &[&str] = &["fields", "enumValues", "args", "inputFields"];
+
+/// Turn a `Document` back into the `{"__schema": ...}` object an introspection query returns.
+///
+/// This is the inverse of `parse` and the output includes every field the introspection query
+/// in the Readme asks for, so `parse` can read it straight back in.
+pub fn to_introspection<'a, T: Text<'a>>(document: &Document<'a, T>) -> json::Value {
+    let kinds: HashMap<&str, &str> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(type_definition) => Some((
+                type_definition_name(type_definition),
+                type_kind(type_definition),
+            )),
+            _ => None,
+        })
+        .collect();
+    let writer = Writer { kinds };
+
+    let schema_definition = document
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::SchemaDefinition(schema_definition) => Some(schema_definition),
+            _ => None,
+        });
+    // without a schema definition the roots are the types with the default names
+    let root_type = |kind: OperationKind| {
+        match schema_definition {
+            Some(schema_definition) => match kind {
+                OperationKind::Query => &schema_definition.query,
+                OperationKind::Mutation => &schema_definition.mutation,
+                OperationKind::Subscription => &schema_definition.subscription,
+            }
+            .as_ref()
+            .map(AsRef::as_ref),
+            None => Some(kind.default_type_name()).filter(|name| writer.kinds.contains_key(name)),
+        }
+        .map(|name| json!({ "name": name }))
+        .unwrap_or(json::Value::Null)
+    };
+
+    let types: Vec<json::Value> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(type_definition) => {
+                Some(writer.type_definition(type_definition, document))
+            }
+            _ => None,
+        })
+        .collect();
+    let directives: Vec<json::Value> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::DirectiveDefinition(directive) => Some(writer.directive(directive)),
+            _ => None,
+        })
+        .collect();
+
+    json!({
+        SCHEMA_FIELD: {
+            "queryType": root_type(OperationKind::Query),
+            "mutationType": root_type(OperationKind::Mutation),
+            "subscriptionType": root_type(OperationKind::Subscription),
+            "types": types,
+            "directives": directives,
+        }
+    })
+}
+
+struct Writer<'w> {
+    kinds: HashMap<&'w str, &'static str>,
+}
+
+impl<'w> Writer<'w> {
+    fn type_definition<'a, T: Text<'a>>(
+        &self,
+        type_definition: &TypeDefinition<'a, T>,
+        document: &Document<'a, T>,
+    ) -> json::Value {
+        let mut result = json!({
+            "kind": type_kind(type_definition),
+            "name": type_definition_name(type_definition),
+            "description": null,
+            "fields": null,
+            "inputFields": null,
+            "interfaces": null,
+            "enumValues": null,
+            "possibleTypes": null,
+        });
+
+        match type_definition {
+            TypeDefinition::Scalar(scalar) => {
+                result["description"] = json!(scalar.description);
+            }
+            TypeDefinition::Object(object) => {
+                result["description"] = json!(object.description);
+                result["fields"] = self.fields(&object.fields);
+                result["interfaces"] = self.named_types(&object.implements_interfaces);
+            }
+            TypeDefinition::Interface(interface) => {
+                result["description"] = json!(interface.description);
+                result["fields"] = self.fields(&interface.fields);
+                result["interfaces"] = self.named_types(&interface.implements_interfaces);
+
+                let implementations: Vec<T::Value> = document
+                    .definitions
+                    .iter()
+                    .filter_map(|definition| match definition {
+                        Definition::TypeDefinition(TypeDefinition::Object(object))
+                            if object.implements_interfaces.contains(&interface.name) =>
+                        {
+                            Some(object.name.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                result["possibleTypes"] = self.named_types(&implementations);
+            }
+            TypeDefinition::Union(union) => {
+                result["description"] = json!(union.description);
+                result["possibleTypes"] = self.named_types(&union.types);
+            }
+            TypeDefinition::Enum(enum_type) => {
+                result["description"] = json!(enum_type.description);
+                result["enumValues"] = enum_type.values.iter().map(enum_value).collect();
+            }
+            TypeDefinition::InputObject(input_object) => {
+                result["description"] = json!(input_object.description);
+                result["inputFields"] = self.input_values(&input_object.fields);
+            }
+        }
+
+        result
+    }
+
+    fn directive<'a, T: Text<'a>>(&self, directive: &DirectiveDefinition<'a, T>) -> json::Value {
+        json!({
+            "name": directive.name.as_ref(),
+            "description": directive.description,
+            "locations": directive
+                .locations
+                .iter()
+                .map(DirectiveLocation::as_str)
+                .collect::<Vec<_>>(),
+            "args": self.input_values(&directive.arguments),
+            "isRepeatable": directive.repeatable,
+        })
+    }
+
+    fn fields<'a, T: Text<'a>>(&self, fields: &[Field<'a, T>]) -> json::Value {
+        fields
+            .iter()
+            .map(|field| {
+                json!({
+                    "name": field.name.as_ref(),
+                    "description": field.description,
+                    "args": self.input_values(&field.arguments),
+                    "type": self.type_ref(&field.field_type),
+                    "isDeprecated": deprecation(&field.directives).is_some(),
+                    "deprecationReason": deprecation(&field.directives).and_then(|d| d.reason),
+                })
+            })
+            .collect()
+    }
+
+    fn input_values<'a, T: Text<'a>>(&self, input_values: &[InputValue<'a, T>]) -> json::Value {
+        input_values
+            .iter()
+            .map(|input_value| {
+                json!({
+                    "name": input_value.name.as_ref(),
+                    "description": input_value.description,
+                    "type": self.type_ref(&input_value.value_type),
+                    "defaultValue": input_value
+                        .default_value
+                        .as_ref()
+                        .map(|value| value.to_string()),
+                    "isDeprecated": deprecation(&input_value.directives).is_some(),
+                    "deprecationReason": deprecation(&input_value.directives).and_then(|d| d.reason),
+                })
+            })
+            .collect()
+    }
+
+    fn named_types<V: AsRef<str>>(&self, names: &[V]) -> json::Value {
+        names
+            .iter()
+            .map(|name| self.named_type(name.as_ref()))
+            .collect()
+    }
+
+    fn named_type(&self, name: &str) -> json::Value {
+        json!({
+            "kind": self.kinds.get(name).cloned().unwrap_or("SCALAR"),
+            "name": name,
+            "ofType": null,
+        })
+    }
+
+    fn type_ref<'a, T: Text<'a>>(&self, value_type: &Type<'a, T>) -> json::Value {
+        match value_type {
+            Type::NamedType(name) => self.named_type(name.as_ref()),
+            Type::ListType(inner) => json!({
+                "kind": "LIST",
+                "name": null,
+                "ofType": self.type_ref(inner),
+            }),
+            Type::NonNullType(inner) => json!({
+                "kind": "NON_NULL",
+                "name": null,
+                "ofType": self.type_ref(inner),
+            }),
+        }
+    }
+}
+
+fn enum_value<'a, T: Text<'a>>(value: &EnumValue<'a, T>) -> json::Value {
+    json!({
+        "name": value.name.as_ref(),
+        "description": value.description,
+        "isDeprecated": deprecation(&value.directives).is_some(),
+        "deprecationReason": deprecation(&value.directives).and_then(|d| d.reason),
+    })
+}
+
+fn type_kind<'a, T: Text<'a>>(type_definition: &TypeDefinition<'a, T>) -> &'static str {
+    match type_definition {
+        TypeDefinition::Scalar(_) => "SCALAR",
+        TypeDefinition::Object(_) => "OBJECT",
+        TypeDefinition::Interface(_) => "INTERFACE",
+        TypeDefinition::Union(_) => "UNION",
+        TypeDefinition::Enum(_) => "ENUM",
+        TypeDefinition::InputObject(_) => "INPUT_OBJECT",
+    }
+}
+
+/// Answer a `__schema` or `__type(name:)` root field by picking the selected fields out of
+/// `introspection`, the schema's `to_introspection` JSON. Deprecated fields, enum values,
+/// arguments and input fields are left out unless selected with `includeDeprecated: true`.
+/// Fields come back in the order they're selected. Returns `None` for any other field.
+pub fn resolve_root_field<'q, Q: Text<'q>>(
+    introspection: &json::Value,
+    field_name: &str,
+    type_name_argument: Option<&str>,
+    selection_sets: &[&SelectionSet<'q, Q>],
+    fragments: &HashMap<&str, &FragmentDefinition<'q, Q>>,
+    variables: &Map<String, json::Value>,
+) -> Option<MockValue> {
+    let schema_json = &introspection[SCHEMA_FIELD];
+    let selector = Selector {
+        fragments,
+        variables,
+    };
+
+    match field_name {
+        SCHEMA_FIELD => Some(selector.select(schema_json, "__Schema", selection_sets)),
+        TYPE_FIELD => {
+            let found = schema_json["types"]
+                .as_array()
+                .and_then(|types| {
+                    types
+                        .iter()
+                        .find(|t| t["name"].as_str() == type_name_argument)
+                })
+                .map(|type_json| selector.select(type_json, "__Type", selection_sets));
+
+            Some(found.unwrap_or(MockValue::Null))
+        }
+        _ => None,
+    }
+}
+
+/// A field selected on an introspection type, merged across fragments
+type SelectedField<'c, 'q, Q> = (
+    &'c str,
+    &'c query::Field<'q, Q>,
+    Vec<&'c SelectionSet<'q, Q>>,
+);
+
+struct Selector<'s, 'f, 'q, Q: Text<'q>> {
+    fragments: &'f HashMap<&'s str, &'s FragmentDefinition<'q, Q>>,
+    /// Used to resolve `includeDeprecated`, `@skip` and `@include`
+    variables: &'f Map<String, json::Value>,
+}
+
+impl<'s, 'f, 'q, Q: Text<'q>> Selector<'s, 'f, 'q, Q> {
+    /// Project a selection onto introspection JSON, `type_name` is the introspection type
+    /// (`__Type`, `__Field`, ...) of `value`
+    fn select(
+        &self,
+        value: &json::Value,
+        type_name: &str,
+        selection_sets: &[&SelectionSet<'q, Q>],
+    ) -> MockValue {
+        match value {
+            json::Value::Array(items) => MockValue::List(
+                items
+                    .iter()
+                    .map(|item| self.select(item, type_name, selection_sets))
+                    .collect(),
+            ),
+            json::Value::Object(object) => {
+                let mut fields: Vec<SelectedField<Q>> = vec![];
+                for selection_set in selection_sets {
+                    self.collect_fields(type_name, selection_set, &mut fields);
+                }
+
+                let mut result = IndexMap::new();
+                for (response_key, field, selection_sets) in fields {
+                    let name = field.name.as_ref();
+                    let value = if name == TYPENAME_FIELD {
+                        MockValue::String(type_name.to_string())
+                    } else {
+                        let child_type = introspection_type(name).unwrap_or(type_name);
+                        let value = match object.get(name) {
+                            Some(json::Value::Array(items))
+                                if DEPRECATABLE_FIELDS.contains(&name)
+                                    && !self.include_deprecated(field) =>
+                            {
+                                items
+                                    .iter()
+                                    .filter(|item| item["isDeprecated"] != json::Value::Bool(true))
+                                    .cloned()
+                                    .collect()
+                            }
+                            Some(value) => value.clone(),
+                            None => json::Value::Null,
+                        };
+
+                        self.select(&value, child_type, &selection_sets)
+                    };
+
+                    result.insert(response_key.to_string(), value);
+                }
+
+                MockValue::Object(result)
+            }
+            other => other.clone().into(),
+        }
+    }
+
+    /// The `includeDeprecated` argument of a field, `false` when it's left out
+    fn include_deprecated(&self, field: &query::Field<'q, Q>) -> bool {
+        let argument = field
+            .arguments
+            .iter()
+            .find(|(name, _)| name.as_ref() == "includeDeprecated");
+
+        match argument {
+            Some((_, Value::Boolean(include))) => *include,
+            Some((_, Value::Variable(name))) => {
+                self.variables.get(name.as_ref()) == Some(&json!(true))
+            }
+            _ => false,
+        }
+    }
+
+    fn collect_fields<'c>(
+        &self,
+        type_name: &str,
+        selection_set: &'c SelectionSet<'q, Q>,
+        fields: &mut Vec<SelectedField<'c, 'q, Q>>,
+    ) where
+        's: 'c,
+    {
+        for selection in &selection_set.items {
+            let directives = match selection {
+                Selection::Field(field) => &field.directives,
+                Selection::InlineFragment(fragment) => &fragment.directives,
+                Selection::FragmentSpread(spread) => &spread.directives,
+            };
+            if !is_included(directives, self.variables) {
+                continue;
+            }
+
+            let (condition, selection_set) = match selection {
+                Selection::Field(field) => {
+                    let response_key = field.alias.as_ref().unwrap_or(&field.name).as_ref();
+                    match fields.iter_mut().find(|(key, _, _)| *key == response_key) {
+                        Some((_, _, selection_sets)) => selection_sets.push(&field.selection_set),
+                        None => fields.push((response_key, field, vec![&field.selection_set])),
+                    }
+                    continue;
+                }
+                Selection::InlineFragment(fragment) => (
+                    fragment
+                        .type_condition
+                        .as_ref()
+                        .map(|TypeCondition::On(condition)| condition.as_ref()),
+                    &fragment.selection_set,
+                ),
+                Selection::FragmentSpread(spread) => {
+                    match self.fragments.get(spread.fragment_name.as_ref()) {
+                        Some(fragment) => {
+                            let TypeCondition::On(condition) = &fragment.type_condition;
+                            (Some(condition.as_ref()), &fragment.selection_set)
+                        }
+                        None => continue,
+                    }
+                }
+            };
+
+            if condition.is_none() || condition == Some(type_name) {
+                self.collect_fields(type_name, selection_set, fields);
+            }
+        }
+    }
+}
+
+/// The introspection type each introspection field returns
+fn introspection_type(field_name: &str) -> Option<&'static str> {
+    match field_name {
+        "types" | "queryType" | "mutationType" | "subscriptionType" | "type" | "ofType"
+        | "interfaces" | "possibleTypes" => Some("__Type"),
+        "fields" => Some("__Field"),
+        "args" | "inputFields" => Some("__InputValue"),
+        "enumValues" => Some("__EnumValue"),
+        "directives" => Some("__Directive"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::{parse_query, parse_schema};
+
+    const SDL: &str = r#"
+        schema { query: Query }
+        type Query { user: User old: String @deprecated(reason: "use user") }
+        type User { name: String }
+        enum Role { ADMIN GUEST @deprecated }
+    "#;
+
+    fn resolve(operation: &str, variables: json::Value) -> json::Value {
+        let introspection = to_introspection(&parse_schema::<String>(SDL).unwrap());
+        let document = parse_query::<String>(operation).unwrap();
+        let fragments = crate::schema::fragments(&document.definitions);
+        let field = match &document.definitions[0] {
+            query::Definition::Operation(query::OperationDefinition::SelectionSet(set)) => {
+                match &set.items[0] {
+                    Selection::Field(field) => field,
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        };
+        let type_name = field.arguments.first().map(|(_, value)| match value {
+            Value::String(name) => name.as_str(),
+            _ => unreachable!(),
+        });
+
+        resolve_root_field(
+            &introspection,
+            &field.name,
+            type_name,
+            &[&field.selection_set],
+            &fragments,
+            variables.as_object().unwrap(),
+        )
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn round_trips_through_parse() {
+        let document = parse_schema::<String>(SDL).unwrap();
+        let introspection = json!({ "data": to_introspection(&document) }).to_string();
+        let parsed = crate::parse::<String>(&introspection).unwrap();
+
+        assert_eq!(
+            to_introspection(&parsed),
+            to_introspection(&document),
+            "parsing the introspection gives back the same schema"
+        );
+    }
+
+    #[test]
+    fn deprecated_members_need_include_deprecated() {
+        let names = |value: &json::Value| -> Vec<String> {
+            value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let query = resolve(
+            r#"{ __type(name: "Query") { fields { name } } }"#,
+            json!({}),
+        );
+        assert_eq!(names(&query["fields"]), vec!["user"]);
+
+        let query = resolve(
+            r#"{ __type(name: "Query") { fields(includeDeprecated: true) { name } } }"#,
+            json!({}),
+        );
+        assert_eq!(names(&query["fields"]), vec!["user", "old"]);
+
+        let role = resolve(
+            r#"{ __type(name: "Role") { enumValues(includeDeprecated: $all) { name } } }"#,
+            json!({ "all": true }),
+        );
+        assert_eq!(names(&role["enumValues"]), vec!["ADMIN", "GUEST"]);
+        let role = resolve(
+            r#"{ __type(name: "Role") { kind enumValues { name } } }"#,
+            json!({}),
+        );
+        assert_eq!(
+            role,
+            json!({ "kind": "ENUM", "enumValues": [{ "name": "ADMIN" }] })
+        );
+    }
+
+    #[test]
+    fn skip_and_include_apply_to_introspection() {
+        let query = resolve(
+            r#"{ __type(name: "User") { name @skip(if: true) kind ... @include(if: $all) { description } } }"#,
+            json!({ "all": false }),
+        );
+        assert_eq!(query, json!({ "kind": "OBJECT" }));
+
+        let query = resolve(
+            r#"{ __type(name: "User") { name @include(if: $all) } }"#,
+            json!({ "all": true }),
+        );
+        assert_eq!(query, json!({ "name": "User" }));
+    }
+
+    #[test]
+    fn roots_default_to_their_type_names_without_a_schema_definition() {
+        let document =
+            parse_schema::<String>("type Query { a: Int } type Mutation { b: Int }").unwrap();
+        let introspection = to_introspection(&document);
+
+        assert_eq!(
+            introspection[SCHEMA_FIELD]["queryType"],
+            json!({ "name": "Query" })
+        );
+        assert_eq!(
+            introspection[SCHEMA_FIELD]["mutationType"],
+            json!({ "name": "Mutation" })
+        );
+        assert_eq!(
+            introspection[SCHEMA_FIELD]["subscriptionType"],
+            json::Value::Null
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use crate::schema::{type_definition_name, BUILT_IN_SCALARS};
+use graphql_parser::schema::{
+    Definition, Document, Field, InputValue, Text, Type, TypeDefinition, Value,
+};
+use serde_json::{self as json, json, Map};
+use std::collections::HashMap;
+
+const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(Debug, Clone, Default)]
+pub struct JsonSchemaOptions {
+    /// Also export objects, interfaces and unions so responses can be validated
+    pub include_output_types: bool,
+    /// JSON Schemas for custom scalars, keyed by the GraphQL scalar name. Anything missing
+    /// accepts any value.
+    pub scalars: HashMap<String, json::Value>,
+}
+
+/// Convert the schema into a single JSON Schema (draft 2020-12) bundle with a `$defs` entry
+/// for each type.
+///
+/// Input objects, enums and scalars are always exported, including built-in scalars the document
+/// doesn't declare. References between types use
+/// `#/$defs/{name}`, so any definition can be pulled out with `$ref`.
+pub fn generate<'a, T: Text<'a>>(
+    document: &Document<'a, T>,
+    options: &JsonSchemaOptions,
+) -> json::Value {
+    let mut definitions = Map::new();
+
+    for definition in &document.definitions {
+        let type_definition = match definition {
+            Definition::TypeDefinition(type_definition) => type_definition,
+            _ => continue,
+        };
+        let name = type_definition_name(type_definition);
+        if name.starts_with("__") {
+            continue;
+        }
+
+        let (description, mut schema) = match type_definition {
+            TypeDefinition::Scalar(scalar) => (
+                &scalar.description,
+                scalar_schema(scalar.name.as_ref(), options),
+            ),
+            TypeDefinition::Enum(enum_type) => (
+                &enum_type.description,
+                json!({
+                    "type": "string",
+                    "enum": enum_type
+                        .values
+                        .iter()
+                        .map(|value| value.name.as_ref())
+                        .collect::<Vec<_>>(),
+                }),
+            ),
+            TypeDefinition::InputObject(input_object) => (
+                &input_object.description,
+                input_object_schema(&input_object.fields),
+            ),
+            TypeDefinition::Object(object) if options.include_output_types => {
+                let mut schema = object_schema(&object.fields);
+                schema["properties"]["__typename"] = json!({ "const": object.name.as_ref() });
+
+                (&object.description, schema)
+            }
+            TypeDefinition::Interface(interface) if options.include_output_types => {
+                (&interface.description, object_schema(&interface.fields))
+            }
+            TypeDefinition::Union(union) if options.include_output_types => (
+                &union.description,
+                json!({
+                    "anyOf": union
+                        .types
+                        .iter()
+                        .map(|name| type_ref(name.as_ref()))
+                        .collect::<Vec<_>>(),
+                }),
+            ),
+            _ => continue,
+        };
+
+        if let Some(description) = description {
+            schema["description"] = json::Value::String(description.clone());
+        }
+        definitions.insert(name.to_string(), schema);
+    }
+    for name in BUILT_IN_SCALARS {
+        if !definitions.contains_key(*name) {
+            definitions.insert(name.to_string(), scalar_schema(name, options));
+        }
+    }
+
+    json!({
+        "$schema": DRAFT_2020_12,
+        "$defs": definitions,
+    })
+}
+
+fn scalar_schema(name: &str, options: &JsonSchemaOptions) -> json::Value {
+    match name {
+        "String" => json!({ "type": "string" }),
+        // IDs are serialized as strings but servers accept integers as input
+        "ID" => json!({ "type": ["string", "integer"] }),
+        "Int" => json!({
+            "type": "integer",
+            "minimum": i32::MIN,
+            "maximum": i32::MAX,
+        }),
+        "Float" => json!({ "type": "number" }),
+        "Boolean" => json!({ "type": "boolean" }),
+        _ => options
+            .scalars
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| json!({})),
+    }
+}
+
+fn input_object_schema<'a, T: Text<'a>>(fields: &[InputValue<'a, T>]) -> json::Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+
+    for field in fields {
+        let mut schema = type_schema(&field.value_type, true);
+        if let Some(description) = &field.description {
+            schema["description"] = json::Value::String(description.clone());
+        }
+
+        // a default means the server fills the field in, even if it's non-null
+        match &field.default_value {
+            Some(default_value) => schema["default"] = value_to_json(default_value),
+            None if is_non_null(&field.value_type) => required.push(field.name.as_ref()),
+            None => (),
+        }
+
+        properties.insert(field.name.as_ref().to_string(), schema);
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Responses only contain the fields that were selected, and may alias them, so unlike inputs
+/// we don't restrict the properties an object can have
+fn object_schema<'a, T: Text<'a>>(fields: &[Field<'a, T>]) -> json::Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+
+    for field in fields {
+        let mut schema = type_schema(&field.field_type, true);
+        if let Some(description) = &field.description {
+            schema["description"] = json::Value::String(description.clone());
+        }
+        if is_non_null(&field.field_type) {
+            required.push(field.name.as_ref());
+        }
+
+        properties.insert(field.name.as_ref().to_string(), schema);
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn type_schema<'a, T: Text<'a>>(value_type: &Type<'a, T>, nullable: bool) -> json::Value {
+    let schema = match value_type {
+        Type::NonNullType(inner) => return type_schema(inner, false),
+        Type::ListType(inner) => json!({
+            "type": "array",
+            "items": type_schema(inner, true),
+        }),
+        Type::NamedType(name) => type_ref(name.as_ref()),
+    };
+
+    if nullable {
+        json!({ "anyOf": [schema, { "type": "null" }] })
+    } else {
+        schema
+    }
+}
+
+fn type_ref(name: &str) -> json::Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+fn is_non_null<'a, T: Text<'a>>(value_type: &Type<'a, T>) -> bool {
+    matches!(value_type, Type::NonNullType(_))
+}
+
+/// The JSON a GraphQL literal is sent as, enums become their names. Variables have to be
+/// resolved before this, they're treated as null.
+pub fn value_to_json<'a, T: Text<'a>>(value: &Value<'a, T>) -> json::Value {
+    match value {
+        Value::Variable(_) => json::Value::Null,
+        Value::Int(number) => number
+            .as_i64()
+            .map(json::Value::from)
+            .unwrap_or(json::Value::Null),
+        Value::Float(float) => json::Number::from_f64(*float)
+            .map(json::Value::Number)
+            .unwrap_or(json::Value::Null),
+        Value::String(string) => json::Value::String(string.clone()),
+        Value::Boolean(boolean) => json::Value::Bool(*boolean),
+        Value::Null => json::Value::Null,
+        Value::Enum(name) => json::Value::String(name.as_ref().to_string()),
+        Value::List(values) => json::Value::Array(values.iter().map(value_to_json).collect()),
+        Value::Object(fields) => json::Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.as_ref().to_string(), value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        scalar DateTime
+        scalar Upload
+
+        enum Role { ADMIN MEMBER }
+
+        "Who to look for"
+        input UserFilter {
+            name: String!
+            role: Role! = MEMBER
+            since: DateTime
+            tags: [String!]
+        }
+
+        type User { id: ID! role: Role }
+        union Result = User
+        type Query { users(filter: UserFilter): [Result] }
+    "#;
+
+    fn generate_with(options: &JsonSchemaOptions) -> json::Value {
+        generate(&parse_schema::<String>(SCHEMA).unwrap(), options)
+    }
+
+    /// Every `$ref` in the value, as the name of the definition it points to
+    fn refs<'v>(value: &'v json::Value, found: &mut Vec<&'v str>) {
+        match value {
+            json::Value::Object(object) => {
+                for (key, value) in object {
+                    match (key.as_str(), value) {
+                        ("$ref", json::Value::String(target)) => {
+                            found.push(target.trim_start_matches("#/$defs/"))
+                        }
+                        _ => refs(value, found),
+                    }
+                }
+            }
+            json::Value::Array(values) => values.iter().for_each(|value| refs(value, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn bundles_inputs_enums_and_scalars() {
+        let bundle = generate_with(&JsonSchemaOptions::default());
+        let definitions = bundle["$defs"].as_object().unwrap();
+
+        assert_eq!(bundle["$schema"], DRAFT_2020_12);
+        assert_eq!(
+            definitions.keys().collect::<Vec<_>>(),
+            vec![
+                "Boolean",
+                "DateTime",
+                "Float",
+                "ID",
+                "Int",
+                "Role",
+                "String",
+                "Upload",
+                "UserFilter",
+            ]
+        );
+        assert_eq!(
+            definitions["UserFilter"],
+            json!({
+                "type": "object",
+                "description": "Who to look for",
+                "properties": {
+                    "name": { "$ref": "#/$defs/String" },
+                    "role": { "$ref": "#/$defs/Role", "default": "MEMBER" },
+                    "since": { "anyOf": [{ "$ref": "#/$defs/DateTime" }, { "type": "null" }] },
+                    "tags": {
+                        "anyOf": [
+                            { "type": "array", "items": { "$ref": "#/$defs/String" } },
+                            { "type": "null" },
+                        ],
+                    },
+                },
+                "required": ["name"],
+                "additionalProperties": false,
+            })
+        );
+        assert_eq!(
+            definitions["Role"],
+            json!({ "type": "string", "enum": ["ADMIN", "MEMBER"] })
+        );
+
+        let mut found = vec![];
+        refs(&bundle, &mut found);
+        assert!(found.iter().all(|name| definitions.contains_key(*name)));
+    }
+
+    #[test]
+    fn uses_the_configured_scalar_schemas() {
+        let mut options = JsonSchemaOptions::default();
+        options.scalars.insert(
+            "DateTime".to_string(),
+            json!({ "type": "string", "format": "date-time" }),
+        );
+        let bundle = generate_with(&options);
+
+        assert_eq!(
+            bundle["$defs"]["DateTime"],
+            json!({ "type": "string", "format": "date-time" })
+        );
+        assert_eq!(bundle["$defs"]["Upload"], json!({}));
+        assert_eq!(bundle["$defs"]["Int"]["maximum"], i32::MAX);
+    }
+
+    #[test]
+    fn output_types_are_opt_in() {
+        let bundle = generate_with(&JsonSchemaOptions {
+            include_output_types: true,
+            ..JsonSchemaOptions::default()
+        });
+        let definitions = bundle["$defs"].as_object().unwrap();
+
+        assert!(generate_with(&JsonSchemaOptions::default())["$defs"]
+            .get("User")
+            .is_none());
+        assert_eq!(
+            definitions["User"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "__typename": { "const": "User" },
+                    "id": { "$ref": "#/$defs/ID" },
+                    "role": { "anyOf": [{ "$ref": "#/$defs/Role" }, { "type": "null" }] },
+                },
+                "required": ["id"],
+            })
+        );
+        assert_eq!(
+            definitions["Result"],
+            json!({ "anyOf": [{ "$ref": "#/$defs/User" }] })
+        );
+
+        let mut found = vec![];
+        refs(&bundle, &mut found);
+        assert!(found.iter().all(|name| definitions.contains_key(*name)));
+    }
+}
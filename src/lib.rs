@@ -1,12 +1,45 @@
+use graphql_parser::query;
 use graphql_parser::schema::{
-    Definition, Document, EnumType, EnumValue, Field, InputObjectType, InputValue, InterfaceType,
-    NamedType, ObjectType, ScalarType, SchemaDefinition, Type, TypeDefinition, UnionType,
+    Definition, Directive, DirectiveDefinition, DirectiveLocation, Document, EnumType, EnumValue,
+    Field, InputObjectType, InputValue, InterfaceType, ObjectType, ScalarType, SchemaDefinition,
+    Text, Type, TypeDefinition, UnionType, Value,
 };
 use graphql_parser::Pos;
 use serde::de::{self, Deserializer, IgnoredAny, MapAccess, Unexpected, Visitor};
 use serde::Deserialize;
-use serde_json as json;
+use serde_json::value::RawValue;
+use std::borrow::Cow;
 use std::fmt;
+use std::io::Read;
+use std::marker::PhantomData;
+
+pub mod codegen;
+pub mod cost;
+pub mod coverage;
+pub mod deprecations;
+pub mod federation;
+pub mod graph;
+pub mod html;
+pub mod incremental;
+pub mod introspection;
+pub mod json_schema;
+pub mod lint;
+pub mod markdown;
+pub mod merge;
+pub mod mock;
+mod naming;
+pub mod operations;
+mod positions;
+pub mod prune;
+pub mod reachability;
+pub mod relay;
+pub mod schema;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod snapshot;
+pub mod typescript;
+
+pub use crate::schema::Schema;
 
 const QUERY_TYPE_ALIAS: &str = "queryType";
 const MUTATION_TYPE_ALIAS: &str = "mutationType";
@@ -28,129 +61,380 @@ const DEFAULT_VALUE_ALIAS: &str = "defaultValue";
 const OF_TYPE_ALIAS: &str = "ofType";
 const IS_DEPRECATED_ALIAS: &str = "isDeprecated";
 const DEPRECATION_REASON_ALIAS: &str = "deprecationReason";
+const LOCATIONS_ALIAS: &str = "locations";
+const IS_REPEATABLE_ALIAS: &str = "isRepeatable";
+
+const DEPRECATED_DIRECTIVE: &str = "deprecated";
+const REASON_ARGUMENT: &str = "reason";
+
+/// The string types a `Document` can be parsed into, from input that lives for `'de`.
+///
+/// `Document<'static, String>` owns all of its names. `Document<'a, &'a str>` borrows them
+/// from the input instead, which fails if a name had to be unescaped (or was read from a
+/// stream) as there's nothing left to borrow from. That includes enum values and object keys
+/// in a `defaultValue` whose literal has escapes, like `{name: "x", role: ADMIN}`, since the
+/// whole literal is a single JSON string. `Document<'a, Cow<'a, str>>` borrows where it can
+/// and copies the rest.
+pub trait ParseText<'de, 'a>: Text<'a> {
+    fn from_borrowed(name: &'de str) -> Self::Value;
+
+    /// Turn a name that couldn't be borrowed into a value, handing it back if this type can
+    /// only borrow
+    fn from_owned(name: String) -> Result<Self::Value, String>;
+}
+
+impl<'de, 'a> ParseText<'de, 'a> for String {
+    fn from_borrowed(name: &'de str) -> Self::Value {
+        name.to_string()
+    }
+
+    fn from_owned(name: String) -> Result<Self::Value, String> {
+        Ok(name)
+    }
+}
+
+impl<'de: 'a, 'a> ParseText<'de, 'a> for &'a str {
+    fn from_borrowed(name: &'de str) -> Self::Value {
+        name
+    }
+
+    fn from_owned(name: String) -> Result<Self::Value, String> {
+        Err(name)
+    }
+}
+
+impl<'de: 'a, 'a> ParseText<'de, 'a> for Cow<'a, str> {
+    fn from_borrowed(name: &'de str) -> Self::Value {
+        Cow::Borrowed(name)
+    }
+
+    fn from_owned(name: String) -> Result<Self::Value, String> {
+        Ok(Cow::Owned(name))
+    }
+}
+
+/// Parse an introspection response into a `Document` with any of the `ParseText` string types,
+/// ie: `parse::<String>(&raw)` for a document that owns its names.
+pub fn parse<'de, 'a, T>(raw_introspection: &'de str) -> serde_json::Result<Document<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
+    serde_json::from_str::<ResponseContainer<DeserializeWith<Document<T>>>>(raw_introspection)
+        .map(|c| c.data.schema.value)
+}
+
+/// Parse an introspection response straight from a stream, so large payloads never have to be
+/// read into a single `String` first. Files should be wrapped in a `BufReader`.
+pub fn parse_reader<R>(reader: R) -> serde_json::Result<Document<'static, String>>
+where
+    R: Read,
+{
+    serde_json::from_reader::<_, ResponseContainer<DeserializeWith<Document<String>>>>(reader)
+        .map(|c| c.data.schema.value)
+}
+
+/// Parse an introspection response like `parse`, but fill in the `position` of every type,
+/// field, argument, input field and enum value with the line and column its object starts at in
+/// the JSON, and the schema definition with that of `__schema`. Errors found in the document
+/// can then point back into the input.
+///
+/// This reads each type's JSON more than once, so it's slower than `parse`. Line and column
+/// numbers in errors are relative to the type that failed.
+pub fn parse_with_positions<'de, 'a, T>(
+    raw_introspection: &'de str,
+) -> serde_json::Result<Document<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
+    let line_index = positions::LineIndex::new(raw_introspection);
+    let raw_schema = serde_json::from_str::<ResponseContainer<&'de RawValue>>(raw_introspection)?
+        .data
+        .schema;
+    let fields = serde_json::from_str::<SchemaFields<T, Vec<&'de RawValue>>>(raw_schema.get())?;
+
+    let mut types = Vec::with_capacity(fields.types.len());
+    for raw_type in &fields.types {
+        let mut type_definition =
+            serde_json::from_str::<DeserializeWith<TypeDefinition<T>>>(raw_type.get())?.value;
+        line_index.fill_type_definition(&mut type_definition, raw_type)?;
+        types.push(type_definition);
+    }
+
+    let mut document = fields.root_types.into_document(types, fields.directives);
+    for definition in &mut document.definitions {
+        if let Definition::SchemaDefinition(schema_definition) = definition {
+            schema_definition.position = line_index.position(raw_schema);
+        }
+    }
+
+    Ok(document)
+}
+
+/// Parse an introspection response, building each of its types on the rayon thread pool.
+///
+/// The `types` array is first split into raw JSON slices, which are then parsed in parallel
+/// and collected in their original order, so the document is identical to what `parse` gives.
+/// Line and column numbers in errors are relative to the type that failed.
+#[cfg(feature = "rayon")]
+pub fn parse_parallel<'de, 'a, T>(
+    raw_introspection: &'de str,
+) -> serde_json::Result<Document<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+    T::Value: Send,
+{
+    use rayon::prelude::*;
+
+    let fields = serde_json::from_str::<ResponseContainer<SchemaFields<T, Vec<&'de RawValue>>>>(
+        raw_introspection,
+    )?
+    .data
+    .schema;
+    let types = fields
+        .types
+        .par_iter()
+        .map(|raw_type| {
+            serde_json::from_str::<DeserializeWith<TypeDefinition<T>>>(raw_type.get())
+                .map(|v| v.value)
+        })
+        .collect::<serde_json::Result<Vec<_>>>()?;
 
-pub fn parse(raw_introspection: &str) -> serde_json::Result<Document> {
-    serde_json::from_str::<ResponseContainer>(raw_introspection).map(|c| c.data.schema)
+    Ok(fields.root_types.into_document(types, fields.directives))
 }
 
 #[derive(Deserialize)]
-struct ResponseContainer {
-    data: SchemaContainer,
+struct ResponseContainer<S> {
+    data: SchemaContainer<S>,
 }
 
 #[derive(Deserialize)]
-struct SchemaContainer {
-    #[serde(
-        rename(deserialize = "__schema"),
-        deserialize_with = "deserialize_document"
-    )]
-    schema: Document,
+struct SchemaContainer<S> {
+    #[serde(rename(deserialize = "__schema"))]
+    schema: S,
 }
 
-fn deserialize_document<'de, D>(deserializer: D) -> Result<Document, D::Error>
+/// Deserialize the `__schema` object of an introspection response from any serde format, so
+/// responses cached as CBOR, MessagePack, YAML, etc. don't have to go through JSON.
+///
+/// Names are borrowed as described on `ParseText` when the format hands out borrowed strings.
+/// Unknown keys are skipped with `IgnoredAny`, so formats that aren't self-describing, like
+/// bincode, only work for data without them.
+pub fn deserialize_schema<'de, 'a, D, T>(deserializer: D) -> Result<Document<'a, T>, D::Error>
 where
     D: Deserializer<'de>,
+    T: ParseText<'de, 'a>,
 {
-    struct DocumentVisitor;
+    SchemaFields::<T, Vec<DeserializeWith<TypeDefinition<T>>>>::deserialize(deserializer).map(
+        |fields| {
+            let types = fields.types.into_iter().map(|v| v.value).collect();
+            fields.root_types.into_document(types, fields.directives)
+        },
+    )
+}
 
-    fn deserialize_root_type<'de, M>(
-        previous_result: &Option<NamedType>,
-        alias: &'static str,
-        access: &mut M,
-    ) -> Result<Option<String>, M::Error>
+impl<'de, 'a, T> Deserialize<'de> for DeserializeWith<Document<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
-        M: MapAccess<'de>,
+        D: Deserializer<'de>,
     {
-        if previous_result.is_none() {
-            access
-                .next_value::<json::Value>()
-                .and_then(|value| match value {
-                    json::Value::Null => Ok(None),
-                    json::Value::Object(map) => map
-                        .get("name")
-                        .and_then(|v| v.as_str())
-                        .map(|s| Some(s.to_string()))
-                        .ok_or_else(|| de::Error::missing_field("name")),
-                    _ => Err(de::Error::invalid_type(
-                        Unexpected::Other(&format!("{}", value)),
-                        &"object type",
-                    )),
-                })
-        } else {
-            Err(de::Error::duplicate_field(alias))
-        }
+        deserialize_schema(deserializer).map(|value| DeserializeWith { value })
     }
+}
 
-    impl<'de> Visitor<'de> for DocumentVisitor {
-        type Value = Document;
+/// The root operation types of a `__schema` object
+struct RootTypes<'a, T: Text<'a>> {
+    query: Option<T::Value>,
+    mutation: Option<T::Value>,
+    subscription: Option<T::Value>,
+}
 
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("A Document object")
-        }
+impl<'a, T: Text<'a>> RootTypes<'a, T> {
+    fn into_document(
+        self,
+        types: Vec<TypeDefinition<'a, T>>,
+        directives: Vec<DirectiveDefinition<'a, T>>,
+    ) -> Document<'a, T> {
+        let schema_definition = Definition::SchemaDefinition(SchemaDefinition {
+            position: Pos::default(),
+            directives: vec![],
+            query: self.query,
+            mutation: self.mutation,
+            subscription: self.subscription,
+        });
+
+        // build up our final definitions vec
+        let mut definitions: Vec<Definition<T>> =
+            types.into_iter().map(Definition::TypeDefinition).collect();
+        definitions.extend(directives.into_iter().map(Definition::DirectiveDefinition));
+        definitions.push(schema_definition);
+
+        Document { definitions }
+    }
+}
 
-        fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+/// The keys of a `__schema` object, with `types` deserialized as `Types` so callers can keep
+/// each type's raw JSON around instead of building it straight away
+struct SchemaFields<'a, T: Text<'a>, Types> {
+    root_types: RootTypes<'a, T>,
+    directives: Vec<DirectiveDefinition<'a, T>>,
+    types: Types,
+}
+
+impl<'de, 'a, T, Types> Deserialize<'de> for SchemaFields<'a, T, Types>
+where
+    T: ParseText<'de, 'a>,
+    Types: Deserialize<'de> + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SchemaFieldsVisitor<'a, T, Types>(PhantomData<(&'a T, Types)>);
+
+        fn deserialize_root_type<'de, 'a, M, T>(
+            previous_result: &Option<T::Value>,
+            alias: &'static str,
+            access: &mut M,
+        ) -> Result<Option<T::Value>, M::Error>
         where
             M: MapAccess<'de>,
+            T: ParseText<'de, 'a>,
         {
-            let mut query_type = None;
-            let mut mutation_type = None;
-            let mut subscription_type = None;
-            let mut types: Vec<Definition> = vec![];
-
-            while let Some(key) = access.next_key()? {
-                match key {
-                    QUERY_TYPE_ALIAS => {
-                        query_type =
-                            deserialize_root_type(&query_type, QUERY_TYPE_ALIAS, &mut access)?;
-                    }
-                    MUTATION_TYPE_ALIAS => {
-                        mutation_type = deserialize_root_type(
-                            &mutation_type,
-                            MUTATION_TYPE_ALIAS,
-                            &mut access,
-                        )?;
-                    }
-                    SUBSCRIPTION_TYPE_ALIAS => {
-                        subscription_type = deserialize_root_type(
-                            &subscription_type,
-                            SUBSCRIPTION_TYPE_ALIAS,
-                            &mut access,
-                        )?;
-                    }
-                    DIRECTIVES_ALIAS => {
-                        access.next_value::<IgnoredAny>()?;
-                    }
-                    TYPES_ALIAS => {
-                        types = access
-                            .next_value::<Vec<DeserializeWith<TypeDefinition>>>()?
-                            .into_iter()
-                            .map(|v| Definition::TypeDefinition(v.value))
-                            .collect();
-                    }
-                    _ => handle_unexpected_key(key, &mut access)?,
-                }
+            if previous_result.is_none() {
+                access
+                    .next_value::<Option<DeserializeWith<RootType<T>>>>()
+                    .map(|root_type| root_type.map(|v| v.value.0))
+            } else {
+                Err(de::Error::duplicate_field(alias))
             }
+        }
 
-            let schema_definition = Definition::SchemaDefinition(SchemaDefinition {
-                position: Pos::default(),
-                directives: vec![],
-                query: query_type,
-                mutation: mutation_type,
-                subscription: subscription_type,
-            });
+        impl<'de, 'a, T, Types> Visitor<'de> for SchemaFieldsVisitor<'a, T, Types>
+        where
+            T: ParseText<'de, 'a>,
+            Types: Deserialize<'de> + Default,
+        {
+            type Value = SchemaFields<'a, T, Types>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("A Document object")
+            }
 
-            // build up our final definitions vec
-            let mut definitions = types;
-            definitions.push(schema_definition);
+            fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let mut query_type = None;
+                let mut mutation_type = None;
+                let mut subscription_type = None;
+                let mut directives = vec![];
+                let mut types = Types::default();
+
+                while let Some(key) = access.next_key::<CowStr>()? {
+                    match key.as_str() {
+                        QUERY_TYPE_ALIAS => {
+                            query_type = deserialize_root_type::<_, T>(
+                                &query_type,
+                                QUERY_TYPE_ALIAS,
+                                &mut access,
+                            )?;
+                        }
+                        MUTATION_TYPE_ALIAS => {
+                            mutation_type = deserialize_root_type::<_, T>(
+                                &mutation_type,
+                                MUTATION_TYPE_ALIAS,
+                                &mut access,
+                            )?;
+                        }
+                        SUBSCRIPTION_TYPE_ALIAS => {
+                            subscription_type = deserialize_root_type::<_, T>(
+                                &subscription_type,
+                                SUBSCRIPTION_TYPE_ALIAS,
+                                &mut access,
+                            )?;
+                        }
+                        DIRECTIVES_ALIAS => {
+                            directives = DeserializeWith::deserialize_array(&mut access)?
+                                .unwrap_or_default();
+                        }
+                        TYPES_ALIAS => {
+                            types = access.next_value()?;
+                        }
+                        _ => handle_unexpected_key(key.as_str(), &mut access)?,
+                    }
+                }
 
-            Ok(Document { definitions })
+                Ok(SchemaFields {
+                    root_types: RootTypes {
+                        query: query_type,
+                        mutation: mutation_type,
+                        subscription: subscription_type,
+                    },
+                    directives,
+                    types,
+                })
+            }
         }
+
+        deserializer.deserialize_map(SchemaFieldsVisitor(PhantomData))
     }
+}
+
+/// The `{ name }` object introspection uses for root operation types
+struct RootType<'a, T: Text<'a>>(T::Value);
+
+impl<'de, 'a, T> Deserialize<'de> for DeserializeWith<RootType<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RootTypeVisitor<'a, T>(PhantomData<&'a T>);
+
+        impl<'de, 'a, T> Visitor<'de> for RootTypeVisitor<'a, T>
+        where
+            T: ParseText<'de, 'a>,
+        {
+            type Value = RootType<'a, T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("An object type")
+            }
+
+            fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let mut name = None;
+
+                while let Some(key) = access.next_key::<CowStr>()? {
+                    match key.as_str() {
+                        NAME_ALIAS => {
+                            name = Some(access.next_value::<CowStr>()?.into_name::<T, _>()?);
+                        }
+                        _ => handle_unexpected_key(key.as_str(), &mut access)?,
+                    }
+                }
+
+                require_field(NAME_ALIAS, name).map(RootType)
+            }
+        }
 
-    deserializer.deserialize_map(DocumentVisitor)
+        deserializer
+            .deserialize_map(RootTypeVisitor(PhantomData))
+            .map(|value| DeserializeWith { value })
+    }
 }
 
-impl<'de> Deserialize<'de> for DeserializeWith<TypeDefinition> {
+impl<'de, 'a, T> Deserialize<'de> for DeserializeWith<TypeDefinition<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -159,14 +443,20 @@ impl<'de> Deserialize<'de> for DeserializeWith<TypeDefinition> {
     }
 }
 
-fn deserialize_type_definition<'de, D>(deserializer: D) -> Result<TypeDefinition, D::Error>
+fn deserialize_type_definition<'de, 'a, D, T>(
+    deserializer: D,
+) -> Result<TypeDefinition<'a, T>, D::Error>
 where
     D: Deserializer<'de>,
+    T: ParseText<'de, 'a>,
 {
-    struct TypeDefinitionVisitor;
+    struct TypeDefinitionVisitor<'a, T>(PhantomData<&'a T>);
 
-    impl<'de> Visitor<'de> for TypeDefinitionVisitor {
-        type Value = TypeDefinition;
+    impl<'de, 'a, T> Visitor<'de> for TypeDefinitionVisitor<'a, T>
+    where
+        T: ParseText<'de, 'a>,
+    {
+        type Value = TypeDefinition<'a, T>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
             formatter.write_str("A TypeDefinition object")
@@ -177,21 +467,21 @@ where
             M: MapAccess<'de>,
         {
             let mut kind: Option<TypeKind> = None;
-            let mut maybe_name: Option<String> = None;
+            let mut maybe_name: Option<T::Value> = None;
             let mut description: Option<String> = None;
-            let mut fields: Option<Vec<Field>> = None;
-            let mut input_fields: Option<Vec<InputValue>> = None;
-            let mut interfaces: Option<Vec<NamedType>> = None;
-            let mut enum_values: Option<Vec<EnumValue>> = None;
-            let mut possible_types: Option<Vec<NamedType>> = None;
-
-            while let Some(key) = access.next_key()? {
-                match key {
+            let mut fields: Option<Vec<Field<T>>> = None;
+            let mut input_fields: Option<Vec<InputValue<T>>> = None;
+            let mut interfaces: Option<Vec<Type<T>>> = None;
+            let mut enum_values: Option<Vec<EnumValue<T>>> = None;
+            let mut possible_types: Option<Vec<Type<T>>> = None;
+
+            while let Some(key) = access.next_key::<CowStr>()? {
+                match key.as_str() {
                     KIND_ALIAS => {
                         kind = Some(access.next_value()?);
                     }
                     NAME_ALIAS => {
-                        maybe_name = Some(access.next_value()?);
+                        maybe_name = Some(access.next_value::<CowStr>()?.into_name::<T, _>()?);
                     }
                     DESCRIPTION_ALIAS => {
                         description = access.next_value()?;
@@ -211,7 +501,7 @@ where
                     POSSIBLE_TYPES_ALIAS => {
                         possible_types = DeserializeWith::deserialize_array(&mut access)?;
                     }
-                    _ => handle_unexpected_key(key, &mut access)?,
+                    _ => handle_unexpected_key(key.as_str(), &mut access)?,
                 }
             }
 
@@ -240,22 +530,22 @@ where
                         position: Pos::default(),
                         description,
                         name,
-                        implements_interfaces: interfaces.unwrap_or_else(|| vec![]),
+                        implements_interfaces: named_types(interfaces)?,
                         directives: vec![],
-                        fields: fields.unwrap_or_else(|| vec![]),
+                        fields: fields.unwrap_or_default(),
                     })
                 }
                 TypeKind::Interface => {
                     require_field_empty(INPUT_FIELDS_ALIAS, input_fields)?;
-                    require_field_empty(INTERFACES_ALIAS, interfaces)?;
                     // even though we don't use POSSIBLE_TYPES_ALIAS, they're ok here
 
                     TypeDefinition::Interface(InterfaceType {
                         position: Pos::default(),
                         description,
                         name,
+                        implements_interfaces: named_types(interfaces)?,
                         directives: vec![],
-                        fields: fields.unwrap_or_else(|| vec![]),
+                        fields: fields.unwrap_or_default(),
                     })
                 }
                 TypeKind::Union => {
@@ -268,7 +558,7 @@ where
                         description,
                         name,
                         directives: vec![],
-                        types: possible_types.unwrap_or_else(|| vec![]),
+                        types: named_types(possible_types)?,
                     })
                 }
                 TypeKind::Enum => {
@@ -282,7 +572,7 @@ where
                         description,
                         name,
                         directives: vec![],
-                        values: enum_values.unwrap_or_else(|| vec![]),
+                        values: enum_values.unwrap_or_default(),
                     })
                 }
                 TypeKind::InputObject => {
@@ -295,7 +585,7 @@ where
                         description,
                         name,
                         directives: vec![],
-                        fields: input_fields.unwrap_or_else(|| vec![]),
+                        fields: input_fields.unwrap_or_default(),
                     })
                 }
             };
@@ -304,10 +594,32 @@ where
         }
     }
 
-    deserializer.deserialize_map(TypeDefinitionVisitor)
+    deserializer.deserialize_map(TypeDefinitionVisitor(PhantomData))
+}
+
+/// Interfaces and possible types are type refs, but they can only ever be named types
+fn named_types<'a, T, E>(type_refs: Option<Vec<Type<'a, T>>>) -> Result<Vec<T::Value>, E>
+where
+    T: Text<'a>,
+    E: de::Error,
+{
+    type_refs
+        .unwrap_or_default()
+        .into_iter()
+        .map(|type_ref| match type_ref {
+            Type::NamedType(name) => Ok(name),
+            unexpected => Err(de::Error::custom(format_args!(
+                "Expected NamedType, found {}",
+                unexpected
+            ))),
+        })
+        .collect()
 }
 
-impl<'de> Deserialize<'de> for DeserializeWith<Field> {
+impl<'de, 'a, T> Deserialize<'de> for DeserializeWith<Field<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -316,14 +628,18 @@ impl<'de> Deserialize<'de> for DeserializeWith<Field> {
     }
 }
 
-fn deserialize_field<'de, D>(deserializer: D) -> Result<Field, D::Error>
+fn deserialize_field<'de, 'a, D, T>(deserializer: D) -> Result<Field<'a, T>, D::Error>
 where
     D: Deserializer<'de>,
+    T: ParseText<'de, 'a>,
 {
-    struct FieldVisitor;
+    struct FieldVisitor<'a, T>(PhantomData<&'a T>);
 
-    impl<'de> Visitor<'de> for FieldVisitor {
-        type Value = Field;
+    impl<'de, 'a, T> Visitor<'de> for FieldVisitor<'a, T>
+    where
+        T: ParseText<'de, 'a>,
+    {
+        type Value = Field<'a, T>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
             formatter.write_str("A Field object")
@@ -333,15 +649,17 @@ where
         where
             M: MapAccess<'de>,
         {
-            let mut name: Option<String> = None;
+            let mut name: Option<T::Value> = None;
             let mut description: Option<String> = None;
-            let mut value_type: Option<Type> = None;
-            let mut input_fields: Option<Vec<InputValue>> = None;
+            let mut value_type: Option<Type<T>> = None;
+            let mut input_fields: Option<Vec<InputValue<T>>> = None;
+            let mut is_deprecated = false;
+            let mut deprecation_reason: Option<String> = None;
 
-            while let Some(key) = access.next_key()? {
-                match key {
+            while let Some(key) = access.next_key::<CowStr>()? {
+                match key.as_str() {
                     NAME_ALIAS => {
-                        name = Some(access.next_value()?);
+                        name = Some(access.next_value::<CowStr>()?.into_name::<T, _>()?);
                     }
                     DESCRIPTION_ALIAS => {
                         description = access.next_value()?;
@@ -353,14 +671,12 @@ where
                         input_fields = DeserializeWith::deserialize_array(&mut access)?;
                     }
                     IS_DEPRECATED_ALIAS => {
-                        // not supported
-                        access.next_value::<IgnoredAny>()?;
+                        is_deprecated = access.next_value::<Option<bool>>()?.unwrap_or_default();
                     }
                     DEPRECATION_REASON_ALIAS => {
-                        // not supported
-                        access.next_value::<IgnoredAny>()?;
+                        deprecation_reason = access.next_value()?;
                     }
-                    _ => handle_unexpected_key(key, &mut access)?,
+                    _ => handle_unexpected_key(key.as_str(), &mut access)?,
                 }
             }
 
@@ -368,17 +684,20 @@ where
                 position: Pos::default(),
                 description,
                 name: require_field(NAME_ALIAS, name)?,
-                arguments: input_fields.unwrap_or_else(|| vec![]),
+                arguments: input_fields.unwrap_or_default(),
                 field_type: require_field(TYPE_ALIAS, value_type)?,
-                directives: vec![],
+                directives: deprecation_directives(is_deprecated, deprecation_reason),
             })
         }
     }
 
-    deserializer.deserialize_map(FieldVisitor)
+    deserializer.deserialize_map(FieldVisitor(PhantomData))
 }
 
-impl<'de> Deserialize<'de> for DeserializeWith<InputValue> {
+impl<'de, 'a, T> Deserialize<'de> for DeserializeWith<InputValue<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -387,14 +706,18 @@ impl<'de> Deserialize<'de> for DeserializeWith<InputValue> {
     }
 }
 
-fn deserialize_input_value<'de, D>(deserializer: D) -> Result<InputValue, D::Error>
+fn deserialize_input_value<'de, 'a, D, T>(deserializer: D) -> Result<InputValue<'a, T>, D::Error>
 where
     D: Deserializer<'de>,
+    T: ParseText<'de, 'a>,
 {
-    struct InputValueVisitor;
+    struct InputValueVisitor<'a, T>(PhantomData<&'a T>);
 
-    impl<'de> Visitor<'de> for InputValueVisitor {
-        type Value = InputValue;
+    impl<'de, 'a, T> Visitor<'de> for InputValueVisitor<'a, T>
+    where
+        T: ParseText<'de, 'a>,
+    {
+        type Value = InputValue<'a, T>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
             formatter.write_str("A InputValue object")
@@ -404,15 +727,17 @@ where
         where
             M: MapAccess<'de>,
         {
-            let mut name: Option<String> = None;
+            let mut name: Option<T::Value> = None;
             let mut description: Option<String> = None;
-            let mut maybe_value_type: Option<Type> = None;
-            let mut default_value_json: Option<json::Value> = None;
+            let mut maybe_value_type: Option<Type<T>> = None;
+            let mut raw_default_value: Option<CowStr> = None;
+            let mut is_deprecated = false;
+            let mut deprecation_reason: Option<String> = None;
 
-            while let Some(key) = access.next_key()? {
-                match key {
+            while let Some(key) = access.next_key::<CowStr>()? {
+                match key.as_str() {
                     NAME_ALIAS => {
-                        name = Some(access.next_value()?);
+                        name = Some(access.next_value::<CowStr>()?.into_name::<T, _>()?);
                     }
                     DESCRIPTION_ALIAS => {
                         description = access.next_value()?;
@@ -421,37 +746,124 @@ where
                         maybe_value_type = DeserializeWith::deserialize_value(&mut access)?;
                     }
                     DEFAULT_VALUE_ALIAS => {
-                        default_value_json = access.next_value()?;
+                        raw_default_value = access.next_value()?;
                     }
-                    _ => {
-                        println!(
-                            "{:?}\n{:?}\n{:?}\n{:?}",
-                            name, description, maybe_value_type, default_value_json
-                        );
-
-                        handle_unexpected_key(key, &mut access)?
+                    IS_DEPRECATED_ALIAS => {
+                        is_deprecated = access.next_value::<Option<bool>>()?.unwrap_or_default();
+                    }
+                    DEPRECATION_REASON_ALIAS => {
+                        deprecation_reason = access.next_value()?;
                     }
+                    _ => handle_unexpected_key(key.as_str(), &mut access)?,
                 }
             }
 
             let value_type = require_field(TYPE_ALIAS, maybe_value_type)?;
-            //let default_value = default_value_json.map(|v| json_value_to_graphql(&v, &value_type));
+            let default_value = match raw_default_value {
+                Some(raw) => Some(parse_default_value::<T, _>(raw)?),
+                None => None,
+            };
 
             Ok(InputValue {
                 position: Pos::default(),
                 description,
                 name: require_field(NAME_ALIAS, name)?,
                 value_type,
-                default_value: None,
-                directives: vec![],
+                default_value,
+                directives: deprecation_directives(is_deprecated, deprecation_reason),
             })
         }
     }
 
-    deserializer.deserialize_map(InputValueVisitor)
+    deserializer.deserialize_map(InputValueVisitor(PhantomData))
+}
+
+const DEFAULT_VALUE_PREFIX: &str = "{ f(v: ";
+
+/// Introspection gives us default values as GraphQL literals, ie: `"\"text\""` or `{a: [1, 2]}`.
+/// graphql-parser doesn't expose a value parser so we parse it as an argument of a query.
+///
+/// Names in the literal are borrowed from `raw` when it's borrowed. If it had to be unescaped
+/// they're copied, which `&str` can't do, so it only gets an error.
+fn parse_default_value<'de, 'a, T, E>(raw: CowStr<'de>) -> Result<Value<'a, T>, E>
+where
+    T: ParseText<'de, 'a>,
+    E: de::Error,
+{
+    let invalid = || {
+        de::Error::invalid_value(
+            Unexpected::Str(raw.as_str()),
+            &"a GraphQL literal for defaultValue",
+        )
+    };
+
+    let wrapped = format!("{}{}) }}", DEFAULT_VALUE_PREFIX, raw.as_str());
+    let document = query::parse_query::<&str>(&wrapped).map_err(|_| invalid())?;
+    let value = match document.definitions.into_iter().next() {
+        Some(query::Definition::Operation(query::OperationDefinition::SelectionSet(
+            selection_set,
+        ))) => match selection_set.items.into_iter().next() {
+            Some(query::Selection::Field(field)) => field
+                .arguments
+                .into_iter()
+                .next()
+                .map(|(_, value)| value)
+                .ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        },
+        _ => return Err(invalid()),
+    };
+
+    // names in the value point into `wrapped`, find the same text in the input to borrow it
+    let rebase = |name: &str| -> Result<T::Value, E> {
+        match &raw.0 {
+            Cow::Borrowed(raw) => {
+                let start = name.as_ptr() as usize - wrapped.as_ptr() as usize;
+                let start = start - DEFAULT_VALUE_PREFIX.len();
+
+                Ok(T::from_borrowed(&raw[start..start + name.len()]))
+            }
+            Cow::Owned(_) => CowStr(Cow::Owned(name.to_string())).into_name::<T, E>(),
+        }
+    };
+
+    convert_value(value, &rebase)
+}
+
+fn convert_value<'v, 'a, T, E>(
+    value: Value<'v, &'v str>,
+    rebase: &dyn Fn(&str) -> Result<T::Value, E>,
+) -> Result<Value<'a, T>, E>
+where
+    T: Text<'a>,
+{
+    Ok(match value {
+        Value::Variable(name) => Value::Variable(rebase(name)?),
+        Value::Int(number) => Value::Int(number),
+        Value::Float(float) => Value::Float(float),
+        Value::String(string) => Value::String(string),
+        Value::Boolean(boolean) => Value::Boolean(boolean),
+        Value::Null => Value::Null,
+        Value::Enum(name) => Value::Enum(rebase(name)?),
+        Value::List(values) => Value::List(
+            values
+                .into_iter()
+                .map(|value| convert_value(value, rebase))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| Ok((rebase(name)?, convert_value(value, rebase)?)))
+                .collect::<Result<_, _>>()?,
+        ),
+    })
 }
 
-impl<'de> Deserialize<'de> for DeserializeWith<Type> {
+impl<'de, 'a, T> Deserialize<'de> for DeserializeWith<Type<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -460,14 +872,18 @@ impl<'de> Deserialize<'de> for DeserializeWith<Type> {
     }
 }
 
-fn deserialize_type_ref<'de, D>(deserializer: D) -> Result<Type, D::Error>
+fn deserialize_type_ref<'de, 'a, D, T>(deserializer: D) -> Result<Type<'a, T>, D::Error>
 where
     D: Deserializer<'de>,
+    T: ParseText<'de, 'a>,
 {
-    struct TypeRefVisitor;
+    struct TypeRefVisitor<'a, T>(PhantomData<&'a T>);
 
-    impl<'de> Visitor<'de> for TypeRefVisitor {
-        type Value = Type;
+    impl<'de, 'a, T> Visitor<'de> for TypeRefVisitor<'a, T>
+    where
+        T: ParseText<'de, 'a>,
+    {
+        type Value = Type<'a, T>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
             formatter.write_str("A TypeRef object")
@@ -477,22 +893,25 @@ where
         where
             M: MapAccess<'de>,
         {
-            let mut kind: Option<String> = None;
-            let mut name: Option<String> = None;
-            let mut of_type: Option<Type> = None;
+            let mut kind: Option<CowStr> = None;
+            let mut name: Option<T::Value> = None;
+            let mut of_type: Option<Type<T>> = None;
 
-            while let Some(key) = access.next_key()? {
-                match key {
+            while let Some(key) = access.next_key::<CowStr>()? {
+                match key.as_str() {
                     KIND_ALIAS => {
                         kind = Some(access.next_value()?);
                     }
                     NAME_ALIAS => {
-                        name = access.next_value()?;
+                        name = access
+                            .next_value::<Option<CowStr>>()?
+                            .map(CowStr::into_name::<T, _>)
+                            .transpose()?;
                     }
                     OF_TYPE_ALIAS => {
                         of_type = DeserializeWith::deserialize_value(&mut access)?;
                     }
-                    _ => handle_unexpected_key(key, &mut access)?,
+                    _ => handle_unexpected_key(key.as_str(), &mut access)?,
                 }
             }
 
@@ -508,83 +927,184 @@ where
         }
     }
 
-    deserializer.deserialize_map(TypeRefVisitor)
+    deserializer.deserialize_map(TypeRefVisitor(PhantomData))
 }
 
-impl<'de> Deserialize<'de> for DeserializeWith<NamedType> {
+impl<'de, 'a, T> Deserialize<'de> for DeserializeWith<EnumValue<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserialize_type_ref(deserializer).and_then(|type_ref| match type_ref {
-            Type::NamedType(name) => Ok(DeserializeWith { value: name }),
-            unexpected => Err(de::Error::custom(format_args!(
-                "Expected NamedType, found {:?}",
-                unexpected
-            ))),
-        })
+        deserialize_enum_value(deserializer).map(|value| DeserializeWith { value })
+    }
+}
+
+fn deserialize_enum_value<'de, 'a, D, T>(deserializer: D) -> Result<EnumValue<'a, T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ParseText<'de, 'a>,
+{
+    struct EnumValueVisitor<'a, T>(PhantomData<&'a T>);
+
+    impl<'de, 'a, T> Visitor<'de> for EnumValueVisitor<'a, T>
+    where
+        T: ParseText<'de, 'a>,
+    {
+        type Value = EnumValue<'a, T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("An EnumValue object")
+        }
+
+        fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+        where
+            M: MapAccess<'de>,
+        {
+            let mut name: Option<T::Value> = None;
+            let mut description: Option<String> = None;
+            let mut is_deprecated = false;
+            let mut deprecation_reason: Option<String> = None;
+
+            while let Some(key) = access.next_key::<CowStr>()? {
+                match key.as_str() {
+                    NAME_ALIAS => {
+                        name = Some(access.next_value::<CowStr>()?.into_name::<T, _>()?);
+                    }
+                    DESCRIPTION_ALIAS => {
+                        description = access.next_value()?;
+                    }
+                    IS_DEPRECATED_ALIAS => {
+                        is_deprecated = access.next_value::<Option<bool>>()?.unwrap_or_default();
+                    }
+                    DEPRECATION_REASON_ALIAS => {
+                        deprecation_reason = access.next_value()?;
+                    }
+                    _ => handle_unexpected_key(key.as_str(), &mut access)?,
+                }
+            }
+
+            Ok(EnumValue {
+                position: Pos::default(),
+                description,
+                name: require_field(NAME_ALIAS, name)?,
+                directives: deprecation_directives(is_deprecated, deprecation_reason),
+            })
+        }
     }
+
+    deserializer.deserialize_map(EnumValueVisitor(PhantomData))
 }
 
-impl<'de> Deserialize<'de> for DeserializeWith<EnumValue> {
+impl<'de, 'a, T> Deserialize<'de> for DeserializeWith<DirectiveDefinition<'a, T>>
+where
+    T: ParseText<'de, 'a>,
+{
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserialize_enum_value(deserializer).map(|value| DeserializeWith { value })
+        deserialize_directive_definition(deserializer).map(|value| DeserializeWith { value })
     }
 }
 
-fn deserialize_enum_value<'de, D>(deserializer: D) -> Result<EnumValue, D::Error>
+fn deserialize_directive_definition<'de, 'a, D, T>(
+    deserializer: D,
+) -> Result<DirectiveDefinition<'a, T>, D::Error>
 where
     D: Deserializer<'de>,
+    T: ParseText<'de, 'a>,
 {
-    struct EnumValueVisitor;
+    struct DirectiveDefinitionVisitor<'a, T>(PhantomData<&'a T>);
 
-    impl<'de> Visitor<'de> for EnumValueVisitor {
-        type Value = EnumValue;
+    impl<'de, 'a, T> Visitor<'de> for DirectiveDefinitionVisitor<'a, T>
+    where
+        T: ParseText<'de, 'a>,
+    {
+        type Value = DirectiveDefinition<'a, T>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("An EnumValue object")
+            formatter.write_str("A DirectiveDefinition object")
         }
 
         fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
         where
             M: MapAccess<'de>,
         {
-            let mut name: Option<String> = None;
+            let mut name: Option<T::Value> = None;
             let mut description: Option<String> = None;
+            let mut locations: Option<Vec<DirectiveLocation>> = None;
+            let mut arguments: Option<Vec<InputValue<T>>> = None;
+            let mut repeatable = false;
 
-            while let Some(key) = access.next_key()? {
-                match key {
+            while let Some(key) = access.next_key::<CowStr>()? {
+                match key.as_str() {
                     NAME_ALIAS => {
-                        name = Some(access.next_value()?);
+                        name = Some(access.next_value::<CowStr>()?.into_name::<T, _>()?);
                     }
                     DESCRIPTION_ALIAS => {
                         description = access.next_value()?;
                     }
-                    IS_DEPRECATED_ALIAS => {
-                        // not supported
-                        access.next_value::<IgnoredAny>()?;
+                    LOCATIONS_ALIAS => {
+                        locations = Some(
+                            access
+                                .next_value::<Vec<CowStr>>()?
+                                .iter()
+                                .map(|location| {
+                                    location.as_str().parse().map_err(|_| {
+                                        de::Error::invalid_value(
+                                            Unexpected::Str(location.as_str()),
+                                            &"a __DirectiveLocation",
+                                        )
+                                    })
+                                })
+                                .collect::<Result<_, _>>()?,
+                        );
                     }
-                    DEPRECATION_REASON_ALIAS => {
-                        // not supported
-                        access.next_value::<IgnoredAny>()?;
+                    ARGS_ALIAS => {
+                        arguments = DeserializeWith::deserialize_array(&mut access)?;
+                    }
+                    IS_REPEATABLE_ALIAS => {
+                        repeatable = access.next_value::<Option<bool>>()?.unwrap_or_default();
                     }
-                    _ => handle_unexpected_key(key, &mut access)?,
+                    _ => handle_unexpected_key(key.as_str(), &mut access)?,
                 }
             }
 
-            Ok(EnumValue {
+            Ok(DirectiveDefinition {
                 position: Pos::default(),
                 description,
                 name: require_field(NAME_ALIAS, name)?,
-                directives: vec![],
+                arguments: arguments.unwrap_or_default(),
+                repeatable,
+                locations: require_field(LOCATIONS_ALIAS, locations)?,
             })
         }
     }
 
-    deserializer.deserialize_map(EnumValueVisitor)
+    deserializer.deserialize_map(DirectiveDefinitionVisitor(PhantomData))
+}
+
+/// Introspection reports deprecation as `isDeprecated` and `deprecationReason`, which SDL
+/// spells as a `@deprecated(reason: "...")` directive
+fn deprecation_directives<'a, T: Text<'a>>(
+    is_deprecated: bool,
+    reason: Option<String>,
+) -> Vec<Directive<'a, T>> {
+    if !is_deprecated {
+        return vec![];
+    }
+
+    vec![Directive {
+        position: Pos::default(),
+        name: DEPRECATED_DIRECTIVE.into(),
+        arguments: reason
+            .map(|reason| (REASON_ARGUMENT.into(), Value::String(reason)))
+            .into_iter()
+            .collect(),
+    }]
 }
 
 struct DeserializeWith<T: Sized> {
@@ -616,22 +1136,45 @@ where
     }
 }
 
-#[derive(Deserialize)]
 enum TypeKind {
-    #[serde(rename(deserialize = "SCALAR"))]
     Scalar,
-    #[serde(rename(deserialize = "OBJECT"))]
     Object,
-    #[serde(rename(deserialize = "INTERFACE"))]
     Interface,
-    #[serde(rename(deserialize = "UNION"))]
     Union,
-    #[serde(rename(deserialize = "ENUM"))]
     Enum,
-    #[serde(rename(deserialize = "INPUT_OBJECT"))]
     InputObject,
 }
 
+const TYPE_KINDS: &[&str] = &[
+    "SCALAR",
+    "OBJECT",
+    "INTERFACE",
+    "UNION",
+    "ENUM",
+    "INPUT_OBJECT",
+];
+
+/// Kinds are strings in every format, a derived enum would expect a variant index from
+/// formats like bincode
+impl<'de> Deserialize<'de> for TypeKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let kind = CowStr::deserialize(deserializer)?;
+
+        match kind.as_str() {
+            "SCALAR" => Ok(TypeKind::Scalar),
+            "OBJECT" => Ok(TypeKind::Object),
+            "INTERFACE" => Ok(TypeKind::Interface),
+            "UNION" => Ok(TypeKind::Union),
+            "ENUM" => Ok(TypeKind::Enum),
+            "INPUT_OBJECT" => Ok(TypeKind::InputObject),
+            unknown => Err(de::Error::unknown_variant(unknown, TYPE_KINDS)),
+        }
+    }
+}
+
 fn require_field<T, E>(key: &'static str, field: Option<T>) -> Result<T, E>
 where
     E: de::Error,
@@ -650,6 +1193,73 @@ where
     }
 }
 
+/// A string that borrows from the input when it can. Strings with escapes, or strings read
+/// from a stream, can't be borrowed so they fall back to an owned copy.
+struct CowStr<'de>(Cow<'de, str>);
+
+impl<'de> CowStr<'de> {
+    fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Turn this into a name for the document, borrowing it if we can
+    fn into_name<'a, T, E>(self) -> Result<T::Value, E>
+    where
+        T: ParseText<'de, 'a>,
+        E: de::Error,
+    {
+        match self.0 {
+            Cow::Borrowed(name) => Ok(T::from_borrowed(name)),
+            Cow::Owned(name) => T::from_owned(name).map_err(|name| {
+                de::Error::invalid_value(
+                    Unexpected::Str(&name),
+                    &"a name that can be borrowed from the input",
+                )
+            }),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CowStr<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CowStrVisitor;
+
+        impl<'de> Visitor<'de> for CowStrVisitor {
+            type Value = CowStr<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("A string")
+            }
+
+            fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(CowStr(Cow::Borrowed(value)))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(CowStr(Cow::Owned(value.to_string())))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(CowStr(Cow::Owned(value)))
+            }
+        }
+
+        deserializer.deserialize_str(CowStrVisitor)
+    }
+}
+
 fn handle_unexpected_key<'de, M>(key: &str, access: &mut M) -> Result<(), M::Error>
 where
     M: MapAccess<'de>,
@@ -661,3 +1271,113 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::to_introspection;
+    use graphql_parser::parse_schema;
+    use serde_json::json;
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parse_parallel_matches_parse() {
+        let introspection = json!({
+            "data": to_introspection(
+                &parse_schema::<String>(
+                    r#"
+                    schema { query: Query }
+                    type Query { user(id: ID!): User users: [User!]! }
+                    type User { name: String role: Role }
+                    enum Role { ADMIN USER }
+                    input Filter { role: Role = ADMIN }
+                    "#,
+                )
+                .unwrap(),
+            )
+        })
+        .to_string();
+
+        assert_eq!(
+            parse_parallel::<String>(&introspection).unwrap(),
+            parse::<String>(&introspection).unwrap()
+        );
+        assert!(parse_parallel::<String>(r#"{"data": {"__schema": {"types": [1]}}}"#).is_err());
+    }
+
+    #[test]
+    fn deserializes_the_schema_from_cbor() {
+        let introspection = to_introspection(
+            &parse_schema::<String>(
+                r#"
+                type Query { user(id: ID!): User @deprecated(reason: "Use node") }
+                type User { name: String role: Role }
+                enum Role { ADMIN USER }
+                input Filter { role: Role = ADMIN name: String = "x" }
+                directive @cached(ttl: Int = 60) on FIELD
+                "#,
+            )
+            .unwrap(),
+        );
+        let mut cbor = vec![];
+        ciborium::ser::into_writer(&introspection["__schema"], &mut cbor).unwrap();
+
+        // ciborium keeps its Deserializer private, DeserializeWith hands it to deserialize_schema
+        let from_cbor: DeserializeWith<Document<String>> =
+            ciborium::de::from_reader(&cbor[..]).unwrap();
+        let from_json = parse::<String>(&json!({ "data": introspection }).to_string()).unwrap();
+        assert_eq!(from_cbor.value, from_json);
+    }
+
+    #[test]
+    fn borrows_names_in_default_values_only_without_escapes() {
+        let introspection = |default: &str| {
+            json!({
+                "data": to_introspection(
+                    &parse_schema::<String>(&format!(
+                        r#"
+                        type Query {{ users(filter: Filter = {}): Int }}
+                        input Filter {{ name: String role: Role }}
+                        enum Role {{ ADMIN USER }}
+                        "#,
+                        default
+                    ))
+                    .unwrap(),
+                )
+            })
+            .to_string()
+        };
+        fn default_value<'a>(document: &Document<'a, Cow<'a, str>>) -> Value<'a, Cow<'a, str>> {
+            match &document.definitions[0] {
+                Definition::TypeDefinition(TypeDefinition::Object(query)) => {
+                    query.fields[0].arguments[0].default_value.clone().unwrap()
+                }
+                _ => panic!("Query should come first"),
+            }
+        }
+
+        let unescaped = introspection("{role: ADMIN}");
+        assert!(parse::<&str>(&unescaped).is_ok());
+        match default_value(&parse::<Cow<str>>(&unescaped).unwrap()) {
+            Value::Object(fields) => {
+                assert!(matches!(
+                    fields.get("role"),
+                    Some(Value::Enum(Cow::Borrowed("ADMIN")))
+                ))
+            }
+            other => panic!("unexpected default {:?}", other),
+        }
+
+        let escaped = introspection(r#"{name: "x", role: ADMIN}"#);
+        let error = parse::<&str>(&escaped).unwrap_err().to_string();
+        assert!(error.contains("a name that can be borrowed from the input"));
+        match default_value(&parse::<Cow<str>>(&escaped).unwrap()) {
+            Value::Object(fields) => {
+                assert!(
+                    matches!(fields.get("role"), Some(Value::Enum(Cow::Owned(role))) if role == "ADMIN")
+                )
+            }
+            other => panic!("unexpected default {:?}", other),
+        }
+    }
+}
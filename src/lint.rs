@@ -0,0 +1,546 @@
+use crate::relay::{self, RelayRule};
+use crate::schema::{
+    deprecation, named_type, type_definition_name, OperationKind, Schema, BUILT_IN_SCALARS,
+};
+use graphql_parser::schema::{Directive, EnumValue, Field, InputValue, Text, Type, TypeDefinition};
+use graphql_parser::Pos;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Off => "off",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// Type names are PascalCase
+    TypeNames,
+    /// Field, argument and input field names are camelCase
+    FieldNames,
+    /// Enum values are SCREAMING_CASE
+    EnumValues,
+    /// Every type, field and input field has a description
+    Descriptions,
+    /// Output fields of type `ID` are non-null
+    NullableIds,
+    /// Deprecated fields and enum values say why
+    DeprecationReasons,
+    /// Input object arguments of mutations are named `*Input`
+    MutationInputs,
+    /// Connections, edges, `PageInfo` and pagination arguments follow the Relay spec, see
+    /// `relay::check`
+    RelayConnections,
+}
+
+pub const RULES: &[Rule] = &[
+    Rule::TypeNames,
+    Rule::FieldNames,
+    Rule::EnumValues,
+    Rule::Descriptions,
+    Rule::NullableIds,
+    Rule::DeprecationReasons,
+    Rule::MutationInputs,
+    Rule::RelayConnections,
+];
+
+impl Rule {
+    /// The name used for the rule in config files and messages
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::TypeNames => "type-names",
+            Rule::FieldNames => "field-names",
+            Rule::EnumValues => "enum-values",
+            Rule::Descriptions => "descriptions",
+            Rule::NullableIds => "nullable-ids",
+            Rule::DeprecationReasons => "deprecation-reasons",
+            Rule::MutationInputs => "mutation-inputs",
+            Rule::RelayConnections => "relay-connections",
+        }
+    }
+
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Rule::TypeNames | Rule::FieldNames | Rule::EnumValues => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+/// Which rules to run and how seriously to take them, rules that aren't listed keep their
+/// default severity. In TOML:
+///
+/// ```toml
+/// [rules]
+/// descriptions = "off"
+/// nullable-ids = "error"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    pub rules: HashMap<Rule, Severity>,
+}
+
+impl LintConfig {
+    pub fn from_toml(raw_config: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(raw_config)
+    }
+
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.rules
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintMessage {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    /// Where the offending definition starts, only set if the schema was parsed with positions
+    pub position: Pos,
+}
+
+impl fmt::Display for LintMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {} [{}]",
+            self.position.line,
+            self.position.column,
+            self.severity,
+            self.message,
+            self.rule.name()
+        )
+    }
+}
+
+/// Check every type in the schema against the rules in `config`, ordered by position.
+/// Introspection's own `__` types are skipped.
+pub fn lint<'a, T: Text<'a>>(schema: &Schema<'a, T>, config: &LintConfig) -> Vec<LintMessage> {
+    let mut linter = Linter {
+        schema,
+        config,
+        messages: vec![],
+    };
+
+    for type_definition in schema.types() {
+        if !type_definition_name(type_definition).starts_with("__") {
+            linter.type_definition(type_definition);
+        }
+    }
+    linter.mutation_inputs();
+    linter.relay_connections();
+
+    let mut messages = linter.messages;
+    messages.sort_by_key(|message| (message.position.line, message.position.column));
+    messages
+}
+
+struct Linter<'l, 'a, T: Text<'a>> {
+    schema: &'l Schema<'a, T>,
+    config: &'l LintConfig,
+    messages: Vec<LintMessage>,
+}
+
+impl<'l, 'a, T: Text<'a>> Linter<'l, 'a, T> {
+    fn report(&mut self, rule: Rule, position: Pos, message: String) {
+        let severity = self.config.severity(rule);
+        if severity != Severity::Off {
+            self.messages.push(LintMessage {
+                rule,
+                severity,
+                message,
+                position,
+            });
+        }
+    }
+
+    fn type_definition(&mut self, type_definition: &TypeDefinition<'a, T>) {
+        let name = type_definition_name(type_definition);
+        let (position, description) = match type_definition {
+            TypeDefinition::Scalar(scalar) => (scalar.position, &scalar.description),
+            TypeDefinition::Object(object) => (object.position, &object.description),
+            TypeDefinition::Interface(interface) => (interface.position, &interface.description),
+            TypeDefinition::Union(union) => (union.position, &union.description),
+            TypeDefinition::Enum(enum_type) => (enum_type.position, &enum_type.description),
+            TypeDefinition::InputObject(input_object) => {
+                (input_object.position, &input_object.description)
+            }
+        };
+
+        if !is_pascal_case(name) {
+            self.report(
+                Rule::TypeNames,
+                position,
+                format!("type '{}' should be PascalCase", name),
+            );
+        }
+        if !has_description(description) && !BUILT_IN_SCALARS.contains(&name) {
+            self.report(
+                Rule::Descriptions,
+                position,
+                format!("type '{}' has no description", name),
+            );
+        }
+
+        match type_definition {
+            TypeDefinition::Object(object) => self.fields(name, &object.fields),
+            TypeDefinition::Interface(interface) => self.fields(name, &interface.fields),
+            TypeDefinition::Enum(enum_type) => self.enum_values(name, &enum_type.values),
+            TypeDefinition::InputObject(input_object) => {
+                for input_field in &input_object.fields {
+                    self.input_value(name, input_field, "input field");
+                    if !has_description(&input_field.description) {
+                        self.report(
+                            Rule::Descriptions,
+                            input_field.position,
+                            format!(
+                                "input field '{}.{}' has no description",
+                                name,
+                                input_field.name.as_ref()
+                            ),
+                        );
+                    }
+                }
+            }
+            TypeDefinition::Scalar(_) | TypeDefinition::Union(_) => {}
+        }
+    }
+
+    fn fields(&mut self, type_name: &str, fields: &[Field<'a, T>]) {
+        for field in fields {
+            let coordinate = format!("{}.{}", type_name, field.name.as_ref());
+
+            if !is_camel_case(field.name.as_ref()) {
+                self.report(
+                    Rule::FieldNames,
+                    field.position,
+                    format!("field '{}' should be camelCase", coordinate),
+                );
+            }
+            if !has_description(&field.description) {
+                self.report(
+                    Rule::Descriptions,
+                    field.position,
+                    format!("field '{}' has no description", coordinate),
+                );
+            }
+            if let Type::NamedType(name) = &field.field_type {
+                if name.as_ref() == "ID" {
+                    self.report(
+                        Rule::NullableIds,
+                        field.position,
+                        format!("field '{}' is a nullable ID, use ID!", coordinate),
+                    );
+                }
+            }
+            self.deprecation_reason(&coordinate, field.position, &field.directives);
+
+            for argument in &field.arguments {
+                self.input_value(&coordinate, argument, "argument");
+            }
+        }
+    }
+
+    fn input_value(&mut self, parent: &str, input_value: &InputValue<'a, T>, what: &str) {
+        if !is_camel_case(input_value.name.as_ref()) {
+            self.report(
+                Rule::FieldNames,
+                input_value.position,
+                format!(
+                    "{} '{}.{}' should be camelCase",
+                    what,
+                    parent,
+                    input_value.name.as_ref()
+                ),
+            );
+        }
+    }
+
+    fn enum_values(&mut self, type_name: &str, values: &[EnumValue<'a, T>]) {
+        for value in values {
+            let coordinate = format!("{}.{}", type_name, value.name.as_ref());
+
+            if !is_screaming_case(value.name.as_ref()) {
+                self.report(
+                    Rule::EnumValues,
+                    value.position,
+                    format!("enum value '{}' should be SCREAMING_CASE", coordinate),
+                );
+            }
+            self.deprecation_reason(&coordinate, value.position, &value.directives);
+        }
+    }
+
+    fn deprecation_reason(
+        &mut self,
+        coordinate: &str,
+        position: Pos,
+        directives: &[Directive<'a, T>],
+    ) {
+        let has_reason = deprecation(directives).map(
+            |deprecation| matches!(deprecation.reason, Some(reason) if !reason.trim().is_empty()),
+        );
+
+        if has_reason == Some(false) {
+            self.report(
+                Rule::DeprecationReasons,
+                position,
+                format!("'{}' is deprecated without a reason", coordinate),
+            );
+        }
+    }
+
+    fn mutation_inputs(&mut self) {
+        let schema = self.schema;
+        let mutation = match schema.root_type(OperationKind::Mutation) {
+            Some(mutation) => mutation,
+            None => return,
+        };
+
+        for field in &mutation.fields {
+            for argument in &field.arguments {
+                let input_type = named_type(&argument.value_type);
+                let is_input_object = matches!(
+                    schema.get_type(input_type),
+                    Some(TypeDefinition::InputObject(_))
+                );
+
+                if is_input_object && !input_type.ends_with("Input") {
+                    self.report(
+                        Rule::MutationInputs,
+                        argument.position,
+                        format!(
+                            "argument '{}.{}.{}' takes '{}', which should be named '*Input'",
+                            mutation.name.as_ref(),
+                            field.name.as_ref(),
+                            argument.name.as_ref(),
+                            input_type
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Connection conventions come from the Relay checker, leaving out object identification
+    /// as plenty of schemas use connections without `Node`
+    fn relay_connections(&mut self) {
+        for finding in relay::check(self.schema) {
+            match finding.rule {
+                RelayRule::NodeInterface | RelayRule::NodeField => {}
+                _ => self.report(
+                    Rule::RelayConnections,
+                    finding.position,
+                    finding.to_string(),
+                ),
+            }
+        }
+    }
+}
+
+fn has_description(description: &Option<String>) -> bool {
+    matches!(description, Some(description) if !description.trim().is_empty())
+}
+
+fn is_pascal_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_camel_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_screaming_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+    use std::collections::HashSet;
+
+    const SCHEMA: &str = r#"
+        schema { query: Query mutation: Mutation }
+        "The root"
+        type Query {
+            "Snake case"
+            user_name: String
+            "Nullable"
+            id: ID
+            "No reason"
+            old: Int @deprecated
+            "No page info"
+            users: UserConnection
+        }
+        "Lower case"
+        type lowerCase { "Fine" a: Int }
+        "Lower case values"
+        enum Role { "Fine" ADMIN "Lower" guest }
+        "Mutations"
+        type Mutation { "Takes UserData" create("Data" data: UserData): Int }
+        "Not named Input"
+        input UserData { name: String }
+        "Connection"
+        type UserConnection { "Edges" edges: [UserEdge] }
+        "Edge"
+        type UserEdge { "Node" node: Query "Cursor" cursor: String! }
+        "Page info"
+        type PageInfo {
+            "Next" hasNextPage: Boolean!
+            "Previous" hasPreviousPage: Boolean!
+            "Start" startCursor: String
+            "End" endCursor: String
+        }
+    "#;
+
+    fn lint_sdl(config: &LintConfig) -> Vec<LintMessage> {
+        lint(
+            &Schema::new(parse_schema::<String>(SCHEMA).unwrap()),
+            config,
+        )
+    }
+
+    fn messages(messages: &[LintMessage], rule: Rule) -> Vec<(&str, Severity)> {
+        messages
+            .iter()
+            .filter(|message| message.rule == rule)
+            .map(|message| (message.message.as_str(), message.severity))
+            .collect()
+    }
+
+    #[test]
+    fn every_rule_fires() {
+        let found = lint_sdl(&LintConfig::default());
+
+        let rules: HashSet<Rule> = found.iter().map(|message| message.rule).collect();
+        assert_eq!(rules, RULES.iter().copied().collect());
+
+        assert_eq!(
+            messages(&found, Rule::TypeNames),
+            vec![("type 'lowerCase' should be PascalCase", Severity::Error)]
+        );
+        assert_eq!(
+            messages(&found, Rule::FieldNames),
+            vec![(
+                "field 'Query.user_name' should be camelCase",
+                Severity::Error
+            )]
+        );
+        assert_eq!(
+            messages(&found, Rule::EnumValues),
+            vec![(
+                "enum value 'Role.guest' should be SCREAMING_CASE",
+                Severity::Error
+            )]
+        );
+        assert_eq!(
+            messages(&found, Rule::Descriptions),
+            vec![(
+                "input field 'UserData.name' has no description",
+                Severity::Warning
+            )]
+        );
+        assert_eq!(
+            messages(&found, Rule::NullableIds),
+            vec![(
+                "field 'Query.id' is a nullable ID, use ID!",
+                Severity::Warning
+            )]
+        );
+        assert_eq!(
+            messages(&found, Rule::DeprecationReasons),
+            vec![(
+                "'Query.old' is deprecated without a reason",
+                Severity::Warning
+            )]
+        );
+        assert_eq!(
+            messages(&found, Rule::MutationInputs),
+            vec![(
+                "argument 'Mutation.create.data' takes 'UserData', which should be named '*Input'",
+                Severity::Warning
+            )]
+        );
+        assert_eq!(
+            messages(&found, Rule::RelayConnections),
+            vec![
+                // ordered by position
+                (
+                    "Query.users: users returns a connection but takes neither first: Int and \
+                     an after cursor, nor last: Int and a before cursor",
+                    Severity::Warning
+                ),
+                (
+                    "UserConnection: UserConnection has no pageInfo field",
+                    Severity::Warning
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn severities_are_configured_from_toml() {
+        let config = LintConfig::from_toml(
+            r#"
+            [rules]
+            descriptions = "off"
+            nullable-ids = "error"
+            type-names = "warning"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.severity(Rule::Descriptions), Severity::Off);
+        assert_eq!(config.severity(Rule::EnumValues), Severity::Error);
+
+        let found = lint_sdl(&config);
+        assert!(messages(&found, Rule::Descriptions).is_empty());
+        assert_eq!(
+            messages(&found, Rule::NullableIds),
+            vec![(
+                "field 'Query.id' is a nullable ID, use ID!",
+                Severity::Error
+            )]
+        );
+        assert_eq!(
+            messages(&found, Rule::TypeNames),
+            vec![("type 'lowerCase' should be PascalCase", Severity::Warning)]
+        );
+    }
+
+    #[test]
+    fn rules_can_be_turned_off() {
+        let mut config = LintConfig::default();
+        for rule in RULES {
+            config.rules.insert(*rule, Severity::Off);
+        }
+
+        assert!(lint_sdl(&config).is_empty());
+        assert!(LintConfig::from_toml("[rules]\nno-such-rule = \"off\"").is_err());
+        assert!(LintConfig::from_toml("[rules]\ndescriptions = \"loud\"").is_err());
+    }
+}
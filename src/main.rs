@@ -0,0 +1,766 @@
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use graphql_introspection_parser::cost::{analyze_cost, CostConfig};
+use graphql_introspection_parser::coverage::coverage;
+use graphql_introspection_parser::deprecations::{self, DeprecatedElement};
+use graphql_introspection_parser::graph::{GraphOptions, TypeGraph};
+use graphql_introspection_parser::html::{self, HtmlOptions};
+use graphql_introspection_parser::introspection::to_introspection;
+use graphql_introspection_parser::json_schema::{self, JsonSchemaOptions};
+use graphql_introspection_parser::lint::{self, LintConfig, Severity};
+use graphql_introspection_parser::markdown::{self, MarkdownLayout, MarkdownOptions};
+#[cfg(feature = "server")]
+use graphql_introspection_parser::mock::MockOptions;
+use graphql_introspection_parser::operations::{generate_operations, OperationOptions};
+use graphql_introspection_parser::prune::prune;
+#[cfg(feature = "server")]
+use graphql_introspection_parser::server;
+use graphql_introspection_parser::typescript::{self, EnumStyle, TypeScriptOptions};
+use graphql_introspection_parser::{parse, parse_with_positions, Schema};
+use graphql_parser::query;
+use graphql_parser::schema::Document;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const SCHEMA_ARG: &str = "SCHEMA";
+const OUTPUT_ARG: &str = "output";
+const SCALAR_ARG: &str = "scalar";
+const ENUMS_ARG: &str = "enums";
+const INCLUDE_OUTPUT_TYPES_ARG: &str = "include-output-types";
+const PORT_ARG: &str = "port";
+const SEED_ARG: &str = "seed";
+const CONFIG_ARG: &str = "config";
+const OPERATIONS_ARG: &str = "OPERATIONS";
+const INTROSPECTION_ARG: &str = "introspection";
+const FORMAT_ARG: &str = "format";
+const ROOT_ARG: &str = "root";
+const DEPTH_ARG: &str = "depth";
+const HIDE_SCALARS_ARG: &str = "hide-scalars";
+const NO_INPUT_TYPES_ARG: &str = "no-input-types";
+const OUT_DIR_ARG: &str = "out-dir";
+const TITLE_ARG: &str = "title";
+const LAYOUT_ARG: &str = "layout";
+const OPERATION_ARG: &str = "OPERATION";
+const MAX_COST_ARG: &str = "max-cost";
+const MAX_DEPTH_ARG: &str = "max-depth";
+const INCLUDE_DEPRECATED_ARG: &str = "include-deprecated";
+
+fn main() {
+    let matches = App::new("graphql-introspect")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Tools for the result of a GraphQL introspection query")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("typescript")
+                .about("Generate TypeScript declarations for every type in the schema")
+                .arg(schema_arg())
+                .arg(output_arg())
+                .arg(scalar_arg())
+                .arg(
+                    Arg::with_name(ENUMS_ARG)
+                        .long(ENUMS_ARG)
+                        .takes_value(true)
+                        .possible_values(&["union", "enum"])
+                        .default_value("union")
+                        .help("Render enums as string unions or TypeScript enums"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("json-schema")
+                .about("Export input types, enums and scalars as a JSON Schema bundle")
+                .arg(schema_arg())
+                .arg(output_arg())
+                .arg(scalar_arg())
+                .arg(
+                    Arg::with_name(INCLUDE_OUTPUT_TYPES_ARG)
+                        .long(INCLUDE_OUTPUT_TYPES_ARG)
+                        .help("Also export objects, interfaces and unions"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Answer GraphQL requests on localhost with mock data")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(PORT_ARG)
+                        .short("p")
+                        .long(PORT_ARG)
+                        .takes_value(true)
+                        .default_value("4000")
+                        .help("The port to listen on"),
+                )
+                .arg(
+                    Arg::with_name(SEED_ARG)
+                        .long(SEED_ARG)
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Seed for the mock data, the same seed gives the same responses"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lint")
+                .about("Check the schema's naming, descriptions and conventions")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(CONFIG_ARG)
+                        .short("c")
+                        .long(CONFIG_ARG)
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("A TOML file setting the severity of each rule"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("prune")
+                .about("Cut the schema down to what a set of operations uses")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(OPERATIONS_ARG)
+                        .required(true)
+                        .help("A directory of .graphql files with the operations and fragments"),
+                )
+                .arg(
+                    Arg::with_name(INTROSPECTION_ARG)
+                        .long(INTROSPECTION_ARG)
+                        .help("Write an introspection result instead of SDL"),
+                )
+                .arg(output_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("graph")
+                .about("Export which types depend on which as a Graphviz or Mermaid graph")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(FORMAT_ARG)
+                        .long(FORMAT_ARG)
+                        .takes_value(true)
+                        .possible_values(&["dot", "mermaid"])
+                        .default_value("dot")
+                        .help("Graphviz DOT or a Mermaid flowchart"),
+                )
+                .arg(
+                    Arg::with_name(ROOT_ARG)
+                        .long(ROOT_ARG)
+                        .takes_value(true)
+                        .value_name("TYPE")
+                        .help("Only include the types this type leads to"),
+                )
+                .arg(
+                    Arg::with_name(DEPTH_ARG)
+                        .long(DEPTH_ARG)
+                        .takes_value(true)
+                        .requires(ROOT_ARG)
+                        .help("How many edges away from --root to go"),
+                )
+                .arg(
+                    Arg::with_name(HIDE_SCALARS_ARG)
+                        .long(HIDE_SCALARS_ARG)
+                        .help("Leave out scalars"),
+                )
+                .arg(
+                    Arg::with_name(NO_INPUT_TYPES_ARG)
+                        .long(NO_INPUT_TYPES_ARG)
+                        .help("Leave out input objects"),
+                )
+                .arg(output_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("docs")
+                .about("Write a static HTML documentation site for the schema")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(OUT_DIR_ARG)
+                        .long(OUT_DIR_ARG)
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .default_value("docs")
+                        .help("The directory to write the site to"),
+                )
+                .arg(
+                    Arg::with_name(TITLE_ARG)
+                        .long(TITLE_ARG)
+                        .takes_value(true)
+                        .help("The title shown on every page"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("markdown")
+                .about("Write Markdown reference documentation for the schema")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(OUT_DIR_ARG)
+                        .long(OUT_DIR_ARG)
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .default_value("docs")
+                        .help("The directory to write the Markdown files to"),
+                )
+                .arg(
+                    Arg::with_name(LAYOUT_ARG)
+                        .long(LAYOUT_ARG)
+                        .takes_value(true)
+                        .possible_values(&["kind", "type"])
+                        .default_value("kind")
+                        .help("One file per kind of type, or one file per type"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cost")
+                .about("Estimate the depth, breadth and cost of operations")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(OPERATION_ARG)
+                        .required(true)
+                        .help("A .graphql file with the operations and their fragments"),
+                )
+                .arg(
+                    Arg::with_name(CONFIG_ARG)
+                        .short("c")
+                        .long(CONFIG_ARG)
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help(
+                            "A TOML file with field costs and list sizes, as introspection \
+                             doesn't include @cost directives",
+                        ),
+                )
+                .arg(
+                    Arg::with_name(MAX_COST_ARG)
+                        .long(MAX_COST_ARG)
+                        .takes_value(true)
+                        .help("Fail if any operation costs more than this"),
+                )
+                .arg(
+                    Arg::with_name(MAX_DEPTH_ARG)
+                        .long(MAX_DEPTH_ARG)
+                        .takes_value(true)
+                        .help("Fail if any operation nests fields deeper than this"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("operations")
+                .about(
+                    "Write an example operation for every query, mutation and subscription field",
+                )
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(DEPTH_ARG)
+                        .long(DEPTH_ARG)
+                        .takes_value(true)
+                        .default_value("3")
+                        .help("How many levels of fields to select, counting the root field"),
+                )
+                .arg(
+                    Arg::with_name(INCLUDE_DEPRECATED_ARG)
+                        .long(INCLUDE_DEPRECATED_ARG)
+                        .help("Select deprecated fields as well"),
+                )
+                .arg(output_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("coverage")
+                .about("Report which types, fields, arguments and enum values operations use")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(OPERATIONS_ARG)
+                        .required(true)
+                        .help("A directory of .graphql files with the operations and fragments"),
+                )
+                .arg(
+                    Arg::with_name(FORMAT_ARG)
+                        .long(FORMAT_ARG)
+                        .takes_value(true)
+                        .possible_values(&["text", "json", "lcov"])
+                        .default_value("text")
+                        .help("A summary per type, the full report as JSON, or an LCOV tracefile"),
+                )
+                .arg(output_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("deprecations")
+                .about("Plan which deprecated fields and enum values operations still use")
+                .arg(schema_arg())
+                .arg(
+                    Arg::with_name(OPERATIONS_ARG)
+                        .required(true)
+                        .help("A directory of .graphql files with the operations and fragments"),
+                )
+                .arg(
+                    Arg::with_name(FORMAT_ARG)
+                        .long(FORMAT_ARG)
+                        .takes_value(true)
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .help("A readable report or the plan as JSON"),
+                )
+                .arg(output_arg()),
+        )
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        ("typescript", Some(matches)) => typescript_command(matches),
+        ("json-schema", Some(matches)) => json_schema_command(matches),
+        ("serve", Some(matches)) => serve_command(matches),
+        ("lint", Some(matches)) => lint_command(matches),
+        ("prune", Some(matches)) => prune_command(matches),
+        ("graph", Some(matches)) => graph_command(matches),
+        ("docs", Some(matches)) => docs_command(matches),
+        ("markdown", Some(matches)) => markdown_command(matches),
+        ("cost", Some(matches)) => cost_command(matches),
+        ("operations", Some(matches)) => operations_command(matches),
+        ("coverage", Some(matches)) => coverage_command(matches),
+        ("deprecations", Some(matches)) => deprecations_command(matches),
+        _ => unreachable!("clap requires a subcommand"),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn typescript_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let document = read_schema(matches)?;
+    let options = TypeScriptOptions {
+        enum_style: match matches.value_of(ENUMS_ARG) {
+            Some("enum") => EnumStyle::Enum,
+            _ => EnumStyle::StringUnion,
+        },
+        scalars: scalar_mappings(matches)?,
+        ..TypeScriptOptions::default()
+    };
+
+    write_output(matches, &typescript::generate(&document, &options))
+}
+
+fn json_schema_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let document = read_schema(matches)?;
+    let scalars = scalar_mappings(matches)?
+        .into_iter()
+        .map(|(name, schema)| {
+            serde_json::from_str(&schema)
+                .map(|schema| (name, schema))
+                .map_err(|e| format!("--scalar expects a JSON Schema, {}", e))
+        })
+        .collect::<Result<_, _>>()?;
+    let options = JsonSchemaOptions {
+        include_output_types: matches.is_present(INCLUDE_OUTPUT_TYPES_ARG),
+        scalars,
+    };
+
+    let bundle = json_schema::generate(&document, &options);
+    write_output(matches, &format!("{:#}\n", bundle))
+}
+
+#[cfg(feature = "server")]
+fn serve_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let schema = Schema::new(read_schema(matches)?);
+    let port: u16 = numeric_arg(matches, PORT_ARG)?;
+    let options = MockOptions {
+        seed: numeric_arg(matches, SEED_ARG)?,
+        ..MockOptions::default()
+    };
+
+    eprintln!("Serving mock responses on http://127.0.0.1:{}", port);
+    server::serve(&schema, port, &options)
+}
+
+#[cfg(not(feature = "server"))]
+fn serve_command(_matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    Err("serve needs the server feature, install with `--features server`".into())
+}
+
+fn lint_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    // positions let every message point at its spot in the JSON
+    let schema = Schema::new(read_schema_with(matches, |raw| parse_with_positions(raw))?);
+    let config = match matches.value_of(CONFIG_ARG) {
+        Some(path) => {
+            let raw_config =
+                fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+            LintConfig::from_toml(&raw_config)
+                .map_err(|e| format!("couldn't parse {}: {}", path, e))?
+        }
+        None => LintConfig::default(),
+    };
+
+    let messages = lint::lint(&schema, &config);
+    let schema_path = matches.value_of(SCHEMA_ARG).expect("SCHEMA is required");
+    for message in &messages {
+        println!("{}:{}", schema_path, message);
+    }
+
+    let errors = messages
+        .iter()
+        .filter(|message| message.severity == Severity::Error)
+        .count();
+    eprintln!("{} errors, {} warnings", errors, messages.len() - errors);
+
+    if errors > 0 {
+        Err(format!("{} failed linting", schema_path).into())
+    } else {
+        Ok(())
+    }
+}
+
+fn prune_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let schema = Schema::new(read_schema(matches)?);
+    let operations: Vec<_> = read_operations(matches)?
+        .into_iter()
+        .map(|(_, document)| document)
+        .collect();
+
+    let pruned = prune(&schema, &operations)?;
+    if matches.is_present(INTROSPECTION_ARG) {
+        write_output(matches, &format!("{:#}\n", to_introspection(&pruned)))
+    } else {
+        write_output(matches, &pruned.to_string())
+    }
+}
+
+fn graph_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let schema = Schema::new(read_schema(matches)?);
+    let options = GraphOptions {
+        root: matches.value_of(ROOT_ARG).map(str::to_string),
+        depth: match matches.value_of(DEPTH_ARG) {
+            Some(_) => Some(numeric_arg(matches, DEPTH_ARG)?),
+            None => None,
+        },
+        hide_scalars: matches.is_present(HIDE_SCALARS_ARG),
+        input_types: !matches.is_present(NO_INPUT_TYPES_ARG),
+    };
+    if let Some(root) = &options.root {
+        if schema.get_type(root).is_none() {
+            return Err(format!("unknown type '{}' for --root", root).into());
+        }
+    }
+
+    let graph = TypeGraph::new(schema.document(), &options);
+    match matches.value_of(FORMAT_ARG) {
+        Some("mermaid") => write_output(matches, &graph.to_mermaid()),
+        _ => write_output(matches, &graph.to_dot()),
+    }
+}
+
+fn docs_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let schema = Schema::new(read_schema(matches)?);
+    let mut options = HtmlOptions::default();
+    if let Some(title) = matches.value_of(TITLE_ARG) {
+        options.title = title.to_string();
+    }
+
+    let out_dir = Path::new(matches.value_of(OUT_DIR_ARG).unwrap_or_default());
+    for file in html::generate(&schema, &options) {
+        write_file(&out_dir.join(&file.path), &file.contents)?;
+    }
+    eprintln!("Wrote the documentation to {}", out_dir.display());
+
+    Ok(())
+}
+
+fn markdown_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let document = read_schema(matches)?;
+    let options = MarkdownOptions {
+        layout: match matches.value_of(LAYOUT_ARG) {
+            Some("type") => MarkdownLayout::ByType,
+            _ => MarkdownLayout::ByKind,
+        },
+    };
+
+    let out_dir = Path::new(matches.value_of(OUT_DIR_ARG).unwrap_or_default());
+    for file in markdown::generate(&document, &options) {
+        write_file(&out_dir.join(&file.path), &file.contents)?;
+    }
+    eprintln!("Wrote the documentation to {}", out_dir.display());
+
+    Ok(())
+}
+
+fn cost_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let schema = Schema::new(read_schema(matches)?);
+    let config = match matches.value_of(CONFIG_ARG) {
+        Some(path) => {
+            let raw_config =
+                fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+            CostConfig::from_toml(&raw_config)
+                .map_err(|e| format!("couldn't parse {}: {}", path, e))?
+        }
+        None => CostConfig::default(),
+    };
+    let max_cost: Option<u64> = match matches.value_of(MAX_COST_ARG) {
+        Some(_) => Some(numeric_arg(matches, MAX_COST_ARG)?),
+        None => None,
+    };
+    let max_depth: Option<usize> = match matches.value_of(MAX_DEPTH_ARG) {
+        Some(_) => Some(numeric_arg(matches, MAX_DEPTH_ARG)?),
+        None => None,
+    };
+
+    let path = matches
+        .value_of(OPERATION_ARG)
+        .expect("OPERATION is required");
+    let raw_operations =
+        fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+    let operations = query::parse_query::<&str>(&raw_operations)
+        .map_err(|e| format!("couldn't parse {}: {}", path, e))?;
+
+    let mut over_limit = vec![];
+    for operation in analyze_cost(&schema, &operations, &config)? {
+        let name = operation.name.as_deref().unwrap_or("(anonymous)");
+        println!(
+            "{}: cost {}, depth {}, breadth {}, aliases {}",
+            name, operation.cost, operation.depth, operation.breadth, operation.aliases
+        );
+        for field in &operation.fields {
+            println!(
+                "  {} {} x {} = {}",
+                field.path, field.weight, field.multiplier, field.cost
+            );
+        }
+
+        if matches!(max_cost, Some(max_cost) if operation.cost > max_cost)
+            || matches!(max_depth, Some(max_depth) if operation.depth > max_depth)
+        {
+            over_limit.push(name.to_string());
+        }
+    }
+
+    if over_limit.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} went over the limits", over_limit.join(", ")).into())
+    }
+}
+
+fn operations_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let schema = Schema::new(read_schema(matches)?);
+    let options = OperationOptions {
+        depth: numeric_arg(matches, DEPTH_ARG)?,
+        include_deprecated: matches.is_present(INCLUDE_DEPRECATED_ARG),
+    };
+
+    write_output(matches, &generate_operations(&schema, &options).to_string())
+}
+
+fn coverage_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let schema = Schema::new(read_schema(matches)?);
+    let operations = read_operations(matches)?;
+    let sources: Vec<_> = operations
+        .iter()
+        .map(|(path, document)| (path.as_str(), document))
+        .collect();
+
+    let report = coverage(&schema, &sources)?;
+    let output = match matches.value_of(FORMAT_ARG) {
+        Some("json") => format!("{}\n", serde_json::to_string_pretty(&report)?),
+        Some("lcov") => report.to_lcov(),
+        _ => {
+            let mut output = String::new();
+            for type_coverage in &report.types {
+                output.push_str(&format!(
+                    "{:>6.1}% {:>4}/{:<4} {}\n",
+                    type_coverage.percentage,
+                    type_coverage.covered,
+                    type_coverage.total,
+                    type_coverage.name
+                ));
+            }
+            output.push_str(&format!(
+                "{:>6.1}% {:>4}/{:<4} total\n",
+                report.percentage, report.covered, report.total
+            ));
+            output
+        }
+    };
+
+    write_output(matches, &output)
+}
+
+fn deprecations_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let schema = Schema::new(read_schema(matches)?);
+    let operations = read_operations(matches)?;
+    let sources: Vec<_> = operations
+        .iter()
+        .map(|(path, document)| (path.as_str(), document))
+        .collect();
+
+    let plan = deprecations::plan(&schema, &sources)?;
+    if matches.value_of(FORMAT_ARG) == Some("json") {
+        return write_output(
+            matches,
+            &format!("{}\n", serde_json::to_string_pretty(&plan)?),
+        );
+    }
+
+    let element = |output: &mut String, element: &DeprecatedElement| {
+        output.push_str(&format!("  {}", element.coordinate));
+        if let Some(reason) = &element.reason {
+            output.push_str(&format!(" ({})", reason));
+        }
+        output.push('\n');
+        for location in &element.references {
+            output.push_str(&format!("    {}\n", location));
+        }
+    };
+
+    let mut output = format!("Deprecated and still used ({}):\n", plan.still_used.len());
+    for still_used in &plan.still_used {
+        element(&mut output, still_used);
+    }
+    output.push_str(&format!(
+        "\nDeprecated and safe to remove ({}):\n",
+        plan.removable.len()
+    ));
+    for removable in &plan.removable {
+        element(&mut output, removable);
+    }
+    output.push_str(&format!(
+        "\nUnused, candidates for deprecation ({}):\n",
+        plan.unused_fields.len()
+    ));
+    for field in &plan.unused_fields {
+        output.push_str(&format!("  {}\n", field));
+    }
+
+    write_output(matches, &output)
+}
+
+/// Write a file, creating the directories it's in
+fn write_file(path: &Path, contents: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("couldn't create {}: {}", dir.display(), e))?;
+    }
+
+    fs::write(path, contents)
+        .map_err(|e| format!("couldn't write {}: {}", path.display(), e).into())
+}
+
+/// Operations along with the path of the file they're from
+type Operations = Vec<(String, query::Document<'static, String>)>;
+
+/// Parse every `.graphql` file in the OPERATIONS directory, sorted by path
+fn read_operations(matches: &ArgMatches) -> Result<Operations, Box<dyn Error>> {
+    let operations_dir = matches
+        .value_of(OPERATIONS_ARG)
+        .expect("OPERATIONS is required");
+
+    let mut paths = vec![];
+    graphql_files(Path::new(operations_dir), &mut paths)?;
+    paths.sort();
+
+    let mut operations = vec![];
+    for path in paths {
+        let raw_operations = fs::read_to_string(&path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        let document = query::parse_query::<String>(&raw_operations)
+            .map_err(|e| format!("couldn't parse {}: {}", path.display(), e))?
+            .into_static();
+        operations.push((path.display().to_string(), document));
+    }
+
+    Ok(operations)
+}
+
+/// Every `.graphql` file in `dir` and its subdirectories
+fn graphql_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("couldn't read {}: {}", dir.display(), e))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("couldn't read {}: {}", dir.display(), e))?
+            .path();
+        if path.is_dir() {
+            graphql_files(&path, paths)?;
+        } else if matches!(path.extension(), Some(extension) if extension == "graphql") {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn schema_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(SCHEMA_ARG)
+        .required(true)
+        .help("The JSON response of an introspection query")
+}
+
+fn output_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(OUTPUT_ARG)
+        .short("o")
+        .long(OUTPUT_ARG)
+        .takes_value(true)
+        .help("Write to this file instead of stdout")
+}
+
+fn scalar_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(SCALAR_ARG)
+        .long(SCALAR_ARG)
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("NAME=TYPE")
+        .help("Map a custom scalar onto a type, can be repeated")
+}
+
+fn read_schema(matches: &ArgMatches) -> Result<Document<'static, String>, Box<dyn Error>> {
+    read_schema_with(matches, |raw| parse(raw))
+}
+
+fn read_schema_with<F>(
+    matches: &ArgMatches,
+    parse: F,
+) -> Result<Document<'static, String>, Box<dyn Error>>
+where
+    F: Fn(&str) -> serde_json::Result<Document<'static, String>>,
+{
+    let path = matches.value_of(SCHEMA_ARG).expect("SCHEMA is required");
+    let raw_introspection =
+        fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+
+    parse(&raw_introspection).map_err(|e| format!("couldn't parse {}: {}", path, e).into())
+}
+
+fn scalar_mappings(matches: &ArgMatches) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    matches
+        .values_of(SCALAR_ARG)
+        .into_iter()
+        .flatten()
+        .map(|mapping| {
+            let mut parts = mapping.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(scalar_type)) if !name.is_empty() => {
+                    Ok((name.to_string(), scalar_type.to_string()))
+                }
+                _ => Err(format!("expected NAME=TYPE for --scalar, found '{}'", mapping).into()),
+            }
+        })
+        .collect()
+}
+
+fn numeric_arg<T>(matches: &ArgMatches, name: &str) -> Result<T, Box<dyn Error>>
+where
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    let value = matches.value_of(name).unwrap_or_default();
+
+    value
+        .parse()
+        .map_err(|e| format!("invalid --{} '{}': {}", name, value, e).into())
+}
+
+fn write_output(matches: &ArgMatches, output: &str) -> Result<(), Box<dyn Error>> {
+    match matches.value_of(OUTPUT_ARG) {
+        Some(path) => {
+            fs::write(path, output).map_err(|e| format!("couldn't write {}: {}", path, e).into())
+        }
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,503 @@
+use crate::schema::{
+    default_value_source, deprecation, named_type, type_definition_name, type_description,
+    Deprecation,
+};
+use graphql_parser::schema::{
+    Definition, Directive, DirectiveDefinition, Document, Field, InputValue, Text, Type,
+    TypeDefinition,
+};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkdownLayout {
+    /// `objects.md`, `interfaces.md`, `unions.md`, `enums.md`, `inputs.md`, `scalars.md` and
+    /// `directives.md`
+    ByKind,
+    /// `User.md` for each type along with `directives.md` and an `index.md` listing them
+    ByType,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarkdownOptions {
+    pub layout: MarkdownLayout,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        MarkdownOptions {
+            layout: MarkdownLayout::ByKind,
+        }
+    }
+}
+
+/// A file of the generated reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownFile {
+    pub path: String,
+    pub contents: String,
+}
+
+const KINDS: &[(&str, &str)] = &[
+    ("objects.md", "Objects"),
+    ("interfaces.md", "Interfaces"),
+    ("unions.md", "Unions"),
+    ("enums.md", "Enums"),
+    ("inputs.md", "Input objects"),
+    ("scalars.md", "Scalars"),
+];
+const DIRECTIVES_FILE: &str = "directives.md";
+
+/// Render the schema as Markdown reference documentation. Every type gets a section with its
+/// description, fields, arguments, default values and deprecations, and types link to each
+/// other with relative links to their headings.
+///
+/// Introspection types (`__Type`, `__Schema`, ...) are left out.
+pub fn generate<'a, T: Text<'a>>(
+    document: &Document<'a, T>,
+    options: &MarkdownOptions,
+) -> Vec<MarkdownFile> {
+    let types: Vec<&TypeDefinition<'a, T>> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(type_definition)
+                if !type_definition_name(type_definition).starts_with("__") =>
+            {
+                Some(type_definition)
+            }
+            _ => None,
+        })
+        .collect();
+    let directives: Vec<&DirectiveDefinition<'a, T>> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::DirectiveDefinition(directive) => Some(directive),
+            _ => None,
+        })
+        .collect();
+
+    let renderer = Renderer {
+        files: types
+            .iter()
+            .map(|type_definition| {
+                let name = type_definition_name(type_definition);
+                let file = match options.layout {
+                    MarkdownLayout::ByKind => KINDS[kind_index(type_definition)].0.to_string(),
+                    MarkdownLayout::ByType => format!("{}.md", name),
+                };
+                (name, file)
+            })
+            .collect(),
+    };
+
+    let mut files = vec![];
+    match options.layout {
+        MarkdownLayout::ByKind => {
+            for (index, (path, heading)) in KINDS.iter().enumerate() {
+                let of_kind: Vec<_> = types
+                    .iter()
+                    .filter(|type_definition| kind_index(type_definition) == index)
+                    .collect();
+                if of_kind.is_empty() {
+                    continue;
+                }
+
+                let mut contents = format!("# {}\n", heading);
+                for type_definition in of_kind {
+                    contents.push('\n');
+                    contents.push_str(&renderer.type_definition(type_definition, path, "##"));
+                }
+                files.push(MarkdownFile {
+                    path: path.to_string(),
+                    contents,
+                });
+            }
+        }
+        MarkdownLayout::ByType => {
+            let mut index = String::from("# Schema\n");
+            for (kind, (_, heading)) in KINDS.iter().enumerate() {
+                let of_kind: Vec<_> = types
+                    .iter()
+                    .filter(|type_definition| kind_index(type_definition) == kind)
+                    .collect();
+                if of_kind.is_empty() {
+                    continue;
+                }
+
+                write!(index, "\n## {}\n\n", heading).unwrap();
+                for type_definition in of_kind {
+                    let name = type_definition_name(type_definition);
+                    let path = format!("{}.md", name);
+                    writeln!(index, "- [{}]({})", name, path).unwrap();
+                    files.push(MarkdownFile {
+                        contents: renderer.type_definition(type_definition, &path, "#"),
+                        path,
+                    });
+                }
+            }
+            if !directives.is_empty() {
+                writeln!(
+                    index,
+                    "\n## Directives\n\n- [Directives]({})",
+                    DIRECTIVES_FILE
+                )
+                .unwrap();
+            }
+            files.insert(
+                0,
+                MarkdownFile {
+                    path: "index.md".to_string(),
+                    contents: index,
+                },
+            );
+        }
+    }
+
+    if !directives.is_empty() {
+        let mut contents = String::from("# Directives\n");
+        for directive in directives {
+            contents.push('\n');
+            contents.push_str(&renderer.directive(directive));
+        }
+        files.push(MarkdownFile {
+            path: DIRECTIVES_FILE.to_string(),
+            contents,
+        });
+    }
+
+    files
+}
+
+struct Renderer<'r> {
+    /// The file each type is documented in
+    files: HashMap<&'r str, String>,
+}
+
+impl<'r> Renderer<'r> {
+    /// A section for the type in `file`, its heading at `level`
+    fn type_definition<'a, T: Text<'a>>(
+        &self,
+        type_definition: &TypeDefinition<'a, T>,
+        file: &str,
+        level: &str,
+    ) -> String {
+        let mut out = format!("{} {}\n", level, type_definition_name(type_definition));
+        description(&mut out, type_description(type_definition));
+
+        match type_definition {
+            TypeDefinition::Scalar(_) => {}
+            TypeDefinition::Object(object) => {
+                self.type_list(&mut out, "Implements", &object.implements_interfaces, file);
+                self.fields(&mut out, &object.fields, file);
+            }
+            TypeDefinition::Interface(interface) => {
+                self.type_list(
+                    &mut out,
+                    "Implements",
+                    &interface.implements_interfaces,
+                    file,
+                );
+                self.fields(&mut out, &interface.fields, file);
+            }
+            TypeDefinition::Union(union) => {
+                self.type_list(&mut out, "Possible types", &union.types, file);
+            }
+            TypeDefinition::Enum(enum_type) => {
+                out.push_str("\n| Value | Description |\n| --- | --- |\n");
+                for value in &enum_type.values {
+                    writeln!(
+                        out,
+                        "| `{}`{} | {} |",
+                        value.name.as_ref(),
+                        deprecated_marker(&value.directives),
+                        cell(deprecation(&value.directives), &value.description)
+                    )
+                    .unwrap();
+                }
+            }
+            TypeDefinition::InputObject(input_object) => {
+                out.push_str(
+                    "\n| Field | Type | Default | Description |\n| --- | --- | --- | --- |\n",
+                );
+                for input_field in &input_object.fields {
+                    writeln!(
+                        out,
+                        "| `{}` | {} | {} | {} |",
+                        input_field.name.as_ref(),
+                        self.type_link(&input_field.value_type, file),
+                        default_value(input_field),
+                        cell(None, &input_field.description)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        out
+    }
+
+    fn fields<'a, T: Text<'a>>(&self, out: &mut String, fields: &[Field<'a, T>], file: &str) {
+        out.push_str("\n| Field | Type | Arguments | Description |\n| --- | --- | --- | --- |\n");
+        for field in fields {
+            writeln!(
+                out,
+                "| `{}`{} | {} | {} | {} |",
+                field.name.as_ref(),
+                deprecated_marker(&field.directives),
+                self.type_link(&field.field_type, file),
+                self.arguments(&field.arguments, file),
+                cell(deprecation(&field.directives), &field.description)
+            )
+            .unwrap();
+        }
+    }
+
+    /// Arguments as `name: Type = default`, one per line of the table cell
+    fn arguments<'a, T: Text<'a>>(&self, arguments: &[InputValue<'a, T>], file: &str) -> String {
+        arguments
+            .iter()
+            .map(|argument| {
+                let mut out = format!(
+                    "`{}`: {}",
+                    argument.name.as_ref(),
+                    self.type_link(&argument.value_type, file)
+                );
+                if argument.default_value.is_some() {
+                    write!(out, " = {}", default_value(argument)).unwrap();
+                }
+                if let Some(description) = &argument.description {
+                    write!(out, " {}", escape_cell(description)).unwrap();
+                }
+                out
+            })
+            .collect::<Vec<_>>()
+            .join("<br>")
+    }
+
+    fn directive<'a, T: Text<'a>>(&self, directive: &DirectiveDefinition<'a, T>) -> String {
+        let mut out = format!("## @{}\n", directive.name.as_ref());
+        description(&mut out, &directive.description);
+
+        let locations: Vec<&str> = directive.locations.iter().map(|l| l.as_str()).collect();
+        write!(out, "\nLocations: {}", locations.join(", ")).unwrap();
+        if directive.repeatable {
+            out.push_str(" (repeatable)");
+        }
+        out.push('\n');
+
+        if !directive.arguments.is_empty() {
+            out.push_str(
+                "\n| Argument | Type | Default | Description |\n| --- | --- | --- | --- |\n",
+            );
+            for argument in &directive.arguments {
+                writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} |",
+                    argument.name.as_ref(),
+                    self.type_link(&argument.value_type, DIRECTIVES_FILE),
+                    default_value(argument),
+                    cell(None, &argument.description)
+                )
+                .unwrap();
+            }
+        }
+
+        out
+    }
+
+    fn type_list<V: AsRef<str>>(&self, out: &mut String, label: &str, names: &[V], file: &str) {
+        if names.is_empty() {
+            return;
+        }
+
+        let links: Vec<String> = names
+            .iter()
+            .map(|name| self.link(name.as_ref(), &format!("`{}`", name.as_ref()), file))
+            .collect();
+        write!(out, "\n{}: {}\n", label, links.join(", ")).unwrap();
+    }
+
+    /// The type as written in SDL, linking to where the named type is documented
+    fn type_link<'a, T: Text<'a>>(&self, value_type: &Type<'a, T>, file: &str) -> String {
+        self.link(named_type(value_type), &format!("`{}`", value_type), file)
+    }
+
+    /// A relative link from `file` to the heading for `name`, or just the text if the type
+    /// isn't documented
+    fn link(&self, name: &str, text: &str, file: &str) -> String {
+        match self.files.get(name) {
+            Some(target) if target == file => format!("[{}](#{})", text, anchor(name)),
+            Some(target) => format!("[{}]({}#{})", text, target, anchor(name)),
+            None => text.to_string(),
+        }
+    }
+}
+
+fn kind_index<'a, T: Text<'a>>(type_definition: &TypeDefinition<'a, T>) -> usize {
+    match type_definition {
+        TypeDefinition::Object(_) => 0,
+        TypeDefinition::Interface(_) => 1,
+        TypeDefinition::Union(_) => 2,
+        TypeDefinition::Enum(_) => 3,
+        TypeDefinition::InputObject(_) => 4,
+        TypeDefinition::Scalar(_) => 5,
+    }
+}
+
+/// The anchor Markdown renderers give a heading, GraphQL names only need lowercasing
+fn anchor(name: &str) -> String {
+    name.to_lowercase()
+}
+
+fn description(out: &mut String, description: &Option<String>) {
+    if let Some(description) = description {
+        write!(out, "\n{}\n", description.trim()).unwrap();
+    }
+}
+
+fn deprecated_marker<'a, T: Text<'a>>(directives: &[Directive<'a, T>]) -> &'static str {
+    if deprecation(directives).is_some() {
+        " _(deprecated)_"
+    } else {
+        ""
+    }
+}
+
+/// A description for a table cell, after the deprecation reason if there is one
+fn cell(deprecation: Option<Deprecation>, description: &Option<String>) -> String {
+    let mut parts = vec![];
+    if let Some(deprecation) = deprecation {
+        parts.push(match deprecation.reason {
+            Some(reason) => format!("**Deprecated:** {}", escape_cell(reason)),
+            None => "**Deprecated**".to_string(),
+        });
+    }
+    if let Some(description) = description {
+        parts.push(escape_cell(description.trim()));
+    }
+
+    parts.join("<br>")
+}
+
+fn default_value<'a, T: Text<'a>>(input_value: &InputValue<'a, T>) -> String {
+    default_value_source(input_value)
+        .map(|value| format!("`{}`", escape_cell(&value)))
+        .unwrap_or_default()
+}
+
+/// Keep text on one line of a table without ending the cell early
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        "A person"
+        type User implements Node {
+            id: ID!
+            best: User
+            role(format: Int = 1): Role @deprecated(reason: "gone")
+        }
+
+        interface Node { id: ID! }
+
+        enum Role { ADMIN }
+
+        directive @cached(scope: Role) on FIELD
+    "#;
+
+    fn generate_with(layout: MarkdownLayout) -> Vec<MarkdownFile> {
+        generate(
+            &parse_schema::<String>(SCHEMA).unwrap(),
+            &MarkdownOptions { layout },
+        )
+    }
+
+    fn paths(files: &[MarkdownFile]) -> Vec<&str> {
+        files.iter().map(|file| file.path.as_str()).collect()
+    }
+
+    #[test]
+    fn groups_types_by_kind() {
+        let files = generate_with(MarkdownLayout::ByKind);
+
+        assert_eq!(
+            paths(&files),
+            vec!["objects.md", "interfaces.md", "enums.md", "directives.md"]
+        );
+        assert_eq!(
+            files[0].contents,
+            "# Objects\n\
+             \n\
+             ## User\n\
+             \n\
+             A person\n\
+             \n\
+             Implements: [`Node`](interfaces.md#node)\n\
+             \n\
+             | Field | Type | Arguments | Description |\n\
+             | --- | --- | --- | --- |\n\
+             | `id` | `ID!` |  |  |\n\
+             | `best` | [`User`](#user) |  |  |\n\
+             | `role` _(deprecated)_ | [`Role`](enums.md#role) | `format`: `Int` = `1` | \
+             **Deprecated:** gone |\n"
+        );
+        assert_eq!(
+            files[3].contents,
+            "# Directives\n\
+             \n\
+             ## @cached\n\
+             \n\
+             Locations: FIELD\n\
+             \n\
+             | Argument | Type | Default | Description |\n\
+             | --- | --- | --- | --- |\n\
+             | `scope` | [`Role`](enums.md#role) |  |  |\n"
+        );
+    }
+
+    #[test]
+    fn gives_each_type_a_file() {
+        let files = generate_with(MarkdownLayout::ByType);
+
+        assert_eq!(
+            paths(&files),
+            vec!["index.md", "User.md", "Node.md", "Role.md", "directives.md"]
+        );
+        assert_eq!(
+            files[0].contents,
+            "# Schema\n\
+             \n\
+             ## Objects\n\
+             \n\
+             - [User](User.md)\n\
+             \n\
+             ## Interfaces\n\
+             \n\
+             - [Node](Node.md)\n\
+             \n\
+             ## Enums\n\
+             \n\
+             - [Role](Role.md)\n\
+             \n\
+             ## Directives\n\
+             \n\
+             - [Directives](directives.md)\n"
+        );
+
+        let user = &files[1].contents;
+        assert!(user.starts_with("# User\n"));
+        assert!(user.contains("Implements: [`Node`](Node.md#node)\n"));
+        assert!(user.contains("| `best` | [`User`](#user) |"));
+        assert!(user.contains("| [`Role`](Role.md#role) |"));
+        assert!(files[4]
+            .contents
+            .contains("| `scope` | [`Role`](Role.md#role) |"));
+    }
+}
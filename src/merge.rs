@@ -0,0 +1,911 @@
+use crate::naming::to_pascal_case;
+use crate::schema::{
+    kind_name, to_owned_document, type_definition_name, OperationKind, BUILT_IN_SCALARS,
+};
+use graphql_parser::schema::{
+    Definition, Directive, DirectiveDefinition, Document, Field, InputValue, SchemaDefinition,
+    Text, Type, TypeDefinition,
+};
+use graphql_parser::Pos;
+use std::collections::HashMap;
+use std::fmt;
+
+/// What to do when two documents define the same type in incompatible ways
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep whatever the earlier document defined, still merging in any fields that don't
+    /// conflict
+    KeepFirst,
+    /// Rename the later document's type to `{Source}{Type}`, ie: `BillingAccount` for an
+    /// `Account` type from the `billing` source, and update its references in that document.
+    /// Root types, built-in scalars and `__` types can't be renamed, so they keep the first.
+    PrefixConflicts,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictKind {
+    /// The same name is a different kind of type in each document, ie: `object` and `enum`
+    TypeKind {
+        existing: &'static str,
+        incoming: &'static str,
+    },
+    /// A field, input field or argument has types that can't be reconciled, ie: `Int` and
+    /// `String`. Types that only differ in nullability are merged instead.
+    FieldType {
+        /// `field` or `field(argument:)`
+        field: String,
+        existing: String,
+        incoming: String,
+    },
+    /// A directive is defined differently in each document
+    Directive,
+    /// `PrefixConflicts` would have renamed the incoming type to this, but a type already has
+    /// that name
+    RenameTaken(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    KeptFirst,
+    /// The incoming type was renamed to this
+    Renamed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// The type or directive the conflict is in
+    pub name: String,
+    /// The source that defined `name` first
+    pub existing_source: String,
+    /// The source that conflicts with it
+    pub incoming_source: String,
+    pub kind: ConflictKind,
+    pub resolution: Resolution,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ConflictKind::TypeKind { existing, incoming } => write!(
+                f,
+                "'{}' is {} {} in {} but {} {} in {}",
+                self.name,
+                article(existing),
+                existing,
+                self.existing_source,
+                article(incoming),
+                incoming,
+                self.incoming_source
+            )?,
+            ConflictKind::FieldType {
+                field,
+                existing,
+                incoming,
+            } => write!(
+                f,
+                "'{}.{}' is {} in {} but {} in {}",
+                self.name, field, existing, self.existing_source, incoming, self.incoming_source
+            )?,
+            ConflictKind::Directive => write!(
+                f,
+                "directive '@{}' is defined differently in {} and {}",
+                self.name, self.existing_source, self.incoming_source
+            )?,
+            ConflictKind::RenameTaken(renamed) => write!(
+                f,
+                "'{}' from {} couldn't be renamed to '{}' as that name is taken",
+                self.name, self.incoming_source, renamed
+            )?,
+        }
+
+        match &self.resolution {
+            Resolution::KeptFirst => write!(f, ", kept {}", self.existing_source),
+            Resolution::Renamed(name) => write!(f, ", renamed to '{}'", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MergedSchema {
+    pub document: Document<'static, String>,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Combine several documents, each named by the source it came from, into one. Types and their
+/// fields, enum values, union members and interfaces are unioned, directive definitions are
+/// deduplicated and the root types of every document are merged, named after the first document
+/// with each root type. A document without a schema definition has the types named `Query`,
+/// `Mutation` and `Subscription` as its roots.
+pub fn merge<'a, T: Text<'a>>(
+    sources: &[(&str, &Document<'a, T>)],
+    strategy: MergeStrategy,
+) -> MergedSchema {
+    let mut merger = Merger {
+        strategy,
+        root_types: [None, None, None],
+        types: vec![],
+        type_sources: HashMap::new(),
+        directives: vec![],
+        conflicts: vec![],
+    };
+
+    for (source, document) in sources {
+        merger.document(source, to_owned_document(document));
+    }
+
+    let [query, mutation, subscription] = merger.root_types;
+    let mut definitions: Vec<Definition<'static, String>> = merger
+        .types
+        .into_iter()
+        .map(Definition::TypeDefinition)
+        .collect();
+    definitions.extend(
+        merger
+            .directives
+            .into_iter()
+            .map(|(_, directive)| Definition::DirectiveDefinition(directive)),
+    );
+    if query.is_some() || mutation.is_some() || subscription.is_some() {
+        definitions.push(Definition::SchemaDefinition(SchemaDefinition {
+            position: Pos::default(),
+            directives: vec![],
+            query,
+            mutation,
+            subscription,
+        }));
+    }
+
+    MergedSchema {
+        document: Document { definitions },
+        conflicts: merger.conflicts,
+    }
+}
+
+struct Merger {
+    strategy: MergeStrategy,
+    /// The merged query, mutation and subscription type names
+    root_types: [Option<String>; 3],
+    types: Vec<TypeDefinition<'static, String>>,
+    /// The source each merged type was first defined by
+    type_sources: HashMap<String, String>,
+    directives: Vec<(String, DirectiveDefinition<'static, String>)>,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger {
+    fn document(&mut self, source: &str, mut document: Document<'static, String>) {
+        let renames = self.root_type_renames(&document);
+        rename_types(&mut document, &renames);
+
+        let mut types = vec![];
+        let mut directives = vec![];
+        for definition in document.definitions {
+            match definition {
+                Definition::TypeDefinition(type_definition) => types.push(type_definition),
+                Definition::DirectiveDefinition(directive) => directives.push(directive),
+                _ => {}
+            }
+        }
+
+        if self.strategy == MergeStrategy::PrefixConflicts {
+            let prefix = to_pascal_case(source);
+            let conflicting: Vec<(String, Vec<ConflictKind>)> = types
+                .iter()
+                .filter(|incoming| self.can_rename(type_definition_name(incoming)))
+                .filter_map(|incoming| {
+                    let name = type_definition_name(incoming);
+                    let conflicts = type_conflicts(self.get_type(name)?, incoming);
+                    if conflicts.is_empty() {
+                        None
+                    } else {
+                        Some((name.to_string(), conflicts))
+                    }
+                })
+                .collect();
+
+            let mut renames = HashMap::new();
+            for (name, conflicts) in conflicting {
+                let renamed = format!("{}{}", prefix, name);
+                let taken = self.get_type(&renamed).is_some()
+                    || types
+                        .iter()
+                        .any(|incoming| type_definition_name(incoming) == renamed);
+                // leave the incoming type to be merged into the existing one
+                if taken {
+                    self.conflict(
+                        &name,
+                        source,
+                        ConflictKind::RenameTaken(renamed),
+                        Resolution::KeptFirst,
+                    );
+                    continue;
+                }
+
+                for kind in conflicts {
+                    self.conflict(&name, source, kind, Resolution::Renamed(renamed.clone()));
+                }
+                renames.insert(name, renamed);
+            }
+
+            // directive definitions from this source can take the renamed input types too
+            let mut renamed_document = Document {
+                definitions: types
+                    .into_iter()
+                    .map(Definition::TypeDefinition)
+                    .chain(directives.into_iter().map(Definition::DirectiveDefinition))
+                    .collect(),
+            };
+            rename_types(&mut renamed_document, &renames);
+            types = vec![];
+            directives = vec![];
+            for definition in renamed_document.definitions {
+                match definition {
+                    Definition::TypeDefinition(type_definition) => types.push(type_definition),
+                    Definition::DirectiveDefinition(directive) => directives.push(directive),
+                    _ => {}
+                }
+            }
+        }
+
+        for incoming in types {
+            self.type_definition(source, incoming);
+        }
+        for directive in directives {
+            self.directive(source, directive);
+        }
+    }
+
+    /// Point the document's root types at the merged root types, the first document to have
+    /// each root type decides what it's called
+    fn root_type_renames(
+        &mut self,
+        document: &Document<'static, String>,
+    ) -> HashMap<String, String> {
+        let schema_definition =
+            document
+                .definitions
+                .iter()
+                .find_map(|definition| match definition {
+                    Definition::SchemaDefinition(schema_definition) => Some(schema_definition),
+                    _ => None,
+                });
+        let roots = match schema_definition {
+            Some(schema) => [
+                schema.query.clone(),
+                schema.mutation.clone(),
+                schema.subscription.clone(),
+            ],
+            // without a schema definition the roots are the types with the default names
+            None => {
+                let has_type = |name: &str| {
+                    document.definitions.iter().any(|definition| {
+                        matches!(definition, Definition::TypeDefinition(type_definition)
+                            if type_definition_name(type_definition) == name)
+                    })
+                };
+                let default_root = |kind: OperationKind| {
+                    Some(kind.default_type_name())
+                        .filter(|name| has_type(name))
+                        .map(str::to_string)
+                };
+                [
+                    default_root(OperationKind::Query),
+                    default_root(OperationKind::Mutation),
+                    default_root(OperationKind::Subscription),
+                ]
+            }
+        };
+
+        let mut renames = HashMap::new();
+        for (i, root) in roots.iter().enumerate() {
+            let root = match root {
+                Some(root) => root,
+                None => continue,
+            };
+            let merged = self.root_types[i].get_or_insert_with(|| root.clone());
+            if merged != root {
+                renames.insert(root.clone(), merged.clone());
+            }
+        }
+
+        renames
+    }
+
+    fn can_rename(&self, name: &str) -> bool {
+        !name.starts_with("__")
+            && !BUILT_IN_SCALARS.contains(&name)
+            && !self.root_types.iter().flatten().any(|root| root == name)
+    }
+
+    fn get_type(&self, name: &str) -> Option<&TypeDefinition<'static, String>> {
+        self.types
+            .iter()
+            .find(|type_definition| type_definition_name(type_definition) == name)
+    }
+
+    fn conflict(&mut self, name: &str, source: &str, kind: ConflictKind, resolution: Resolution) {
+        self.conflicts.push(MergeConflict {
+            name: name.to_string(),
+            existing_source: self.type_sources.get(name).cloned().unwrap_or_default(),
+            incoming_source: source.to_string(),
+            kind,
+            resolution,
+        });
+    }
+
+    fn type_definition(&mut self, source: &str, incoming: TypeDefinition<'static, String>) {
+        let name = type_definition_name(&incoming).to_string();
+        let index = match self
+            .types
+            .iter()
+            .position(|type_definition| type_definition_name(type_definition) == name)
+        {
+            Some(index) => index,
+            None => {
+                self.type_sources.insert(name, source.to_string());
+                self.types.push(incoming);
+                return;
+            }
+        };
+
+        // the introspection types are the same everywhere, and any differences aren't ours
+        if !name.starts_with("__") {
+            for kind in type_conflicts(&self.types[index], &incoming) {
+                self.conflict(&name, source, kind, Resolution::KeptFirst);
+            }
+        }
+        merge_type(&mut self.types[index], incoming);
+    }
+
+    fn directive(&mut self, source: &str, incoming: DirectiveDefinition<'static, String>) {
+        match self
+            .directives
+            .iter()
+            .find(|(_, existing)| existing.name == incoming.name)
+        {
+            Some((existing_source, existing)) => {
+                if existing.to_string() != incoming.to_string() {
+                    self.conflicts.push(MergeConflict {
+                        name: incoming.name,
+                        existing_source: existing_source.clone(),
+                        incoming_source: source.to_string(),
+                        kind: ConflictKind::Directive,
+                        resolution: Resolution::KeptFirst,
+                    });
+                }
+            }
+            None => self.directives.push((source.to_string(), incoming)),
+        }
+    }
+}
+
+/// Everything about `incoming` that can't be merged into `existing`
+fn type_conflicts(
+    existing: &TypeDefinition<'static, String>,
+    incoming: &TypeDefinition<'static, String>,
+) -> Vec<ConflictKind> {
+    match (existing, incoming) {
+        (TypeDefinition::Object(existing), TypeDefinition::Object(incoming)) => {
+            field_conflicts(&existing.fields, &incoming.fields)
+        }
+        (TypeDefinition::Interface(existing), TypeDefinition::Interface(incoming)) => {
+            field_conflicts(&existing.fields, &incoming.fields)
+        }
+        (TypeDefinition::InputObject(existing), TypeDefinition::InputObject(incoming)) => {
+            input_value_conflicts("", &existing.fields, &incoming.fields, false)
+        }
+        (TypeDefinition::Scalar(_), TypeDefinition::Scalar(_))
+        | (TypeDefinition::Union(_), TypeDefinition::Union(_))
+        | (TypeDefinition::Enum(_), TypeDefinition::Enum(_)) => vec![],
+        (existing, incoming) => vec![ConflictKind::TypeKind {
+            existing: kind_name(existing),
+            incoming: kind_name(incoming),
+        }],
+    }
+}
+
+fn field_conflicts(
+    existing: &[Field<'static, String>],
+    incoming: &[Field<'static, String>],
+) -> Vec<ConflictKind> {
+    let mut conflicts = vec![];
+
+    for incoming_field in incoming {
+        let existing_field = match existing.iter().find(|f| f.name == incoming_field.name) {
+            Some(existing_field) => existing_field,
+            None => continue,
+        };
+
+        if merge_field_types(&existing_field.field_type, &incoming_field.field_type, true).is_none()
+        {
+            conflicts.push(ConflictKind::FieldType {
+                field: incoming_field.name.clone(),
+                existing: existing_field.field_type.to_string(),
+                incoming: incoming_field.field_type.to_string(),
+            });
+        }
+        conflicts.extend(input_value_conflicts(
+            &incoming_field.name,
+            &existing_field.arguments,
+            &incoming_field.arguments,
+            true,
+        ));
+    }
+
+    conflicts
+}
+
+/// Conflicting input fields, or arguments of `field` if `arguments` is set
+fn input_value_conflicts(
+    field: &str,
+    existing: &[InputValue<'static, String>],
+    incoming: &[InputValue<'static, String>],
+    arguments: bool,
+) -> Vec<ConflictKind> {
+    incoming
+        .iter()
+        .filter_map(|incoming_value| {
+            let existing_value = existing.iter().find(|v| v.name == incoming_value.name)?;
+            if merge_field_types(
+                &existing_value.value_type,
+                &incoming_value.value_type,
+                false,
+            )
+            .is_some()
+            {
+                return None;
+            }
+
+            Some(ConflictKind::FieldType {
+                field: if arguments {
+                    format!("{}({}:)", field, incoming_value.name)
+                } else {
+                    incoming_value.name.clone()
+                },
+                existing: existing_value.value_type.to_string(),
+                incoming: incoming_value.value_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Reconcile two types that only differ in nullability. Outputs take the nullable type, since
+/// either source may return null, and inputs the non-null one, since either may require it.
+fn merge_field_types(
+    existing: &Type<'static, String>,
+    incoming: &Type<'static, String>,
+    output: bool,
+) -> Option<Type<'static, String>> {
+    match (existing, incoming) {
+        (Type::NonNullType(existing), Type::NonNullType(incoming)) => Some(Type::NonNullType(
+            Box::new(merge_field_types(existing, incoming, output)?),
+        )),
+        (Type::NonNullType(non_null), other) | (other, Type::NonNullType(non_null)) => {
+            let merged = merge_field_types(non_null, other, output)?;
+            Some(if output {
+                merged
+            } else {
+                Type::NonNullType(Box::new(merged))
+            })
+        }
+        (Type::ListType(existing), Type::ListType(incoming)) => Some(Type::ListType(Box::new(
+            merge_field_types(existing, incoming, output)?,
+        ))),
+        (Type::NamedType(existing), Type::NamedType(incoming)) if existing == incoming => {
+            Some(Type::NamedType(existing.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Merge `incoming` into `existing`, keeping `existing`'s side of anything that conflicts
+fn merge_type(
+    existing: &mut TypeDefinition<'static, String>,
+    incoming: TypeDefinition<'static, String>,
+) {
+    match (existing, incoming) {
+        (TypeDefinition::Scalar(existing), TypeDefinition::Scalar(incoming)) => {
+            merge_description(&mut existing.description, incoming.description);
+            merge_directives(&mut existing.directives, incoming.directives);
+        }
+        (TypeDefinition::Object(existing), TypeDefinition::Object(incoming)) => {
+            merge_description(&mut existing.description, incoming.description);
+            merge_directives(&mut existing.directives, incoming.directives);
+            merge_names(
+                &mut existing.implements_interfaces,
+                incoming.implements_interfaces,
+            );
+            merge_fields(&mut existing.fields, incoming.fields);
+        }
+        (TypeDefinition::Interface(existing), TypeDefinition::Interface(incoming)) => {
+            merge_description(&mut existing.description, incoming.description);
+            merge_directives(&mut existing.directives, incoming.directives);
+            merge_names(
+                &mut existing.implements_interfaces,
+                incoming.implements_interfaces,
+            );
+            merge_fields(&mut existing.fields, incoming.fields);
+        }
+        (TypeDefinition::Union(existing), TypeDefinition::Union(incoming)) => {
+            merge_description(&mut existing.description, incoming.description);
+            merge_directives(&mut existing.directives, incoming.directives);
+            merge_names(&mut existing.types, incoming.types);
+        }
+        (TypeDefinition::Enum(existing), TypeDefinition::Enum(incoming)) => {
+            merge_description(&mut existing.description, incoming.description);
+            merge_directives(&mut existing.directives, incoming.directives);
+            for value in incoming.values {
+                if !existing.values.iter().any(|v| v.name == value.name) {
+                    existing.values.push(value);
+                }
+            }
+        }
+        (TypeDefinition::InputObject(existing), TypeDefinition::InputObject(incoming)) => {
+            merge_description(&mut existing.description, incoming.description);
+            merge_directives(&mut existing.directives, incoming.directives);
+            merge_input_values(&mut existing.fields, incoming.fields);
+        }
+        // different kinds, already reported as a conflict
+        _ => {}
+    }
+}
+
+fn merge_fields(existing: &mut Vec<Field<'static, String>>, incoming: Vec<Field<'static, String>>) {
+    for incoming_field in incoming {
+        match existing.iter_mut().find(|f| f.name == incoming_field.name) {
+            Some(existing_field) => {
+                merge_description(&mut existing_field.description, incoming_field.description);
+                merge_directives(&mut existing_field.directives, incoming_field.directives);
+                if let Some(merged) =
+                    merge_field_types(&existing_field.field_type, &incoming_field.field_type, true)
+                {
+                    existing_field.field_type = merged;
+                }
+                merge_input_values(&mut existing_field.arguments, incoming_field.arguments);
+            }
+            None => existing.push(incoming_field),
+        }
+    }
+}
+
+fn merge_input_values(
+    existing: &mut Vec<InputValue<'static, String>>,
+    incoming: Vec<InputValue<'static, String>>,
+) {
+    for incoming_value in incoming {
+        match existing.iter_mut().find(|v| v.name == incoming_value.name) {
+            Some(existing_value) => {
+                merge_description(&mut existing_value.description, incoming_value.description);
+                if let Some(merged) = merge_field_types(
+                    &existing_value.value_type,
+                    &incoming_value.value_type,
+                    false,
+                ) {
+                    existing_value.value_type = merged;
+                }
+            }
+            None => existing.push(incoming_value),
+        }
+    }
+}
+
+fn merge_description(existing: &mut Option<String>, incoming: Option<String>) {
+    if existing.is_none() {
+        *existing = incoming;
+    }
+}
+
+/// Add the incoming directives `existing` doesn't already apply in the same way
+fn merge_directives(
+    existing: &mut Vec<Directive<'static, String>>,
+    incoming: Vec<Directive<'static, String>>,
+) {
+    for directive in incoming {
+        // positions differ between documents, so compare what's applied
+        if !existing
+            .iter()
+            .any(|e| e.name == directive.name && e.arguments == directive.arguments)
+        {
+            existing.push(directive);
+        }
+    }
+}
+
+fn merge_names(existing: &mut Vec<String>, incoming: Vec<String>) {
+    for name in incoming {
+        if !existing.contains(&name) {
+            existing.push(name);
+        }
+    }
+}
+
+/// The article for a kind of type, so it reads as `an object` or `a scalar`
+fn article(kind: &str) -> &'static str {
+    if kind.starts_with(|c| "aeiou".contains(c)) {
+        "an"
+    } else {
+        "a"
+    }
+}
+
+/// Rename types, and every reference to them, throughout a document
+fn rename_types(document: &mut Document<'static, String>, renames: &HashMap<String, String>) {
+    if renames.is_empty() {
+        return;
+    }
+
+    let rename = |name: &mut String| {
+        if let Some(renamed) = renames.get(name.as_str()) {
+            *name = renamed.clone();
+        }
+    };
+    let rename_type = |value_type: &mut Type<'static, String>| {
+        let mut value_type = value_type;
+        loop {
+            match value_type {
+                Type::NamedType(name) => break rename(name),
+                Type::ListType(inner) | Type::NonNullType(inner) => value_type = inner,
+            }
+        }
+    };
+    let rename_input_values = |input_values: &mut Vec<InputValue<'static, String>>| {
+        for input_value in input_values {
+            rename_type(&mut input_value.value_type);
+        }
+    };
+    let rename_fields = |fields: &mut Vec<Field<'static, String>>| {
+        for field in fields {
+            rename_type(&mut field.field_type);
+            rename_input_values(&mut field.arguments);
+        }
+    };
+
+    for definition in &mut document.definitions {
+        match definition {
+            Definition::SchemaDefinition(schema) => {
+                schema
+                    .query
+                    .iter_mut()
+                    .chain(&mut schema.mutation)
+                    .chain(&mut schema.subscription)
+                    .for_each(rename);
+            }
+            Definition::TypeDefinition(type_definition) => match type_definition {
+                TypeDefinition::Scalar(scalar) => rename(&mut scalar.name),
+                TypeDefinition::Object(object) => {
+                    rename(&mut object.name);
+                    object.implements_interfaces.iter_mut().for_each(rename);
+                    rename_fields(&mut object.fields);
+                }
+                TypeDefinition::Interface(interface) => {
+                    rename(&mut interface.name);
+                    interface.implements_interfaces.iter_mut().for_each(rename);
+                    rename_fields(&mut interface.fields);
+                }
+                TypeDefinition::Union(union) => {
+                    rename(&mut union.name);
+                    union.types.iter_mut().for_each(rename);
+                }
+                TypeDefinition::Enum(enum_type) => rename(&mut enum_type.name),
+                TypeDefinition::InputObject(input_object) => {
+                    rename(&mut input_object.name);
+                    rename_input_values(&mut input_object.fields);
+                }
+            },
+            Definition::DirectiveDefinition(directive) => {
+                rename_input_values(&mut directive.arguments)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    fn merge_sdl(sources: &[(&str, &str)], strategy: MergeStrategy) -> MergedSchema {
+        let documents: Vec<(&str, Document<String>)> = sources
+            .iter()
+            .map(|(source, sdl)| (*source, parse_schema::<String>(sdl).unwrap()))
+            .collect();
+        let sources: Vec<(&str, &Document<String>)> = documents
+            .iter()
+            .map(|(source, document)| (*source, document))
+            .collect();
+        merge(&sources, strategy)
+    }
+
+    fn merged_type(merged: &MergedSchema, name: &str) -> String {
+        merged
+            .document
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::TypeDefinition(type_definition)
+                    if type_definition_name(type_definition) == name =>
+                {
+                    Some(type_definition.to_string())
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("{} wasn't merged", name))
+    }
+
+    #[test]
+    fn keep_first_reports_field_type_conflicts() {
+        let merged = merge_sdl(
+            &[
+                ("users", "type User { id: ID! name: String }"),
+                ("accounts", "type User { id: ID name: Int email: String }"),
+            ],
+            MergeStrategy::KeepFirst,
+        );
+
+        assert_eq!(
+            merged_type(&merged, "User"),
+            "type User {\n  id: ID\n  name: String\n  email: String\n}\n"
+        );
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(
+            merged.conflicts[0].kind,
+            ConflictKind::FieldType {
+                field: "name".to_string(),
+                existing: "String".to_string(),
+                incoming: "Int".to_string(),
+            }
+        );
+        assert_eq!(merged.conflicts[0].resolution, Resolution::KeptFirst);
+    }
+
+    #[test]
+    fn reports_types_of_different_kinds() {
+        let merged = merge_sdl(
+            &[
+                ("users", "type Role { name: String }"),
+                ("accounts", "enum Role { ADMIN }"),
+            ],
+            MergeStrategy::KeepFirst,
+        );
+
+        assert_eq!(
+            merged.conflicts[0].to_string(),
+            "'Role' is an object in users but an enum in accounts, kept users"
+        );
+    }
+
+    #[test]
+    fn applied_directives_are_kept() {
+        let merged = merge_sdl(
+            &[
+                ("users", r#"type User @key(fields: "id") { id: ID! }"#),
+                (
+                    "reviews",
+                    r#"type User @key(fields: "id") @shareable { id: ID! @external }"#,
+                ),
+            ],
+            MergeStrategy::KeepFirst,
+        );
+
+        assert_eq!(
+            merged_type(&merged, "User"),
+            "type User @key(fields: \"id\") @shareable {\n  id: ID! @external\n}\n"
+        );
+        assert!(merged.conflicts.is_empty());
+    }
+
+    #[test]
+    fn prefix_conflicts_renames_in_directive_definitions() {
+        let merged = merge_sdl(
+            &[
+                ("users", "input Filter { name: String }"),
+                (
+                    "reviews",
+                    "input Filter { name: Int }\n\
+                     directive @filtered(by: Filter) on FIELD_DEFINITION",
+                ),
+            ],
+            MergeStrategy::PrefixConflicts,
+        );
+
+        merged_type(&merged, "ReviewsFilter");
+        assert_eq!(
+            merged.conflicts[0].resolution,
+            Resolution::Renamed("ReviewsFilter".to_string())
+        );
+        let directive = merged
+            .document
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::DirectiveDefinition(directive) => Some(directive.to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            directive,
+            "directive @filtered(by: ReviewsFilter) on FIELD_DEFINITION\n"
+        );
+    }
+
+    #[test]
+    fn prefix_conflicts_reports_taken_names() {
+        let merged = merge_sdl(
+            &[
+                (
+                    "users",
+                    "type User { name: String }\ntype ReviewsUser { id: ID }",
+                ),
+                ("reviews", "type User { name: Int }"),
+            ],
+            MergeStrategy::PrefixConflicts,
+        );
+
+        assert_eq!(
+            merged_type(&merged, "ReviewsUser"),
+            "type ReviewsUser {\n  id: ID\n}\n"
+        );
+        assert_eq!(
+            merged_type(&merged, "User"),
+            "type User {\n  name: String\n}\n"
+        );
+        let kinds: Vec<&ConflictKind> = merged.conflicts.iter().map(|c| &c.kind).collect();
+        assert_eq!(kinds.len(), 2);
+        assert_eq!(
+            *kinds[0],
+            ConflictKind::RenameTaken("ReviewsUser".to_string())
+        );
+        assert!(merged
+            .conflicts
+            .iter()
+            .all(|conflict| conflict.resolution == Resolution::KeptFirst));
+    }
+
+    #[test]
+    fn implicit_root_types_are_merged() {
+        let merged = merge_sdl(
+            &[
+                ("a", "type Query { a: Int }"),
+                ("b", "type Query { b: Int } type Mutation { c: Int }"),
+            ],
+            MergeStrategy::KeepFirst,
+        );
+
+        assert_eq!(
+            merged_type(&merged, "Query"),
+            "type Query {\n  a: Int\n  b: Int\n}\n"
+        );
+        assert!(merged
+            .document
+            .to_string()
+            .ends_with("schema {\n  query: Query\n  mutation: Mutation\n}\n"));
+
+        let merged = merge_sdl(
+            &[
+                ("a", "schema { query: Root } type Root { a: Int }"),
+                ("b", "type Query { b: Int }"),
+            ],
+            MergeStrategy::KeepFirst,
+        );
+        assert_eq!(
+            merged_type(&merged, "Root"),
+            "type Root {\n  a: Int\n  b: Int\n}\n"
+        );
+        assert!(!merged.document.to_string().contains("type Query"));
+    }
+
+    #[test]
+    fn documents_without_roots_have_no_schema_definition() {
+        let merged = merge_sdl(
+            &[("a", "type User { id: ID }"), ("b", "type Team { id: ID }")],
+            MergeStrategy::KeepFirst,
+        );
+
+        assert!(!merged
+            .document
+            .definitions
+            .iter()
+            .any(|definition| matches!(definition, Definition::SchemaDefinition(_))));
+    }
+}
@@ -0,0 +1,607 @@
+use crate::introspection;
+use crate::schema::{
+    fragments, is_included, named_type, operation_parts, OperationKind, OperationParts, Schema,
+    BUILT_IN_SCALARS, TYPENAME_FIELD,
+};
+use graphql_parser::query::{
+    self, Definition, Directive, FragmentDefinition, OperationDefinition, Selection, SelectionSet,
+    Text, TypeCondition, Value,
+};
+use graphql_parser::schema::{Type, TypeDefinition};
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::{self as json, Map};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+];
+
+/// Produces a JSON value for a scalar, given the mock's random number generator
+pub type ScalarGenerator = fn(&mut MockRng) -> json::Value;
+
+/// A mocked response. Objects keep their fields in the order the operation selects them, as
+/// the spec asks of responses, whichever features `serde_json` is built with. Serialize it for
+/// the JSON text or convert it into a `serde_json::Value`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum MockValue {
+    Null,
+    Bool(bool),
+    Number(json::Number),
+    String(String),
+    List(Vec<MockValue>),
+    Object(IndexMap<String, MockValue>),
+}
+
+impl From<json::Value> for MockValue {
+    fn from(value: json::Value) -> Self {
+        match value {
+            json::Value::Null => MockValue::Null,
+            json::Value::Bool(boolean) => MockValue::Bool(boolean),
+            json::Value::Number(number) => MockValue::Number(number),
+            json::Value::String(string) => MockValue::String(string),
+            json::Value::Array(items) => {
+                MockValue::List(items.into_iter().map(MockValue::from).collect())
+            }
+            json::Value::Object(object) => MockValue::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, MockValue::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<MockValue> for json::Value {
+    fn from(value: MockValue) -> Self {
+        match value {
+            MockValue::Null => json::Value::Null,
+            MockValue::Bool(boolean) => json::Value::Bool(boolean),
+            MockValue::Number(number) => json::Value::Number(number),
+            MockValue::String(string) => json::Value::String(string),
+            MockValue::List(items) => {
+                json::Value::Array(items.into_iter().map(json::Value::from).collect())
+            }
+            MockValue::Object(object) => json::Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, json::Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockOptions {
+    /// The same seed, schema and operation always produce the same response
+    pub seed: u64,
+    /// Which operation to mock, only needed when the document has more than one
+    pub operation_name: Option<String>,
+    /// Used to resolve `@skip` and `@include`
+    pub variables: Map<String, json::Value>,
+    /// How often, from 0 to 1, a nullable field is mocked as null
+    pub null_ratio: f64,
+    pub min_list_length: usize,
+    pub max_list_length: usize,
+    /// Generators for scalars, keyed by the GraphQL scalar name. These override the built in
+    /// generators, and anything missing falls back to a string.
+    pub scalars: HashMap<String, ScalarGenerator>,
+    /// The schema's `introspection::to_introspection` JSON, to answer `__schema` and `__type`
+    /// with. When unset it's built from the schema the first time an operation asks for it.
+    pub introspection: Option<Arc<json::Value>>,
+}
+
+impl Default for MockOptions {
+    fn default() -> Self {
+        MockOptions {
+            seed: 0,
+            operation_name: None,
+            variables: Map::new(),
+            null_ratio: 0.1,
+            min_list_length: 1,
+            max_list_length: 3,
+            scalars: HashMap::new(),
+            introspection: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockError {
+    /// There's no operation with the requested name, or no operations at all
+    UnknownOperation(Option<String>),
+    /// The document has several operations and no name was given to choose between them
+    AmbiguousOperation,
+    MissingRootType(OperationKind),
+    UnknownType(String),
+    UnknownField {
+        type_name: String,
+        field: String,
+    },
+    UnknownFragment(String),
+    /// An enum without values, or an interface or union no object implements, that the
+    /// operation needs a value of
+    NoPossibleValue(String),
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MockError::UnknownOperation(Some(name)) => write!(f, "unknown operation '{}'", name),
+            MockError::UnknownOperation(None) => f.write_str("the document has no operations"),
+            MockError::AmbiguousOperation => {
+                f.write_str("the document has several operations, pick one by name")
+            }
+            MockError::MissingRootType(kind) => {
+                write!(f, "the schema has no {:?} root type", kind)
+            }
+            MockError::UnknownType(name) => write!(f, "unknown type '{}'", name),
+            MockError::UnknownField { type_name, field } => {
+                write!(f, "unknown field '{}' on type '{}'", field, type_name)
+            }
+            MockError::UnknownFragment(name) => write!(f, "unknown fragment '{}'", name),
+            MockError::NoPossibleValue(name) => write!(f, "type '{}' has no possible values", name),
+        }
+    }
+}
+
+impl Error for MockError {}
+
+/// Mock a response for the only operation in `document`, using the default options
+pub fn mock<'s, 'q, T, Q>(
+    schema: &Schema<'s, T>,
+    document: &query::Document<'q, Q>,
+    seed: u64,
+) -> Result<MockValue, MockError>
+where
+    T: Text<'s>,
+    Q: Text<'q>,
+{
+    mock_with_options(
+        schema,
+        document,
+        &MockOptions {
+            seed,
+            ..MockOptions::default()
+        },
+    )
+}
+
+/// Build a `{"data": ...}` response for an operation that's valid against the schema: non-null
+/// fields always have values, enums use the schema's values and abstract types pick one of
+/// their possible types for `__typename` and fragments. `__schema` and `__type` are answered
+/// with the schema's real introspection rather than mock data.
+pub fn mock_with_options<'s, 'q, T, Q>(
+    schema: &Schema<'s, T>,
+    document: &query::Document<'q, Q>,
+    options: &MockOptions,
+) -> Result<MockValue, MockError>
+where
+    T: Text<'s>,
+    Q: Text<'q>,
+{
+    let operation = find_operation(document, options.operation_name.as_deref())?;
+    let OperationParts {
+        kind,
+        directives,
+        selection_set,
+        ..
+    } = operation_parts(operation);
+    let root_type = schema
+        .root_type_name(kind)
+        .ok_or(MockError::MissingRootType(kind))?;
+
+    let mut mocker = Mocker {
+        schema,
+        options,
+        fragments: fragments(&document.definitions),
+        rng: MockRng::new(options.seed),
+        introspection: options.introspection.clone(),
+    };
+
+    let data = if mocker.included(directives) {
+        mocker.object(root_type, &[selection_set])?
+    } else {
+        MockValue::Object(IndexMap::new())
+    };
+
+    Ok(MockValue::Object(
+        vec![("data".to_string(), data)].into_iter().collect(),
+    ))
+}
+
+fn find_operation<'d, 'q, Q: Text<'q>>(
+    document: &'d query::Document<'q, Q>,
+    operation_name: Option<&str>,
+) -> Result<&'d OperationDefinition<'q, Q>, MockError> {
+    let mut operations = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            _ => None,
+        });
+
+    match operation_name {
+        Some(operation_name) => operations
+            .find(|operation| operation_parts(operation).name == Some(operation_name))
+            .ok_or_else(|| MockError::UnknownOperation(Some(operation_name.to_string()))),
+        None => match (operations.next(), operations.next()) {
+            (Some(operation), None) => Ok(operation),
+            (Some(_), Some(_)) => Err(MockError::AmbiguousOperation),
+            (None, _) => Err(MockError::UnknownOperation(None)),
+        },
+    }
+}
+
+struct Mocker<'a, 's, 'q, T: Text<'s>, Q: Text<'q>> {
+    schema: &'a Schema<'s, T>,
+    options: &'a MockOptions,
+    fragments: HashMap<&'a str, &'a FragmentDefinition<'q, Q>>,
+    rng: MockRng,
+    /// The schema's introspection JSON, from the options or built the first time it's needed
+    introspection: Option<Arc<json::Value>>,
+}
+
+impl<'a, 's, 'q, T: Text<'s>, Q: Text<'q>> Mocker<'a, 's, 'q, T, Q> {
+    /// Mock the fields selected on a concrete object type
+    fn object(
+        &mut self,
+        type_name: &str,
+        selection_sets: &[&'a SelectionSet<'q, Q>],
+    ) -> Result<MockValue, MockError> {
+        let mut fields: Vec<FieldSelection<'a, 'q, Q>> = vec![];
+        for selection_set in selection_sets {
+            self.collect_fields(type_name, selection_set, &mut fields)?;
+        }
+
+        let is_query_root = self.schema.root_type_name(OperationKind::Query) == Some(type_name);
+
+        let mut result = IndexMap::new();
+        for (response_key, query_field, selection_sets) in fields {
+            let name = query_field.name.as_ref();
+            let is_introspection =
+                name == introspection::SCHEMA_FIELD || name == introspection::TYPE_FIELD;
+            let introspection = if is_query_root && is_introspection {
+                let type_name_argument = self.string_argument(query_field, "name");
+                introspection::resolve_root_field(
+                    &self.introspection(),
+                    name,
+                    type_name_argument.as_deref(),
+                    &selection_sets,
+                    &self.fragments,
+                    &self.options.variables,
+                )
+            } else {
+                None
+            };
+
+            let value = if let Some(introspection) = introspection {
+                introspection
+            } else if name == TYPENAME_FIELD {
+                MockValue::String(type_name.to_string())
+            } else {
+                let schema = self.schema;
+                let field =
+                    schema
+                        .field(type_name, name)
+                        .ok_or_else(|| MockError::UnknownField {
+                            type_name: type_name.to_string(),
+                            field: name.to_string(),
+                        })?;
+
+                self.value(&field.field_type, &selection_sets, true)?
+            };
+
+            result.insert(response_key.to_string(), value);
+        }
+
+        Ok(MockValue::Object(result))
+    }
+
+    fn value(
+        &mut self,
+        value_type: &Type<'s, T>,
+        selection_sets: &[&'a SelectionSet<'q, Q>],
+        nullable: bool,
+    ) -> Result<MockValue, MockError> {
+        if let Type::NonNullType(inner) = value_type {
+            return self.value(inner, selection_sets, false);
+        }
+        if nullable && self.rng.chance(self.options.null_ratio) {
+            return Ok(MockValue::Null);
+        }
+
+        match value_type {
+            Type::ListType(inner) => {
+                let min = self.options.min_list_length;
+                let max = self.options.max_list_length.max(min);
+                let length = min + self.rng.below((max - min + 1) as u64) as usize;
+
+                (0..length)
+                    .map(|_| self.value(inner, selection_sets, true))
+                    .collect::<Result<_, _>>()
+                    .map(MockValue::List)
+            }
+            _ => self.named_value(named_type(value_type), selection_sets),
+        }
+    }
+
+    fn named_value(
+        &mut self,
+        type_name: &str,
+        selection_sets: &[&'a SelectionSet<'q, Q>],
+    ) -> Result<MockValue, MockError> {
+        let schema = self.schema;
+        match schema.get_type(type_name) {
+            Some(TypeDefinition::Scalar(_)) => Ok(self.scalar(type_name)),
+            None if BUILT_IN_SCALARS.contains(&type_name) => Ok(self.scalar(type_name)),
+            Some(TypeDefinition::Enum(enum_type)) => {
+                if enum_type.values.is_empty() {
+                    return Err(MockError::NoPossibleValue(type_name.to_string()));
+                }
+                let index = self.rng.below(enum_type.values.len() as u64) as usize;
+
+                Ok(MockValue::String(
+                    enum_type.values[index].name.as_ref().to_string(),
+                ))
+            }
+            Some(TypeDefinition::Object(_)) => self.object(type_name, selection_sets),
+            Some(TypeDefinition::Interface(_)) | Some(TypeDefinition::Union(_)) => {
+                let possible_types = schema.possible_types(type_name);
+                if possible_types.is_empty() {
+                    return Err(MockError::NoPossibleValue(type_name.to_string()));
+                }
+                let index = self.rng.below(possible_types.len() as u64) as usize;
+
+                self.object(possible_types[index], selection_sets)
+            }
+            Some(TypeDefinition::InputObject(_)) | None => {
+                Err(MockError::UnknownType(type_name.to_string()))
+            }
+        }
+    }
+
+    fn scalar(&mut self, type_name: &str) -> MockValue {
+        if let Some(generator) = self.options.scalars.get(type_name) {
+            return generator(&mut self.rng).into();
+        }
+
+        match type_name {
+            "Int" => MockValue::Number(self.rng.below(1000).into()),
+            "Float" => json::Number::from_f64(self.rng.below(100_000) as f64 / 100.0)
+                .map(MockValue::Number)
+                .unwrap_or(MockValue::Null),
+            "Boolean" => MockValue::Bool(self.rng.chance(0.5)),
+            "ID" => MockValue::String(self.rng.below(1_000_000).to_string()),
+            _ => {
+                let first = WORDS[self.rng.below(WORDS.len() as u64) as usize];
+                let second = WORDS[self.rng.below(WORDS.len() as u64) as usize];
+
+                MockValue::String(format!("{} {}", first, second))
+            }
+        }
+    }
+
+    /// Gather the fields that apply to `type_name`, merging repeated response keys so their
+    /// sub-selections are mocked together
+    fn collect_fields(
+        &self,
+        type_name: &str,
+        selection_set: &'a SelectionSet<'q, Q>,
+        fields: &mut Vec<FieldSelection<'a, 'q, Q>>,
+    ) -> Result<(), MockError> {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    if !self.included(&field.directives) {
+                        continue;
+                    }
+                    let response_key = field.alias.as_ref().unwrap_or(&field.name).as_ref();
+                    match fields.iter_mut().find(|(key, _, _)| *key == response_key) {
+                        Some((_, _, selection_sets)) => selection_sets.push(&field.selection_set),
+                        None => fields.push((response_key, field, vec![&field.selection_set])),
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    if !self.included(&spread.directives) {
+                        continue;
+                    }
+                    let fragment = self
+                        .fragments
+                        .get(spread.fragment_name.as_ref())
+                        .ok_or_else(|| {
+                            MockError::UnknownFragment(spread.fragment_name.as_ref().to_string())
+                        })?;
+                    let TypeCondition::On(condition) = &fragment.type_condition;
+                    if self.applies(type_name, condition.as_ref()) {
+                        self.collect_fields(type_name, &fragment.selection_set, fields)?;
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    if !self.included(&fragment.directives) {
+                        continue;
+                    }
+                    let applies = match &fragment.type_condition {
+                        Some(TypeCondition::On(condition)) => {
+                            self.applies(type_name, condition.as_ref())
+                        }
+                        None => true,
+                    };
+                    if applies {
+                        self.collect_fields(type_name, &fragment.selection_set, fields)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn applies(&self, type_name: &str, condition: &str) -> bool {
+        type_name == condition || self.schema.possible_types(condition).contains(&type_name)
+    }
+
+    fn introspection(&mut self) -> Arc<json::Value> {
+        let schema = self.schema;
+        self.introspection
+            .get_or_insert_with(|| Arc::new(introspection::to_introspection(schema.document())))
+            .clone()
+    }
+
+    fn string_argument(&self, field: &query::Field<'q, Q>, argument: &str) -> Option<String> {
+        field
+            .arguments
+            .iter()
+            .find(|(name, _)| name.as_ref() == argument)
+            .and_then(|(_, value)| match value {
+                Value::String(value) => Some(value.clone()),
+                Value::Variable(name) => self
+                    .options
+                    .variables
+                    .get(name.as_ref())
+                    .and_then(json::Value::as_str)
+                    .map(str::to_string),
+                _ => None,
+            })
+    }
+
+    fn included(&self, directives: &[Directive<'q, Q>]) -> bool {
+        is_included(directives, &self.options.variables)
+    }
+}
+
+/// A response key, the field it selects and every selection set merged under that key
+type FieldSelection<'a, 'q, Q> = (
+    &'a str,
+    &'a query::Field<'q, Q>,
+    Vec<&'a SelectionSet<'q, Q>>,
+);
+
+/// A small deterministic random number generator (splitmix64) so mocks are stable across
+/// platforms and releases
+#[derive(Debug, Clone)]
+pub struct MockRng {
+    state: u64,
+}
+
+impl MockRng {
+    pub fn new(seed: u64) -> Self {
+        MockRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, or 0 if `bound` is 0
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            0
+        } else {
+            self.next_u64() % bound
+        }
+    }
+
+    /// A number in `0.0..1.0`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// True with the given probability
+    pub fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        schema { query: Query }
+        type Query { user: User! users: [User!]! node: Node! }
+        type User { id: ID! name: String role: Role! }
+        enum Role { ADMIN GUEST }
+        interface Node { id: ID! }
+    "#;
+
+    fn mock_sdl(operation: &str, options: &MockOptions) -> Result<json::Value, MockError> {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap());
+        let document = query::parse_query::<String>(operation).unwrap();
+        mock_with_options(&schema, &document, options).map(json::Value::from)
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_response() {
+        let operation = "{ users { id name role } }";
+        let first = mock_sdl(operation, &MockOptions::default()).unwrap();
+
+        assert_eq!(first, mock_sdl(operation, &MockOptions::default()).unwrap());
+        let reseeded: Vec<json::Value> = (1..5)
+            .map(|seed| {
+                let options = MockOptions {
+                    seed,
+                    ..MockOptions::default()
+                };
+                mock_sdl(operation, &options).unwrap()
+            })
+            .collect();
+        assert!(reseeded.iter().any(|response| *response != first));
+    }
+
+    #[test]
+    fn non_null_fields_and_enums_are_valid() {
+        let options = MockOptions {
+            null_ratio: 1.0,
+            ..MockOptions::default()
+        };
+        let response = mock_sdl("{ user { id name role __typename } }", &options).unwrap();
+        let user = &response["data"]["user"];
+
+        assert!(user["id"].is_string());
+        assert!(user["name"].is_null());
+        assert!(["ADMIN", "GUEST"].contains(&user["role"].as_str().unwrap()));
+        assert_eq!(user["__typename"], "User");
+    }
+
+    #[test]
+    fn abstract_types_without_possible_types_are_errors() {
+        assert_eq!(
+            mock_sdl("{ node { id } }", &MockOptions::default()),
+            Err(MockError::NoPossibleValue("Node".to_string()))
+        );
+    }
+
+    #[test]
+    fn fields_keep_their_selection_order() {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap());
+        let document =
+            query::parse_query::<String>("{ user { role __typename id } users { name } }").unwrap();
+        let options = MockOptions {
+            null_ratio: 0.0,
+            ..MockOptions::default()
+        };
+        let response =
+            json::to_string(&mock_with_options(&schema, &document, &options).unwrap()).unwrap();
+
+        let position = |key: &str| response.find(&format!("\"{}\"", key)).unwrap();
+        assert!(position("user") < position("role"));
+        assert!(position("role") < position("__typename"));
+        assert!(position("__typename") < position("id"));
+        assert!(position("id") < position("users"));
+    }
+}
@@ -0,0 +1,80 @@
+/// Split a GraphQL name into lowercase words on `_`, `-`, and case boundaries.
+/// `userID`, `user_id` and `UserId` all become `["user", "id"]`.
+pub(crate) fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = vec![];
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' || c == ' ' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && !current.is_empty() {
+            let previous = chars[i - 1];
+            let next_is_lower = matches!(chars.get(i + 1), Some(n) if n.is_lowercase());
+            // break on aB, and on the B in ABc so acronyms stay together
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+
+        current.extend(c.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+pub(crate) fn to_snake_case(name: &str) -> String {
+    words(name).join("_")
+}
+
+pub(crate) fn to_pascal_case(name: &str) -> String {
+    words(name).iter().map(|word| capitalize(word)).collect()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Turn any GraphQL name into a usable Rust identifier, escaping keywords
+pub(crate) fn rust_identifier(name: &str) -> String {
+    match name {
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        _ if RUST_KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+/// A snake case Rust field name for a GraphQL name. Names without any words, like `_`, become
+/// `underscore`, and ones starting with a digit once their underscores are gone get one back.
+pub(crate) fn field_identifier(name: &str) -> String {
+    let snake = to_snake_case(name);
+    match snake.chars().next() {
+        None => "underscore".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("_{}", snake),
+        Some(_) => rust_identifier(&snake),
+    }
+}
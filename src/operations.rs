@@ -0,0 +1,607 @@
+use crate::naming::to_pascal_case;
+use crate::schema::{
+    deprecation, named_type, to_owned_type, OperationKind, Schema, TYPENAME_FIELD,
+};
+use graphql_parser::query::{
+    self, Definition, InlineFragment, OperationDefinition, Selection, SelectionSet, TypeCondition,
+    Value, VariableDefinition,
+};
+use graphql_parser::schema::{Field, Text, Type, TypeDefinition};
+use graphql_parser::Pos;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone)]
+pub struct OperationOptions {
+    /// How many levels of fields to nest, counting the root field. Composite fields that would
+    /// go deeper are left out, though a root field always selects at least `__typename`.
+    pub depth: usize,
+    /// Select deprecated fields below the root field too
+    pub include_deprecated: bool,
+}
+
+impl Default for OperationOptions {
+    fn default() -> Self {
+        OperationOptions {
+            depth: 3,
+            include_deprecated: false,
+        }
+    }
+}
+
+/// Write an operation for every field of the query, mutation and subscription types, named
+/// after the field and its kind (`UserQuery`, `CreateUserMutation`, ...).
+///
+/// Each operation selects the scalar and enum fields of what the root field returns, and of
+/// the composite fields under it down to `options.depth`. Interfaces and unions get
+/// `__typename` and an inline fragment for each possible type, and required arguments are
+/// passed as variables typed as the schema declares them. Arguments with defaults and
+/// nullable ones are left out.
+pub fn generate_operations<'a, T: Text<'a>>(
+    schema: &Schema<'a, T>,
+    options: &OperationOptions,
+) -> query::Document<'static, String> {
+    let mut definitions = vec![];
+    let mut names = HashSet::new();
+
+    for kind in &[
+        OperationKind::Query,
+        OperationKind::Mutation,
+        OperationKind::Subscription,
+    ] {
+        let root = match schema.root_type(*kind) {
+            Some(root) => root,
+            None => continue,
+        };
+
+        for field in &root.fields {
+            let field_name = field.name.as_ref();
+            if field_name.starts_with("__") {
+                continue;
+            }
+
+            let mut generator = Generator {
+                schema,
+                options,
+                variables: vec![],
+                variable_names: HashMap::new(),
+            };
+            let root_field = generator
+                .field(root.name.as_ref(), field, None, 1)
+                .expect("root fields always select something");
+            let selection_set = selection_set(vec![Selection::Field(root_field)]);
+
+            let name = unique_name(
+                &mut names,
+                format!("{}{:?}", to_pascal_case(field_name), kind),
+            );
+            let variable_definitions = generator.variables;
+            let operation = match kind {
+                OperationKind::Query => OperationDefinition::Query(query::Query {
+                    position: Pos::default(),
+                    name: Some(name),
+                    variable_definitions,
+                    directives: vec![],
+                    selection_set,
+                }),
+                OperationKind::Mutation => OperationDefinition::Mutation(query::Mutation {
+                    position: Pos::default(),
+                    name: Some(name),
+                    variable_definitions,
+                    directives: vec![],
+                    selection_set,
+                }),
+                OperationKind::Subscription => {
+                    OperationDefinition::Subscription(query::Subscription {
+                        position: Pos::default(),
+                        name: Some(name),
+                        variable_definitions,
+                        directives: vec![],
+                        selection_set,
+                    })
+                }
+            };
+            definitions.push(Definition::Operation(operation));
+        }
+    }
+
+    query::Document { definitions }
+}
+
+struct Generator<'g, 'a, T: Text<'a>> {
+    schema: &'g Schema<'a, T>,
+    options: &'g OperationOptions,
+    variables: Vec<VariableDefinition<'static, String>>,
+    /// The variable for each `Type.field(argument:)`, so a field selected twice passes the
+    /// same arguments both times and the selections can merge
+    variable_names: HashMap<String, String>,
+}
+
+impl<'g, 'a, T: Text<'a>> Generator<'g, 'a, T> {
+    /// The field at `depth` along with what it selects, or `None` if it's composite and
+    /// nothing under it fits within the depth
+    fn field(
+        &mut self,
+        parent: &str,
+        field: &Field<'a, T>,
+        alias: Option<String>,
+        depth: usize,
+    ) -> Option<query::Field<'static, String>> {
+        // declare the field's own variables before those of the fields under it, undoing both
+        // if the field is left out
+        let declared = (self.variables.len(), self.variable_names.clone());
+
+        let mut arguments = vec![];
+        for argument in &field.arguments {
+            let is_required = matches!(argument.value_type, Type::NonNullType(_));
+            if !is_required || argument.default_value.is_some() {
+                continue;
+            }
+
+            let argument_name = argument.name.as_ref();
+            let variable = self.variable(parent, field.name.as_ref(), argument_name, || {
+                to_owned_type(&argument.value_type)
+            });
+            arguments.push((argument_name.to_string(), Value::Variable(variable)));
+        }
+
+        let type_name = named_type(&field.field_type);
+        let mut items = vec![];
+        if !self.schema.is_leaf(type_name) {
+            items = self.selections(type_name, depth + 1);
+            if items.is_empty() || items.iter().all(is_typename) {
+                if depth > 1 {
+                    self.variables.truncate(declared.0);
+                    self.variable_names = declared.1;
+                    return None;
+                }
+                items = vec![typename()];
+            }
+        }
+
+        Some(query::Field {
+            position: Pos::default(),
+            alias,
+            name: field.name.as_ref().to_string(),
+            arguments,
+            directives: vec![],
+            selection_set: selection_set(items),
+        })
+    }
+
+    /// What to select on `type_name`, with fields at `depth`
+    fn selections(&mut self, type_name: &str, depth: usize) -> Vec<Selection<'static, String>> {
+        let mut items = vec![];
+        // the type each response key was selected with, as fields from different fragments
+        // with the same key must have the same type
+        let mut keys = HashMap::new();
+
+        match self.schema.get_type(type_name) {
+            Some(TypeDefinition::Object(_)) => {
+                self.fields(type_name, &[], &mut keys, depth, &mut items);
+            }
+            Some(TypeDefinition::Interface(_)) => {
+                items.push(typename());
+                keys.insert(TYPENAME_FIELD.to_string(), "String!".to_string());
+                self.fields(type_name, &[], &mut keys, depth, &mut items);
+
+                let interface_fields: Vec<&str> = self
+                    .schema
+                    .fields(type_name)
+                    .iter()
+                    .map(|field| field.name.as_ref())
+                    .collect();
+                for object in self.schema.possible_types(type_name) {
+                    let mut fragment_items = vec![];
+                    self.fields(
+                        object,
+                        &interface_fields,
+                        &mut keys,
+                        depth,
+                        &mut fragment_items,
+                    );
+                    if !fragment_items.is_empty() {
+                        items.push(inline_fragment(object, fragment_items));
+                    }
+                }
+            }
+            Some(TypeDefinition::Union(_)) => {
+                items.push(typename());
+                keys.insert(TYPENAME_FIELD.to_string(), "String!".to_string());
+
+                for object in self.schema.possible_types(type_name) {
+                    let mut fragment_items = vec![];
+                    self.fields(object, &[], &mut keys, depth, &mut fragment_items);
+                    if !fragment_items.is_empty() {
+                        items.push(inline_fragment(object, fragment_items));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        items
+    }
+
+    /// Select the fields of `type_name` not in `skip`, aliasing any whose name is already
+    /// taken by a field of a different type
+    fn fields(
+        &mut self,
+        type_name: &str,
+        skip: &[&str],
+        keys: &mut HashMap<String, String>,
+        depth: usize,
+        items: &mut Vec<Selection<'static, String>>,
+    ) {
+        if depth > self.options.depth {
+            return;
+        }
+
+        for field in self.schema.fields(type_name) {
+            let field_name = field.name.as_ref();
+            if skip.contains(&field_name)
+                || (!self.options.include_deprecated && deprecation(&field.directives).is_some())
+            {
+                continue;
+            }
+            let is_leaf = self.schema.is_leaf(named_type(&field.field_type));
+            if !is_leaf && depth >= self.options.depth {
+                continue;
+            }
+
+            let field_type = field.field_type.to_string();
+            let alias = match keys.get(field_name) {
+                Some(existing) if *existing != field_type => {
+                    let mut alias =
+                        format!("{}{}", lower_first(type_name), to_pascal_case(field_name));
+                    let mut suffix = 1;
+                    while keys.contains_key(&alias) {
+                        suffix += 1;
+                        alias = format!(
+                            "{}{}{}",
+                            lower_first(type_name),
+                            to_pascal_case(field_name),
+                            suffix
+                        );
+                    }
+                    Some(alias)
+                }
+                _ => None,
+            };
+
+            if let Some(selected) = self.field(type_name, field, alias.clone(), depth) {
+                keys.insert(alias.unwrap_or_else(|| field_name.to_string()), field_type);
+                items.push(Selection::Field(selected));
+            }
+        }
+    }
+
+    /// The variable for an argument, declaring it the first time it's needed. Variables are
+    /// named after the argument, then the field and argument, then numbered.
+    fn variable(
+        &mut self,
+        type_name: &str,
+        field_name: &str,
+        argument_name: &str,
+        var_type: impl FnOnce() -> query::Type<'static, String>,
+    ) -> String {
+        let coordinate = format!("{}.{}({}:)", type_name, field_name, argument_name);
+        if let Some(name) = self.variable_names.get(&coordinate) {
+            return name.clone();
+        }
+
+        let taken: HashSet<String> = self.variable_names.values().cloned().collect();
+        let mut name = argument_name.to_string();
+        if taken.contains(&name) {
+            name = format!("{}{}", field_name, to_pascal_case(argument_name));
+        }
+        let base = name.clone();
+        let mut suffix = 1;
+        while taken.contains(&name) {
+            suffix += 1;
+            name = format!("{}{}", base, suffix);
+        }
+
+        self.variables.push(VariableDefinition {
+            position: Pos::default(),
+            name: name.clone(),
+            var_type: var_type(),
+            default_value: None,
+        });
+        self.variable_names.insert(coordinate, name.clone());
+        name
+    }
+}
+
+fn selection_set(items: Vec<Selection<'static, String>>) -> SelectionSet<'static, String> {
+    SelectionSet {
+        span: (Pos::default(), Pos::default()),
+        items,
+    }
+}
+
+fn typename() -> Selection<'static, String> {
+    Selection::Field(query::Field {
+        position: Pos::default(),
+        alias: None,
+        name: TYPENAME_FIELD.to_string(),
+        arguments: vec![],
+        directives: vec![],
+        selection_set: selection_set(vec![]),
+    })
+}
+
+fn is_typename(selection: &Selection<'static, String>) -> bool {
+    matches!(selection, Selection::Field(field) if field.name == TYPENAME_FIELD)
+}
+
+fn inline_fragment(
+    type_name: &str,
+    items: Vec<Selection<'static, String>>,
+) -> Selection<'static, String> {
+    Selection::InlineFragment(InlineFragment {
+        position: Pos::default(),
+        type_condition: Some(TypeCondition::On(type_name.to_string())),
+        directives: vec![],
+        selection_set: selection_set(items),
+    })
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn unique_name(names: &mut HashSet<String>, name: String) -> String {
+    let mut unique = name.clone();
+    let mut suffix = 1;
+    while names.contains(&unique) {
+        suffix += 1;
+        unique = format!("{}{}", name, suffix);
+    }
+    names.insert(unique.clone());
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::coverage;
+    use graphql_parser::{parse_query, parse_schema};
+
+    const SCHEMA: &str = r#"
+        schema { query: Query mutation: Mutation }
+        type Query {
+            user(id: ID!, active: Boolean = true): User
+            search(term: String!, first: Int): [Result!]!
+            node(id: ID!): Node
+        }
+        type Mutation { createUser(name: String!): User! }
+        interface Node { id: ID! }
+        type User implements Node { id: ID! name: String role: Role old: String @deprecated }
+        type Post implements Node { id: ID! name: Int author: User }
+        union Result = User | Post
+        enum Role { ADMIN USER }
+    "#;
+
+    /// The problems with an operation: unknown fields and arguments, missing required
+    /// arguments, variables that are defined but not used or used but not defined, and fields
+    /// under the same response key that can't merge
+    fn validate(schema: &Schema<String>, definition: &Definition<String>) -> Vec<String> {
+        let (root, operation) = match definition {
+            Definition::Operation(OperationDefinition::Query(query)) => ("Query", query),
+            Definition::Operation(OperationDefinition::Mutation(mutation)) => {
+                return validate_operation(
+                    schema,
+                    "Mutation",
+                    &mutation.variable_definitions,
+                    &mutation.selection_set,
+                )
+            }
+            _ => return vec!["only queries and mutations are generated".to_string()],
+        };
+        validate_operation(
+            schema,
+            root,
+            &operation.variable_definitions,
+            &operation.selection_set,
+        )
+    }
+
+    fn validate_operation(
+        schema: &Schema<String>,
+        root: &str,
+        variable_definitions: &[VariableDefinition<String>],
+        selection_set: &SelectionSet<String>,
+    ) -> Vec<String> {
+        let mut errors = vec![];
+        let mut used = HashSet::new();
+        validate_selection_set(schema, root, selection_set, &mut used, &mut errors);
+
+        let defined: HashSet<&str> = variable_definitions
+            .iter()
+            .map(|variable| variable.name.as_str())
+            .collect();
+        for name in defined.difference(&used) {
+            errors.push(format!("${} is never used", name));
+        }
+        for name in used.difference(&defined) {
+            errors.push(format!("${} isn't defined", name));
+        }
+        errors
+    }
+
+    fn validate_selection_set<'o>(
+        schema: &Schema<String>,
+        parent: &str,
+        selection_set: &'o SelectionSet<String>,
+        used: &mut HashSet<&'o str>,
+        errors: &mut Vec<String>,
+    ) {
+        // response key, parent type, field name, arguments and type of every field that ends
+        // up in this selection set's response object
+        let mut merged: HashMap<&str, (String, &str, String, String)> = HashMap::new();
+        let mut pending = vec![(parent.to_string(), selection_set)];
+
+        while let Some((parent, selection_set)) = pending.pop() {
+            for selection in &selection_set.items {
+                let field = match selection {
+                    Selection::Field(field) => field,
+                    Selection::InlineFragment(fragment) => {
+                        let on = match &fragment.type_condition {
+                            Some(TypeCondition::On(on)) => on.clone(),
+                            None => parent.clone(),
+                        };
+                        pending.push((on, &fragment.selection_set));
+                        continue;
+                    }
+                    Selection::FragmentSpread(spread) => {
+                        errors.push(format!("unexpected spread of {}", spread.fragment_name));
+                        continue;
+                    }
+                };
+
+                let field_type = if field.name == TYPENAME_FIELD {
+                    "String!".to_string()
+                } else {
+                    let definition = match schema.field(&parent, &field.name) {
+                        Some(definition) => definition,
+                        None => {
+                            errors.push(format!("unknown field {}.{}", parent, field.name));
+                            continue;
+                        }
+                    };
+                    for argument in &definition.arguments {
+                        let required = matches!(argument.value_type, Type::NonNullType(_))
+                            && argument.default_value.is_none();
+                        let passed = field
+                            .arguments
+                            .iter()
+                            .any(|(name, _)| *name == argument.name);
+                        if required && !passed {
+                            errors.push(format!(
+                                "{}.{} is missing {}",
+                                parent, field.name, argument.name
+                            ));
+                        }
+                    }
+                    for (name, value) in &field.arguments {
+                        if !definition
+                            .arguments
+                            .iter()
+                            .any(|argument| argument.name == *name)
+                        {
+                            errors.push(format!(
+                                "unknown argument {}.{}({}:)",
+                                parent, field.name, name
+                            ));
+                        }
+                        if let Value::Variable(variable) = value {
+                            used.insert(variable.as_str());
+                        }
+                    }
+                    if !field.selection_set.items.is_empty() {
+                        validate_selection_set(
+                            schema,
+                            named_type(&definition.field_type),
+                            &field.selection_set,
+                            used,
+                            errors,
+                        );
+                    }
+                    definition.field_type.to_string()
+                };
+
+                let key = field.alias.as_deref().unwrap_or(&field.name);
+                let arguments = format!("{:?}", field.arguments);
+                match merged.get(key) {
+                    Some((_, _, _, other_type)) if *other_type != field_type => {
+                        errors.push(format!("{} is both {} and {}", key, other_type, field_type))
+                    }
+                    Some((other_parent, other_name, other_arguments, _))
+                        if *other_parent == parent
+                            && (*other_name != field.name || *other_arguments != arguments) =>
+                    {
+                        errors.push(format!("{} selects different fields of {}", key, parent))
+                    }
+                    Some(_) => {}
+                    None => {
+                        merged.insert(key, (parent.clone(), &field.name, arguments, field_type));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generated_operations_are_valid() {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap());
+        let operations = generate_operations(&schema, &OperationOptions::default());
+
+        let names: Vec<&str> = operations
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Operation(OperationDefinition::Query(query)) => query.name.as_deref(),
+                Definition::Operation(OperationDefinition::Mutation(mutation)) => {
+                    mutation.name.as_deref()
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "UserQuery",
+                "SearchQuery",
+                "NodeQuery",
+                "CreateUserMutation"
+            ]
+        );
+
+        for definition in &operations.definitions {
+            assert_eq!(validate(&schema, definition), Vec::<String>::new());
+        }
+
+        let invalid = parse_query::<String>(
+            "query Bad($unused: ID) { user { name: role } \
+             search(term: $term) { ... on Post { name } ... on User { name } } }",
+        )
+        .unwrap();
+        let mut errors = validate(&schema, &invalid.definitions[0]);
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "$term isn't defined",
+                "$unused is never used",
+                "Query.user is missing id",
+                "name is both String and Int",
+            ]
+        );
+
+        // every field and type exists, and the printed operations parse back the same
+        let report = coverage(&schema, &[("generated", &operations)]).unwrap();
+        assert!(report.covered > 0);
+        let printed = operations.to_string();
+        assert_eq!(
+            parse_query::<String>(&printed).unwrap().to_string(),
+            printed
+        );
+    }
+
+    #[test]
+    fn passes_required_arguments_and_aliases_conflicting_fields() {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap());
+        let printed = generate_operations(&schema, &OperationOptions::default()).to_string();
+
+        assert!(printed.contains("query UserQuery($id: ID!) {"));
+        assert!(printed.contains("user(id: $id) {"));
+        assert!(printed.contains("query SearchQuery($term: String!) {"));
+        // Post.name is an Int while User.name is a String, so it can't share the key
+        assert!(printed.contains("postName: name"));
+        assert!(!printed.contains("old"));
+    }
+}
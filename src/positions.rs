@@ -0,0 +1,172 @@
+use graphql_parser::schema::{Field, InputValue, Text, TypeDefinition};
+use graphql_parser::Pos;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+/// Maps raw JSON values borrowed from `input` back to the line and column they start at
+pub(crate) struct LineIndex<'r> {
+    input: &'r str,
+    line_starts: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+struct RawTypeDefinition<'r> {
+    #[serde(borrow, default)]
+    fields: Option<Vec<&'r RawValue>>,
+    #[serde(borrow, default, rename = "inputFields")]
+    input_fields: Option<Vec<&'r RawValue>>,
+    #[serde(borrow, default, rename = "enumValues")]
+    enum_values: Option<Vec<&'r RawValue>>,
+}
+
+#[derive(Deserialize)]
+struct RawField<'r> {
+    #[serde(borrow, default)]
+    args: Option<Vec<&'r RawValue>>,
+}
+
+impl<'r> LineIndex<'r> {
+    pub(crate) fn new(input: &'r str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        LineIndex { input, line_starts }
+    }
+
+    /// The 1-based line and column `raw` starts at, `raw` must be a slice of the input
+    pub(crate) fn position(&self, raw: &RawValue) -> Pos {
+        let offset = raw.get().as_ptr() as usize - self.input.as_ptr() as usize;
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let column = self.input[self.line_starts[line]..offset].chars().count() + 1;
+
+        Pos {
+            line: line + 1,
+            column,
+        }
+    }
+
+    /// Fill in the positions of a type, and its fields, arguments and enum values, from the raw
+    /// JSON object it was deserialized from
+    pub(crate) fn fill_type_definition<'a, T: Text<'a>>(
+        &self,
+        type_definition: &mut TypeDefinition<'a, T>,
+        raw: &RawValue,
+    ) -> serde_json::Result<()> {
+        let position = self.position(raw);
+        let raw_type: RawTypeDefinition = serde_json::from_str(raw.get())?;
+
+        match type_definition {
+            TypeDefinition::Scalar(scalar) => scalar.position = position,
+            TypeDefinition::Object(object) => {
+                object.position = position;
+                self.fill_fields(&mut object.fields, raw_type.fields)?;
+            }
+            TypeDefinition::Interface(interface) => {
+                interface.position = position;
+                self.fill_fields(&mut interface.fields, raw_type.fields)?;
+            }
+            TypeDefinition::Union(union) => union.position = position,
+            TypeDefinition::Enum(enum_type) => {
+                enum_type.position = position;
+                for (value, raw) in enum_type
+                    .values
+                    .iter_mut()
+                    .zip(raw_type.enum_values.unwrap_or_default())
+                {
+                    value.position = self.position(raw);
+                }
+            }
+            TypeDefinition::InputObject(input_object) => {
+                input_object.position = position;
+                self.fill_input_values(&mut input_object.fields, raw_type.input_fields);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_fields<'a, T: Text<'a>>(
+        &self,
+        fields: &mut [Field<'a, T>],
+        raw_fields: Option<Vec<&RawValue>>,
+    ) -> serde_json::Result<()> {
+        for (field, raw) in fields.iter_mut().zip(raw_fields.unwrap_or_default()) {
+            field.position = self.position(raw);
+            let raw_field: RawField = serde_json::from_str(raw.get())?;
+            self.fill_input_values(&mut field.arguments, raw_field.args);
+        }
+
+        Ok(())
+    }
+
+    fn fill_input_values<'a, T: Text<'a>>(
+        &self,
+        input_values: &mut [InputValue<'a, T>],
+        raw_input_values: Option<Vec<&RawValue>>,
+    ) {
+        for (input_value, raw) in input_values
+            .iter_mut()
+            .zip(raw_input_values.unwrap_or_default())
+        {
+            input_value.position = self.position(raw);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graphql_parser::schema::{Definition, TypeDefinition};
+    use graphql_parser::Pos;
+
+    const INTROSPECTION: &str = r#"{"data": {"__schema": {
+  "queryType": {"name": "Query"},
+  "types": [
+    {"kind": "OBJECT", "name": "Query", "description": "héllo ✓", "fields": [{"name": "user", "args": [
+        {"name": "id", "type": {"kind": "NON_NULL", "ofType": {"kind": "SCALAR", "name": "ID"}}}
+      ], "type": {"kind": "SCALAR", "name": "String"}}, {"name": "ünïcode", "args": [], "type": {"kind": "SCALAR", "name": "String"}}
+    ], "interfaces": []},
+    {"kind": "ENUM", "name": "Role", "enumValues": [{"name": "ADMIN"},
+      {"name": "USER"}]}
+  ]
+}}}"#;
+
+    fn position(line: usize, column: usize) -> Pos {
+        Pos { line, column }
+    }
+
+    #[test]
+    fn fills_lines_and_char_columns() {
+        let document = crate::parse_with_positions::<String>(INTROSPECTION).unwrap();
+        let types: Vec<&TypeDefinition<String>> = document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::TypeDefinition(type_definition) => Some(type_definition),
+                _ => None,
+            })
+            .collect();
+
+        match types[0] {
+            TypeDefinition::Object(query) => {
+                assert_eq!(query.position, position(4, 5));
+                // The description before it has multi-byte chars, columns count chars not bytes
+                assert_eq!(query.fields[0].position, position(4, 78));
+                assert_eq!(query.fields[0].arguments[0].position, position(5, 9));
+                assert_eq!(query.fields[1].position, position(6, 57));
+            }
+            other => panic!("expected an object, got {:?}", other),
+        }
+        match types[1] {
+            TypeDefinition::Enum(role) => {
+                assert_eq!(role.position, position(8, 5));
+                assert_eq!(role.values[0].position, position(8, 53));
+                assert_eq!(role.values[1].position, position(9, 7));
+            }
+            other => panic!("expected an enum, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,651 @@
+use crate::schema::{
+    fragments, named_type, operation_parts, type_definition_name, OperationKind, Schema,
+    BUILT_IN_SCALARS,
+};
+use graphql_parser::query::{
+    self, FragmentDefinition, OperationDefinition, Selection, SelectionSet, TypeCondition,
+};
+use graphql_parser::schema::{
+    Definition, Document, Field, InputValue, SchemaDefinition, Text, Type, TypeDefinition, Value,
+};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PruneError {
+    MissingRootType(OperationKind),
+    UnknownType(String),
+    UnknownField {
+        type_name: String,
+        field: String,
+    },
+    UnknownArgument {
+        type_name: String,
+        field: String,
+        argument: String,
+    },
+    UnknownFragment(String),
+}
+
+impl fmt::Display for PruneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PruneError::MissingRootType(kind) => {
+                write!(f, "the schema has no {:?} root type", kind)
+            }
+            PruneError::UnknownType(name) => write!(f, "unknown type '{}'", name),
+            PruneError::UnknownField { type_name, field } => {
+                write!(f, "unknown field '{}' on type '{}'", field, type_name)
+            }
+            PruneError::UnknownArgument {
+                type_name,
+                field,
+                argument,
+            } => write!(
+                f,
+                "unknown argument '{}' on field '{}.{}'",
+                argument, type_name, field
+            ),
+            PruneError::UnknownFragment(name) => write!(f, "unknown fragment '{}'", name),
+        }
+    }
+}
+
+impl Error for PruneError {}
+
+/// What the operations use of a type
+#[derive(Debug, Default)]
+struct Usage {
+    /// Fields or input fields, with the arguments used on each field
+    fields: HashMap<String, HashSet<String>>,
+    enum_values: HashSet<String>,
+    /// Set for input types given by variables and enums that are returned, since any of their
+    /// fields or values could turn up
+    everything: bool,
+}
+
+/// Cut the schema down to the types, fields, arguments and enum values `operations` reach.
+///
+/// Fragments can be defined in any of the documents. Input types given as variables are kept
+/// whole, while literal arguments only keep the input fields and enum values they spell out.
+/// Enums returned by a field keep every value, as the server could return any of them.
+pub fn prune<'s, 'q, T, Q>(
+    schema: &Schema<'s, T>,
+    operations: &[query::Document<'q, Q>],
+) -> Result<Document<'s, T>, PruneError>
+where
+    T: Text<'s> + Clone,
+    Q: Text<'q>,
+{
+    let mut pruner = Pruner {
+        schema,
+        fragments: fragments(operations.iter().flat_map(|document| &document.definitions)),
+        spreading: HashSet::new(),
+        types: HashMap::new(),
+        directives: HashSet::new(),
+    };
+
+    for definition in operations.iter().flat_map(|document| &document.definitions) {
+        if let query::Definition::Operation(operation) = definition {
+            pruner.operation(operation)?;
+        }
+    }
+    pruner.fill_empty_types()?;
+    while pruner.complete_types()? {
+        pruner.fill_empty_types()?;
+    }
+
+    Ok(pruner.document())
+}
+
+struct Pruner<'p, 's, 'q, T: Text<'s>, Q: Text<'q>> {
+    schema: &'p Schema<'s, T>,
+    fragments: HashMap<&'p str, &'p FragmentDefinition<'q, Q>>,
+    /// Fragments being walked, so a cycle of spreads can't recurse forever
+    spreading: HashSet<&'p str>,
+    types: HashMap<String, Usage>,
+    directives: HashSet<String>,
+}
+
+impl<'p, 's, 'q, T: Text<'s> + Clone, Q: Text<'q>> Pruner<'p, 's, 'q, T, Q> {
+    fn operation(&mut self, operation: &'p OperationDefinition<'q, Q>) -> Result<(), PruneError> {
+        let parts = operation_parts(operation);
+        let root_type = self
+            .schema
+            .root_type_name(parts.kind)
+            .ok_or(PruneError::MissingRootType(parts.kind))?;
+
+        for variable in parts.variable_definitions {
+            self.whole_input_type(named_type(&variable.var_type))?;
+        }
+        self.directives(parts.directives)?;
+        self.use_type(root_type)?;
+        self.selection_set(root_type, parts.selection_set)
+    }
+
+    fn selection_set(
+        &mut self,
+        type_name: &str,
+        selection_set: &'p SelectionSet<'q, Q>,
+    ) -> Result<(), PruneError> {
+        let schema = self.schema;
+
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let name = field.name.as_ref();
+                    self.directives(&field.directives)?;
+                    // __typename, __schema and __type belong to every schema
+                    if name.starts_with("__") {
+                        continue;
+                    }
+
+                    let schema_field =
+                        schema
+                            .field(type_name, name)
+                            .ok_or_else(|| PruneError::UnknownField {
+                                type_name: type_name.to_string(),
+                                field: name.to_string(),
+                            })?;
+
+                    let mut arguments = HashSet::new();
+                    for (argument, value) in &field.arguments {
+                        let argument = argument.as_ref();
+                        let schema_argument = schema_field
+                            .arguments
+                            .iter()
+                            .find(|arg| arg.name.as_ref() == argument)
+                            .ok_or_else(|| PruneError::UnknownArgument {
+                                type_name: type_name.to_string(),
+                                field: name.to_string(),
+                                argument: argument.to_string(),
+                            })?;
+                        self.input_value(&schema_argument.value_type, value)?;
+                        if let Some(default_value) = &schema_argument.default_value {
+                            self.input_value(&schema_argument.value_type, default_value)?;
+                        }
+                        arguments.insert(argument.to_string());
+                    }
+                    self.use_type(type_name)?
+                        .fields
+                        .entry(name.to_string())
+                        .or_default()
+                        .extend(arguments);
+
+                    let field_type = named_type(&schema_field.field_type);
+                    if let Some(TypeDefinition::Enum(_)) = schema.get_type(field_type) {
+                        self.use_type(field_type)?.everything = true;
+                    } else {
+                        self.use_type(field_type)?;
+                    }
+                    self.selection_set(field_type, &field.selection_set)?;
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.directives(&spread.directives)?;
+                    let name = spread.fragment_name.as_ref();
+                    let fragment = self
+                        .fragments
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| PruneError::UnknownFragment(name.to_string()))?;
+
+                    if self.spreading.insert(fragment.name.as_ref()) {
+                        let TypeCondition::On(condition) = &fragment.type_condition;
+                        self.directives(&fragment.directives)?;
+                        self.use_type(condition.as_ref())?;
+                        self.selection_set(condition.as_ref(), &fragment.selection_set)?;
+                        self.spreading.remove(fragment.name.as_ref());
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.directives(&fragment.directives)?;
+                    let condition = match &fragment.type_condition {
+                        Some(TypeCondition::On(condition)) => condition.as_ref(),
+                        None => type_name,
+                    };
+                    self.use_type(condition)?;
+                    self.selection_set(condition, &fragment.selection_set)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Use the parts of an input type that a literal value spells out, either in an operation or
+    /// as a default value in the schema
+    fn input_value<'v, V: Text<'v>>(
+        &mut self,
+        value_type: &Type<'s, T>,
+        value: &Value<'v, V>,
+    ) -> Result<(), PruneError> {
+        let schema = self.schema;
+        let type_name = named_type(value_type);
+        self.use_type(type_name)?;
+
+        match value {
+            Value::Variable(_) => self.whole_input_type(type_name)?,
+            Value::List(items) => {
+                for item in items {
+                    self.input_value(value_type, item)?;
+                }
+            }
+            Value::Enum(enum_value) => {
+                self.use_type(type_name)?
+                    .enum_values
+                    .insert(enum_value.as_ref().to_string());
+            }
+            Value::Object(fields) => {
+                for (name, value) in fields {
+                    let input_field = schema
+                        .input_fields(type_name)
+                        .iter()
+                        .find(|input_field| input_field.name.as_ref() == name.as_ref())
+                        .ok_or_else(|| PruneError::UnknownField {
+                            type_name: type_name.to_string(),
+                            field: name.as_ref().to_string(),
+                        })?;
+                    self.use_type(type_name)?
+                        .fields
+                        .entry(name.as_ref().to_string())
+                        .or_default();
+                    self.input_value(&input_field.value_type, value)?;
+                    if let Some(default_value) = &input_field.default_value {
+                        self.input_value(&input_field.value_type, default_value)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Use every input field and enum value reachable from an input type
+    fn whole_input_type(&mut self, type_name: &str) -> Result<(), PruneError> {
+        let usage = self.use_type(type_name)?;
+        if usage.everything {
+            return Ok(());
+        }
+        usage.everything = true;
+
+        for input_field in self.schema.input_fields(type_name) {
+            self.whole_input_type(named_type(&input_field.value_type))?;
+        }
+
+        Ok(())
+    }
+
+    fn directives(&mut self, directives: &[query::Directive<'q, Q>]) -> Result<(), PruneError> {
+        let schema = self.schema;
+
+        for directive in directives {
+            let definition =
+                schema
+                    .document()
+                    .definitions
+                    .iter()
+                    .find_map(|definition| match definition {
+                        Definition::DirectiveDefinition(definition)
+                            if definition.name.as_ref() == directive.name.as_ref() =>
+                        {
+                            Some(definition)
+                        }
+                        _ => None,
+                    });
+
+            if let Some(definition) = definition {
+                if self.directives.insert(directive.name.as_ref().to_string()) {
+                    for argument in &definition.arguments {
+                        self.whole_input_type(named_type(&argument.value_type))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Objects and interfaces need at least one field, so those only reached through
+    /// `__typename` or fragments on other types keep their first scalar or enum field
+    fn fill_empty_types(&mut self) -> Result<(), PruneError> {
+        let schema = self.schema;
+
+        loop {
+            let empty: Vec<String> = self
+                .types
+                .iter()
+                .filter(|(name, usage)| usage.fields.is_empty() && !schema.fields(name).is_empty())
+                .map(|(name, _)| name.clone())
+                .collect();
+            if empty.is_empty() {
+                return Ok(());
+            }
+
+            for type_name in empty {
+                let fields = schema.fields(&type_name);
+                let field = fields
+                    .iter()
+                    .find(|field| schema.is_leaf(named_type(&field.field_type)))
+                    .unwrap_or(&fields[0]);
+                let field_type = named_type(&field.field_type);
+
+                self.use_type(&type_name)?
+                    .fields
+                    .insert(field.name.as_ref().to_string(), HashSet::new());
+                self.use_type(field_type)?.everything = true;
+                for argument in field.arguments.iter().filter(|arg| is_required(arg)) {
+                    self.whole_input_type(named_type(&argument.value_type))?;
+                }
+            }
+        }
+    }
+
+    /// Use what the used types need to be valid: the fields of the interfaces an object or
+    /// interface implements, the types every used field returns and a member of each union.
+    /// Returns whether anything new was used.
+    fn complete_types(&mut self) -> Result<bool, PruneError> {
+        let schema = self.schema;
+        let mut changed = false;
+
+        let used: Vec<String> = self.types.keys().cloned().collect();
+        for type_name in &used {
+            let interfaces = match schema.get_type(type_name) {
+                Some(TypeDefinition::Object(object)) => &object.implements_interfaces,
+                Some(TypeDefinition::Interface(interface)) => &interface.implements_interfaces,
+                Some(TypeDefinition::Union(union)) => {
+                    let has_member = union
+                        .types
+                        .iter()
+                        .any(|member| self.types.contains_key(member.as_ref()));
+                    if let (false, Some(member)) = (has_member, union.types.first()) {
+                        self.use_type(member.as_ref())?;
+                        changed = true;
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+
+            for interface in interfaces {
+                let interface_fields = match self.types.get(interface.as_ref()) {
+                    Some(usage) => usage.fields.clone(),
+                    None => continue,
+                };
+                let usage = self.use_type(type_name)?;
+                for (field, arguments) in interface_fields {
+                    let used_arguments = usage.fields.entry(field).or_insert_with(|| {
+                        changed = true;
+                        HashSet::new()
+                    });
+                    if !arguments.is_subset(used_arguments) {
+                        used_arguments.extend(arguments);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        // fields kept through an interface can return a more specific type than it does
+        let used_fields: Vec<(String, Vec<String>)> = self
+            .types
+            .iter()
+            .map(|(name, usage)| (name.clone(), usage.fields.keys().cloned().collect()))
+            .collect();
+        for (type_name, fields) in used_fields {
+            for field_name in fields {
+                let field = match schema.field(&type_name, &field_name) {
+                    Some(field) => field,
+                    None => continue,
+                };
+                let field_type = named_type(&field.field_type);
+                if self.types.contains_key(field_type) {
+                    continue;
+                }
+
+                changed = true;
+                if let Some(TypeDefinition::Enum(_)) = schema.get_type(field_type) {
+                    self.use_type(field_type)?.everything = true;
+                } else {
+                    self.use_type(field_type)?;
+                }
+                for argument in field.arguments.iter().filter(|arg| is_required(arg)) {
+                    self.whole_input_type(named_type(&argument.value_type))?;
+                }
+            }
+        }
+
+        Ok(changed)
+    }
+
+    fn use_type(&mut self, type_name: &str) -> Result<&mut Usage, PruneError> {
+        if self.schema.get_type(type_name).is_none() && !BUILT_IN_SCALARS.contains(&type_name) {
+            return Err(PruneError::UnknownType(type_name.to_string()));
+        }
+
+        Ok(self.types.entry(type_name.to_string()).or_default())
+    }
+
+    /// Copy the used parts of the schema, in the order they were defined
+    fn document(&self) -> Document<'s, T> {
+        let mut definitions = vec![];
+
+        for definition in &self.schema.document().definitions {
+            match definition {
+                Definition::TypeDefinition(type_definition) => {
+                    if let Some(usage) = self.types.get(type_definition_name(type_definition)) {
+                        definitions.push(Definition::TypeDefinition(
+                            self.type_definition(type_definition, usage),
+                        ));
+                    }
+                }
+                Definition::DirectiveDefinition(directive)
+                    if self.directives.contains(directive.name.as_ref()) =>
+                {
+                    definitions.push(Definition::DirectiveDefinition(directive.clone()));
+                }
+                Definition::SchemaDefinition(schema_definition) => {
+                    let used = |root: &Option<T::Value>| {
+                        root.clone()
+                            .filter(|root| self.types.contains_key(root.as_ref()))
+                    };
+                    definitions.push(Definition::SchemaDefinition(SchemaDefinition {
+                        query: used(&schema_definition.query),
+                        mutation: used(&schema_definition.mutation),
+                        subscription: used(&schema_definition.subscription),
+                        ..schema_definition.clone()
+                    }));
+                }
+                _ => {}
+            }
+        }
+
+        Document { definitions }
+    }
+
+    fn type_definition(
+        &self,
+        type_definition: &TypeDefinition<'s, T>,
+        usage: &Usage,
+    ) -> TypeDefinition<'s, T> {
+        let mut type_definition = type_definition.clone();
+
+        match &mut type_definition {
+            TypeDefinition::Scalar(_) => {}
+            TypeDefinition::Object(object) => {
+                self.interfaces(&mut object.implements_interfaces);
+                object.fields = self.fields(&object.fields, usage);
+            }
+            TypeDefinition::Interface(interface) => {
+                self.interfaces(&mut interface.implements_interfaces);
+                interface.fields = self.fields(&interface.fields, usage);
+            }
+            TypeDefinition::Union(union) => union
+                .types
+                .retain(|member| self.types.contains_key(member.as_ref())),
+            TypeDefinition::Enum(enum_type) => {
+                if !usage.everything {
+                    enum_type
+                        .values
+                        .retain(|value| usage.enum_values.contains(value.name.as_ref()));
+                }
+            }
+            TypeDefinition::InputObject(input_object) => {
+                if !usage.everything {
+                    input_object.fields.retain(|input_field| {
+                        usage.fields.contains_key(input_field.name.as_ref())
+                            || is_required(input_field)
+                    });
+                }
+            }
+        }
+
+        type_definition
+    }
+
+    fn interfaces(&self, interfaces: &mut Vec<T::Value>) {
+        interfaces.retain(|interface| self.types.contains_key(interface.as_ref()));
+    }
+
+    /// The used fields of an object or interface, which include those used through the
+    /// interfaces it implements
+    fn fields(&self, fields: &[Field<'s, T>], usage: &Usage) -> Vec<Field<'s, T>> {
+        fields
+            .iter()
+            .filter_map(|field| {
+                let used_arguments = usage.fields.get(field.name.as_ref())?;
+                let mut field = field.clone();
+                field.arguments.retain(|argument| {
+                    used_arguments.contains(argument.name.as_ref()) || is_required(argument)
+                });
+                Some(field)
+            })
+            .collect()
+    }
+}
+
+fn is_required<'a, T: Text<'a>>(input_value: &InputValue<'a, T>) -> bool {
+    matches!(input_value.value_type, Type::NonNullType(_)) && input_value.default_value.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        schema { query: Query }
+        type Query { node(id: ID!): Node search(filter: Filter): [Result!]! unused: String }
+        interface Node { id: ID! owner: Node }
+        type User implements Node { id: ID! owner: Admin name: String age: Int }
+        type Admin implements Node { id: ID! owner: Admin level: Int }
+        type Team implements Node { id: ID! owner: Team size: Int }
+        union Result = User | Team
+        input Filter { name: String role: Role limit: Int }
+        enum Role { ADMIN GUEST }
+    "#;
+
+    fn prune_sdl(operations: &str) -> Document<'static, String> {
+        let schema = Schema::new(parse_schema::<String>(SCHEMA).unwrap().into_static());
+        let operations = query::parse_query::<String>(operations).unwrap();
+        let pruned = prune(&schema, &[operations]).unwrap();
+        assert_references_kept(&pruned);
+        pruned
+    }
+
+    /// Every type the pruned document mentions is defined in it, and no union is empty
+    fn assert_references_kept(document: &Document<'static, String>) {
+        let schema = Schema::new(document.clone());
+        let defined = |name: &str| {
+            assert!(
+                schema.get_type(name).is_some() || BUILT_IN_SCALARS.contains(&name),
+                "'{}' is used but was pruned",
+                name
+            )
+        };
+        let input_values = |values: &[InputValue<'static, String>]| {
+            for value in values {
+                defined(named_type(&value.value_type));
+            }
+        };
+
+        for type_definition in schema.types() {
+            match type_definition {
+                TypeDefinition::Object(object) => {
+                    object.implements_interfaces.iter().for_each(|i| defined(i));
+                    for field in &object.fields {
+                        defined(named_type(&field.field_type));
+                        input_values(&field.arguments);
+                    }
+                }
+                TypeDefinition::Interface(interface) => {
+                    for field in &interface.fields {
+                        defined(named_type(&field.field_type));
+                        input_values(&field.arguments);
+                    }
+                }
+                TypeDefinition::Union(union) => {
+                    assert!(!union.types.is_empty(), "'{}' has no members", union.name);
+                    union.types.iter().for_each(|member| defined(member));
+                }
+                TypeDefinition::InputObject(input_object) => input_values(&input_object.fields),
+                TypeDefinition::Scalar(_) | TypeDefinition::Enum(_) => {}
+            }
+        }
+    }
+
+    fn type_names<'d>(document: &'d Document<'static, String>) -> Vec<&'d str> {
+        document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::TypeDefinition(type_definition) => {
+                    Some(type_definition_name(type_definition))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fields_kept_through_an_interface_keep_their_return_types() {
+        let pruned = prune_sdl(r#"{ node(id: "1") { owner { id } ... on User { name } } }"#);
+
+        // `User.owner` returns `Admin`, which has to satisfy `Node` too
+        assert_eq!(type_names(&pruned), vec!["Query", "Node", "User", "Admin"]);
+        let schema = Schema::new(pruned);
+        let field_names = |type_name| -> Vec<&str> {
+            schema
+                .fields(type_name)
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect()
+        };
+        assert_eq!(field_names("User"), vec!["id", "owner", "name"]);
+        assert_eq!(field_names("Admin"), vec!["id", "owner"]);
+    }
+
+    #[test]
+    fn unions_keep_a_member() {
+        let pruned = prune_sdl("{ search { __typename } }");
+
+        assert_eq!(type_names(&pruned), vec!["Query", "User", "Result"]);
+    }
+
+    #[test]
+    fn literal_arguments_keep_what_they_spell_out() {
+        let pruned = prune_sdl("{ search(filter: { role: ADMIN }) { ... on Team { size } } }");
+        let schema = Schema::new(pruned);
+
+        assert_eq!(schema.input_fields("Filter").len(), 1);
+        match schema.get_type("Role") {
+            Some(TypeDefinition::Enum(role)) => {
+                assert_eq!(role.values.len(), 1);
+                assert_eq!(role.values[0].name, "ADMIN");
+            }
+            other => panic!("expected the Role enum, got {:?}", other),
+        }
+        assert!(schema.field("Query", "unused").is_none());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::schema::{named_type, type_definition_name, OperationKind, Schema, BUILT_IN_SCALARS};
+use graphql_parser::schema::{Definition, Document, Text, TypeDefinition};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reachability {
+    /// Types no root operation type can lead to, in the order the server reported them.
+    /// Built-in scalars and introspection's own `__` types aren't included.
+    pub unreachable_types: Vec<String>,
+    /// Interfaces no object type implements
+    pub unimplemented_interfaces: Vec<String>,
+}
+
+impl Reachability {
+    pub fn is_clean(&self) -> bool {
+        self.unreachable_types.is_empty() && self.unimplemented_interfaces.is_empty()
+    }
+}
+
+/// Find the types that can't be reached from the query, mutation or subscription types and the
+/// interfaces without implementations
+pub fn analyze<'a, T: Text<'a>>(schema: &Schema<'a, T>) -> Reachability {
+    let reachable = reachable_types(schema);
+
+    let unreachable_types = schema
+        .types()
+        .map(type_definition_name)
+        .filter(|name| {
+            !reachable.contains(name) && !name.starts_with("__") && !BUILT_IN_SCALARS.contains(name)
+        })
+        .map(str::to_string)
+        .collect();
+    let unimplemented_interfaces = schema
+        .types()
+        .filter_map(|type_definition| match type_definition {
+            TypeDefinition::Interface(interface)
+                if schema.possible_types(interface.name.as_ref()).is_empty() =>
+            {
+                Some(interface.name.as_ref().to_string())
+            }
+            _ => None,
+        })
+        .collect();
+
+    Reachability {
+        unreachable_types,
+        unimplemented_interfaces,
+    }
+}
+
+/// Every type reachable from a root operation type through fields, arguments, input fields,
+/// interfaces, union members and the implementations of interfaces. The argument types of
+/// directive definitions count too, as operations can use them.
+pub fn reachable_types<'s, 'a, T: Text<'a>>(schema: &'s Schema<'a, T>) -> HashSet<&'s str> {
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<&str> = [
+        OperationKind::Query,
+        OperationKind::Mutation,
+        OperationKind::Subscription,
+    ]
+    .iter()
+    .filter_map(|kind| schema.root_type_name(*kind))
+    .collect();
+
+    for definition in &schema.document().definitions {
+        if let Definition::DirectiveDefinition(directive) = definition {
+            pending.extend(
+                directive
+                    .arguments
+                    .iter()
+                    .map(|argument| named_type(&argument.value_type)),
+            );
+        }
+    }
+
+    while let Some(name) = pending.pop() {
+        let type_definition = match schema.get_type(name) {
+            Some(type_definition) if reachable.insert(name) => type_definition,
+            _ => continue,
+        };
+
+        match type_definition {
+            TypeDefinition::Scalar(_) | TypeDefinition::Enum(_) => {}
+            TypeDefinition::Object(object) => {
+                pending.extend(object.implements_interfaces.iter().map(AsRef::as_ref));
+                for field in &object.fields {
+                    pending.push(named_type(&field.field_type));
+                    pending.extend(
+                        field
+                            .arguments
+                            .iter()
+                            .map(|arg| named_type(&arg.value_type)),
+                    );
+                }
+            }
+            TypeDefinition::Interface(interface) => {
+                pending.extend(interface.implements_interfaces.iter().map(AsRef::as_ref));
+                for field in &interface.fields {
+                    pending.push(named_type(&field.field_type));
+                    pending.extend(
+                        field
+                            .arguments
+                            .iter()
+                            .map(|arg| named_type(&arg.value_type)),
+                    );
+                }
+                // interfaces implementing this one, as well as objects
+                pending.extend(schema.types().filter_map(
+                    |type_definition| match type_definition {
+                        TypeDefinition::Object(object)
+                            if object.implements_interfaces.contains(&interface.name) =>
+                        {
+                            Some(object.name.as_ref())
+                        }
+                        TypeDefinition::Interface(other)
+                            if other.implements_interfaces.contains(&interface.name) =>
+                        {
+                            Some(other.name.as_ref())
+                        }
+                        _ => None,
+                    },
+                ));
+            }
+            TypeDefinition::Union(union) => {
+                pending.extend(union.types.iter().map(AsRef::as_ref));
+            }
+            TypeDefinition::InputObject(input_object) => {
+                pending.extend(
+                    input_object
+                        .fields
+                        .iter()
+                        .map(|input_field| named_type(&input_field.value_type)),
+                );
+            }
+        }
+    }
+
+    reachable
+}
+
+/// Drop the types `analyze` reports as unreachable. Interfaces without implementations are only
+/// dropped if they're unreachable as well, since removing one a field returns would leave the
+/// field without a type.
+pub fn remove_unreachable<'a, T: Text<'a>>(schema: Schema<'a, T>) -> Document<'a, T> {
+    let unreachable: HashSet<String> = analyze(&schema).unreachable_types.into_iter().collect();
+
+    let mut document = schema.into_document();
+    document.definitions.retain(|definition| match definition {
+        Definition::TypeDefinition(type_definition) => {
+            !unreachable.contains(type_definition_name(type_definition))
+        }
+        _ => true,
+    });
+
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SCHEMA: &str = r#"
+        directive @cached(scope: CacheScope) on FIELD
+
+        enum CacheScope { PUBLIC PRIVATE }
+
+        interface Node { id: ID! }
+
+        interface Named { name: String }
+
+        type User implements Node { id: ID! profile: Profile }
+
+        type Profile { bio: String }
+
+        type Orphan { id: ID! }
+
+        input OrphanInput { id: ID! }
+
+        type Query { node(id: ID!): Node }
+    "#;
+
+    fn schema(sdl: &str) -> Schema<'_, String> {
+        Schema::new(parse_schema::<String>(sdl).unwrap())
+    }
+
+    #[test]
+    fn reports_orphans_and_unimplemented_interfaces() {
+        assert_eq!(
+            analyze(&schema(SCHEMA)),
+            Reachability {
+                unreachable_types: vec![
+                    "Named".to_string(),
+                    "Orphan".to_string(),
+                    "OrphanInput".to_string(),
+                ],
+                unimplemented_interfaces: vec!["Named".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn reaches_implementations_and_directive_arguments() {
+        let schema = schema(SCHEMA);
+        let reachable = reachable_types(&schema);
+
+        // User is only reachable as an implementation of Node, and Profile through User
+        assert!(reachable.contains("User"));
+        assert!(reachable.contains("Profile"));
+        // CacheScope is only used by @cached
+        assert!(reachable.contains("CacheScope"));
+        assert!(!reachable.contains("Orphan"));
+    }
+
+    #[test]
+    fn removing_unreachable_types_leaves_a_valid_document() {
+        let sdl = remove_unreachable(schema(SCHEMA)).to_string();
+        let document = parse_schema::<String>(&sdl).unwrap();
+        let pruned = Schema::new(document);
+
+        assert!(analyze(&pruned).is_clean());
+        for type_definition in pruned.types() {
+            let fields = match type_definition {
+                TypeDefinition::Object(object) => &object.fields,
+                TypeDefinition::Interface(interface) => &interface.fields,
+                _ => continue,
+            };
+            let referenced = fields.iter().flat_map(|field| {
+                std::iter::once(named_type(&field.field_type)).chain(
+                    field
+                        .arguments
+                        .iter()
+                        .map(|arg| named_type(&arg.value_type)),
+                )
+            });
+            for name in referenced {
+                assert!(
+                    pruned.get_type(name).is_some() || BUILT_IN_SCALARS.contains(&name),
+                    "{} is missing",
+                    name
+                );
+            }
+        }
+        assert!(pruned.get_type("CacheScope").is_some());
+        assert!(pruned.get_type("Orphan").is_none());
+    }
+}
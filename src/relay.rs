@@ -0,0 +1,526 @@
+use crate::schema::{is_list, named_type, OperationKind, Schema, BUILT_IN_SCALARS};
+use graphql_parser::schema::{Field, Text, Type, TypeDefinition};
+use graphql_parser::Pos;
+use std::collections::BTreeSet;
+use std::fmt;
+
+const NODE_INTERFACE: &str = "Node";
+const PAGE_INFO: &str = "PageInfo";
+const CONNECTION_SUFFIX: &str = "Connection";
+const EDGE_SUFFIX: &str = "Edge";
+
+/// The part of the Relay server specification a finding is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelayRule {
+    /// A `Node` interface with `id: ID!`
+    NodeInterface,
+    /// A `node(id: ID!): Node` field on the query type
+    NodeField,
+    /// `*Connection` types with a list of `edges` and `pageInfo: PageInfo!`
+    Connection,
+    /// Edge types with a `cursor` and a single `node`
+    Edge,
+    /// `PageInfo` with `hasNextPage`, `hasPreviousPage`, `startCursor` and `endCursor`. Cursors
+    /// can be any scalar, as the spec only asks that they serialize as strings.
+    PageInfo,
+    /// Connection fields taking `first` and `after`, or `last` and `before`, with cursors of
+    /// any scalar type
+    PaginationArguments,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelayFinding {
+    pub rule: RelayRule,
+    /// The type or field the finding is about, ie: `UserConnection.edges`
+    pub coordinate: String,
+    pub message: String,
+    /// Where the type or field starts, only set if the schema was parsed with positions
+    pub position: Pos,
+}
+
+impl fmt::Display for RelayFinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.coordinate, self.message)
+    }
+}
+
+/// Check a schema against the Relay server specification, covering object identification,
+/// connections, edges, `PageInfo` and pagination arguments. An empty result means the schema
+/// is compliant.
+pub fn check<'a, T: Text<'a>>(schema: &Schema<'a, T>) -> Vec<RelayFinding> {
+    let mut checker = Checker {
+        schema,
+        findings: vec![],
+    };
+
+    checker.node_interface();
+    checker.node_field();
+
+    let mut edge_types = BTreeSet::new();
+    let mut has_connections = false;
+    for type_definition in schema.types() {
+        if let TypeDefinition::Object(object) = type_definition {
+            let name = object.name.as_ref();
+            if name.ends_with(CONNECTION_SUFFIX) {
+                has_connections = true;
+                if let Some(edge_type) = checker.connection(name, object.position, &object.fields) {
+                    edge_types.insert(edge_type);
+                }
+            } else if name.ends_with(EDGE_SUFFIX) {
+                edge_types.insert(name);
+            }
+
+            checker.pagination_arguments(name, &object.fields);
+        } else if let TypeDefinition::Interface(interface) = type_definition {
+            checker.pagination_arguments(interface.name.as_ref(), &interface.fields);
+        }
+    }
+
+    for edge_type in edge_types {
+        checker.edge(edge_type);
+    }
+    if has_connections || schema.get_type(PAGE_INFO).is_some() {
+        checker.page_info();
+    }
+
+    checker.findings
+}
+
+struct Checker<'c, 'a, T: Text<'a>> {
+    schema: &'c Schema<'a, T>,
+    findings: Vec<RelayFinding>,
+}
+
+impl<'c, 'a, T: Text<'a>> Checker<'c, 'a, T> {
+    fn report(&mut self, rule: RelayRule, coordinate: String, position: Pos, message: String) {
+        self.findings.push(RelayFinding {
+            rule,
+            coordinate,
+            message,
+            position,
+        });
+    }
+
+    fn node_interface(&mut self) {
+        let node = match self.schema.get_type(NODE_INTERFACE) {
+            Some(TypeDefinition::Interface(node)) => node,
+            Some(_) => {
+                return self.report(
+                    RelayRule::NodeInterface,
+                    NODE_INTERFACE.to_string(),
+                    Pos::default(),
+                    "Node should be an interface".to_string(),
+                )
+            }
+            None => {
+                return self.report(
+                    RelayRule::NodeInterface,
+                    NODE_INTERFACE.to_string(),
+                    Pos::default(),
+                    "there's no Node interface".to_string(),
+                )
+            }
+        };
+
+        match node.fields.iter().find(|field| field.name.as_ref() == "id") {
+            Some(id) if id.field_type.to_string() == "ID!" => {}
+            Some(id) => self.report(
+                RelayRule::NodeInterface,
+                "Node.id".to_string(),
+                id.position,
+                format!("id should be ID!, found {}", id.field_type),
+            ),
+            None => self.report(
+                RelayRule::NodeInterface,
+                NODE_INTERFACE.to_string(),
+                node.position,
+                "Node has no id field".to_string(),
+            ),
+        }
+    }
+
+    fn node_field(&mut self) {
+        let query = match self.schema.root_type(OperationKind::Query) {
+            Some(query) => query,
+            None => return,
+        };
+        let coordinate = format!("{}.node", query.name.as_ref());
+
+        let node = match query
+            .fields
+            .iter()
+            .find(|field| field.name.as_ref() == "node")
+        {
+            Some(node) => node,
+            None => {
+                return self.report(
+                    RelayRule::NodeField,
+                    query.name.as_ref().to_string(),
+                    query.position,
+                    "the query type has no node(id: ID!) field".to_string(),
+                )
+            }
+        };
+
+        if named_type(&node.field_type) != NODE_INTERFACE || is_list(&node.field_type) {
+            self.report(
+                RelayRule::NodeField,
+                coordinate.clone(),
+                node.position,
+                format!("node should return Node, found {}", node.field_type),
+            );
+        }
+        match node.arguments.iter().find(|arg| arg.name.as_ref() == "id") {
+            Some(id) if id.value_type.to_string() == "ID!" => {}
+            Some(id) => self.report(
+                RelayRule::NodeField,
+                coordinate,
+                id.position,
+                format!("node's id argument should be ID!, found {}", id.value_type),
+            ),
+            None => self.report(
+                RelayRule::NodeField,
+                coordinate,
+                node.position,
+                "node has no id argument".to_string(),
+            ),
+        }
+    }
+
+    /// Check a connection type, returning its edge type if it has one
+    fn connection(
+        &mut self,
+        name: &str,
+        position: Pos,
+        fields: &'c [Field<'a, T>],
+    ) -> Option<&'c str> {
+        let mut edge_type = None;
+
+        match fields.iter().find(|field| field.name.as_ref() == "edges") {
+            Some(edges) if is_list(&edges.field_type) => {
+                let edges_type = named_type(&edges.field_type);
+                match self.schema.get_type(edges_type) {
+                    Some(TypeDefinition::Object(_)) => edge_type = Some(edges_type),
+                    _ => self.report(
+                        RelayRule::Connection,
+                        format!("{}.edges", name),
+                        edges.position,
+                        format!(
+                            "edges should be a list of an object type, found {}",
+                            edges_type
+                        ),
+                    ),
+                }
+            }
+            Some(edges) => self.report(
+                RelayRule::Connection,
+                format!("{}.edges", name),
+                edges.position,
+                format!("edges should be a list, found {}", edges.field_type),
+            ),
+            None => self.report(
+                RelayRule::Connection,
+                name.to_string(),
+                position,
+                format!("{} has no edges field", name),
+            ),
+        }
+
+        match fields
+            .iter()
+            .find(|field| field.name.as_ref() == "pageInfo")
+        {
+            Some(page_info) if page_info.field_type.to_string() == "PageInfo!" => {}
+            Some(page_info) => self.report(
+                RelayRule::Connection,
+                format!("{}.pageInfo", name),
+                page_info.position,
+                format!(
+                    "pageInfo should be PageInfo!, found {}",
+                    page_info.field_type
+                ),
+            ),
+            None => self.report(
+                RelayRule::Connection,
+                name.to_string(),
+                position,
+                format!("{} has no pageInfo field", name),
+            ),
+        }
+
+        edge_type
+    }
+
+    fn edge(&mut self, name: &str) {
+        let (position, fields) = match self.schema.get_type(name) {
+            Some(TypeDefinition::Object(object)) => (object.position, &object.fields),
+            _ => return,
+        };
+
+        match fields.iter().find(|field| field.name.as_ref() == "node") {
+            Some(node) if is_list(&node.field_type) => self.report(
+                RelayRule::Edge,
+                format!("{}.node", name),
+                node.position,
+                format!("node should be a single value, found {}", node.field_type),
+            ),
+            Some(_) => {}
+            None => self.report(
+                RelayRule::Edge,
+                name.to_string(),
+                position,
+                format!("{} has no node field", name),
+            ),
+        }
+
+        match fields.iter().find(|field| field.name.as_ref() == "cursor") {
+            Some(cursor) if self.is_non_null_scalar(&cursor.field_type) => {}
+            Some(cursor) => self.report(
+                RelayRule::Edge,
+                format!("{}.cursor", name),
+                cursor.position,
+                format!(
+                    "cursor should be a non-null scalar, found {}",
+                    cursor.field_type
+                ),
+            ),
+            None => self.report(
+                RelayRule::Edge,
+                name.to_string(),
+                position,
+                format!("{} has no cursor field", name),
+            ),
+        }
+    }
+
+    fn page_info(&mut self) {
+        let (position, fields) = match self.schema.get_type(PAGE_INFO) {
+            Some(TypeDefinition::Object(object)) => (object.position, &object.fields),
+            _ => {
+                return self.report(
+                    RelayRule::PageInfo,
+                    PAGE_INFO.to_string(),
+                    Pos::default(),
+                    "there's no PageInfo object type".to_string(),
+                )
+            }
+        };
+
+        for field_name in &["hasNextPage", "hasPreviousPage", "startCursor", "endCursor"] {
+            let is_cursor = field_name.ends_with("Cursor");
+            match fields
+                .iter()
+                .find(|field| field.name.as_ref() == *field_name)
+            {
+                Some(field) if is_cursor && self.is_scalar(&field.field_type) => {}
+                Some(field) if !is_cursor && field.field_type.to_string() == "Boolean!" => {}
+                Some(field) => self.report(
+                    RelayRule::PageInfo,
+                    format!("{}.{}", PAGE_INFO, field_name),
+                    field.position,
+                    format!(
+                        "{} should be {}, found {}",
+                        field_name,
+                        if is_cursor { "a scalar" } else { "Boolean!" },
+                        field.field_type
+                    ),
+                ),
+                None => self.report(
+                    RelayRule::PageInfo,
+                    PAGE_INFO.to_string(),
+                    position,
+                    format!("PageInfo has no {} field", field_name),
+                ),
+            }
+        }
+    }
+
+    fn pagination_arguments(&mut self, type_name: &str, fields: &[Field<'a, T>]) {
+        for field in fields {
+            let return_type = named_type(&field.field_type);
+            let returns_connection = return_type.ends_with(CONNECTION_SUFFIX)
+                && matches!(
+                    self.schema.get_type(return_type),
+                    Some(TypeDefinition::Object(_))
+                );
+            if !returns_connection {
+                continue;
+            }
+
+            let size = |name: &str| {
+                field.arguments.iter().any(|arg| {
+                    arg.name.as_ref() == name && arg.value_type.to_string().starts_with("Int")
+                })
+            };
+            let cursor = |name: &str| {
+                field
+                    .arguments
+                    .iter()
+                    .any(|arg| arg.name.as_ref() == name && self.is_scalar(&arg.value_type))
+            };
+            let forward = size("first") && cursor("after");
+            let backward = size("last") && cursor("before");
+
+            if !forward && !backward {
+                self.report(
+                    RelayRule::PaginationArguments,
+                    format!("{}.{}", type_name, field.name.as_ref()),
+                    field.position,
+                    format!(
+                        "{} returns a connection but takes neither first: Int and an after \
+                         cursor, nor last: Int and a before cursor",
+                        field.name.as_ref()
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Whether a type is a scalar, nullable or not, counting undeclared built-in scalars
+    fn is_scalar(&self, value_type: &Type<'a, T>) -> bool {
+        let inner = match value_type {
+            Type::NonNullType(inner) => inner.as_ref(),
+            other => other,
+        };
+        match inner {
+            Type::NamedType(name) => match self.schema.get_type(name.as_ref()) {
+                Some(TypeDefinition::Scalar(_)) => true,
+                Some(_) => false,
+                None => BUILT_IN_SCALARS.contains(&name.as_ref()),
+            },
+            _ => false,
+        }
+    }
+
+    fn is_non_null_scalar(&self, value_type: &Type<'a, T>) -> bool {
+        match value_type {
+            Type::NonNullType(inner) => match inner.as_ref() {
+                Type::NamedType(name) => matches!(
+                    self.schema.get_type(name.as_ref()),
+                    Some(TypeDefinition::Scalar(_)) | None
+                ),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const COMPLIANT: &str = r#"
+        scalar Cursor
+
+        interface Node { id: ID! }
+
+        type User implements Node { id: ID! name: String }
+
+        type UserEdge { cursor: Cursor! node: User }
+
+        type UserConnection { edges: [UserEdge] pageInfo: PageInfo! }
+
+        type PageInfo {
+            hasNextPage: Boolean!
+            hasPreviousPage: Boolean!
+            startCursor: Cursor
+            endCursor: String!
+        }
+
+        type Query {
+            node(id: ID!): Node
+            users(first: Int, after: Cursor, last: Int, before: String): UserConnection
+            friends(last: Int!, before: Cursor!): UserConnection!
+        }
+    "#;
+
+    fn findings(sdl: &str) -> Vec<(RelayRule, String)> {
+        let schema = Schema::new(parse_schema::<String>(sdl).unwrap());
+        check(&schema)
+            .into_iter()
+            .map(|finding| (finding.rule, finding.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn compliant_schemas_have_no_findings() {
+        assert_eq!(findings(COMPLIANT), vec![]);
+    }
+
+    #[test]
+    fn reports_connections_and_pagination_arguments() {
+        let sdl = COMPLIANT
+            .replace(
+                "edges: [UserEdge] pageInfo: PageInfo!",
+                "edges: UserEdge pageInfo: PageInfo",
+            )
+            .replace("after: Cursor, last: Int, before: String", "after: User");
+        assert_eq!(
+            findings(&sdl),
+            vec![
+                (
+                    RelayRule::Connection,
+                    "UserConnection.edges: edges should be a list, found UserEdge".to_string()
+                ),
+                (
+                    RelayRule::Connection,
+                    "UserConnection.pageInfo: pageInfo should be PageInfo!, found PageInfo"
+                        .to_string()
+                ),
+                (
+                    RelayRule::PaginationArguments,
+                    "Query.users: users returns a connection but takes neither first: Int and \
+                     an after cursor, nor last: Int and a before cursor"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_edges() {
+        let sdl = COMPLIANT.replace("cursor: Cursor! node: User", "cursor: Cursor node: [User]");
+        assert_eq!(
+            findings(&sdl),
+            vec![
+                (
+                    RelayRule::Edge,
+                    "UserEdge.node: node should be a single value, found [User]".to_string()
+                ),
+                (
+                    RelayRule::Edge,
+                    "UserEdge.cursor: cursor should be a non-null scalar, found Cursor".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_page_info() {
+        let sdl = COMPLIANT
+            .replace("hasNextPage: Boolean!", "hasNextPage: Boolean")
+            .replace("startCursor: Cursor", "startCursor: [Cursor]")
+            .replace("endCursor: String!", "");
+        assert_eq!(
+            findings(&sdl),
+            vec![
+                (
+                    RelayRule::PageInfo,
+                    "PageInfo.hasNextPage: hasNextPage should be Boolean!, found Boolean"
+                        .to_string()
+                ),
+                (
+                    RelayRule::PageInfo,
+                    "PageInfo.startCursor: startCursor should be a scalar, found [Cursor]"
+                        .to_string()
+                ),
+                (
+                    RelayRule::PageInfo,
+                    "PageInfo: PageInfo has no endCursor field".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,636 @@
+use graphql_parser::query::{self, FragmentDefinition, OperationDefinition, SelectionSet};
+use graphql_parser::schema::{
+    Definition, Directive, DirectiveDefinition, Document, EnumType, EnumTypeExtension, EnumValue,
+    Field, InputObjectType, InputObjectTypeExtension, InputValue, InterfaceType,
+    InterfaceTypeExtension, ObjectType, ObjectTypeExtension, ScalarType, ScalarTypeExtension,
+    SchemaDefinition, Text, Type, TypeDefinition, TypeExtension, UnionType, UnionTypeExtension,
+    Value,
+};
+use graphql_parser::Pos;
+use serde_json::{self as json, Map};
+use std::collections::HashMap;
+
+/// The scalars every schema has, which SDL may leave undeclared
+pub const BUILT_IN_SCALARS: &[&str] = &["String", "Int", "Float", "Boolean", "ID"];
+/// The meta field every object, interface and union can select
+pub const TYPENAME_FIELD: &str = "__typename";
+
+/// A parsed `Document` indexed by type name so the rest of the crate can resolve named types
+/// and root operation types without scanning every definition.
+#[derive(Debug, Clone)]
+pub struct Schema<'a, T: Text<'a>> {
+    document: Document<'a, T>,
+    types: HashMap<String, usize>,
+    schema_definition: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl OperationKind {
+    /// The name the root type has when the schema definition is left out
+    pub fn default_type_name(self) -> &'static str {
+        match self {
+            OperationKind::Query => "Query",
+            OperationKind::Mutation => "Mutation",
+            OperationKind::Subscription => "Subscription",
+        }
+    }
+}
+
+impl<'a, T: Text<'a>> Schema<'a, T> {
+    pub fn new(document: Document<'a, T>) -> Self {
+        let mut types = HashMap::new();
+        let mut schema_definition = None;
+
+        for (index, definition) in document.definitions.iter().enumerate() {
+            match definition {
+                Definition::TypeDefinition(type_definition) => {
+                    types.insert(type_definition_name(type_definition).to_string(), index);
+                }
+                Definition::SchemaDefinition(_) => schema_definition = Some(index),
+                _ => (),
+            }
+        }
+
+        Schema {
+            document,
+            types,
+            schema_definition,
+        }
+    }
+
+    pub fn document(&self) -> &Document<'a, T> {
+        &self.document
+    }
+
+    pub fn into_document(self) -> Document<'a, T> {
+        self.document
+    }
+
+    pub fn schema_definition(&self) -> Option<&SchemaDefinition<'a, T>> {
+        self.schema_definition
+            .and_then(|index| match &self.document.definitions[index] {
+                Definition::SchemaDefinition(schema_definition) => Some(schema_definition),
+                _ => None,
+            })
+    }
+
+    /// All the type definitions in the order the server reported them
+    pub fn types(&self) -> impl Iterator<Item = &TypeDefinition<'a, T>> {
+        self.document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::TypeDefinition(type_definition) => Some(type_definition),
+                _ => None,
+            })
+    }
+
+    pub fn get_type(&self, name: &str) -> Option<&TypeDefinition<'a, T>> {
+        self.types
+            .get(name)
+            .and_then(|index| match &self.document.definitions[*index] {
+                Definition::TypeDefinition(type_definition) => Some(type_definition),
+                _ => None,
+            })
+    }
+
+    /// The root type for `kind`, falling back to a type named `Query`, `Mutation` or
+    /// `Subscription` when there's no schema definition, as SDL may leave it out
+    pub fn root_type_name(&self, kind: OperationKind) -> Option<&str> {
+        match self.schema_definition() {
+            Some(schema) => match kind {
+                OperationKind::Query => &schema.query,
+                OperationKind::Mutation => &schema.mutation,
+                OperationKind::Subscription => &schema.subscription,
+            }
+            .as_ref()
+            .map(AsRef::as_ref),
+            None => Some(kind.default_type_name()).filter(|name| self.types.contains_key(*name)),
+        }
+    }
+
+    pub fn root_type(&self, kind: OperationKind) -> Option<&ObjectType<'a, T>> {
+        self.root_type_name(kind)
+            .and_then(|name| self.get_type(name))
+            .and_then(|type_definition| match type_definition {
+                TypeDefinition::Object(object) => Some(object),
+                _ => None,
+            })
+    }
+
+    /// The fields of an object or interface, empty for every other kind
+    pub fn fields(&self, type_name: &str) -> &[Field<'a, T>] {
+        match self.get_type(type_name) {
+            Some(TypeDefinition::Object(object)) => &object.fields,
+            Some(TypeDefinition::Interface(interface)) => &interface.fields,
+            _ => &[],
+        }
+    }
+
+    pub fn field(&self, type_name: &str, field_name: &str) -> Option<&Field<'a, T>> {
+        self.fields(type_name)
+            .iter()
+            .find(|field| field.name.as_ref() == field_name)
+    }
+
+    /// The fields of an input object, empty for every other kind
+    pub fn input_fields(&self, type_name: &str) -> &[InputValue<'a, T>] {
+        match self.get_type(type_name) {
+            Some(TypeDefinition::InputObject(input_object)) => &input_object.fields,
+            _ => &[],
+        }
+    }
+
+    /// The object types a value of `type_name` can resolve to at runtime
+    pub fn possible_types(&self, type_name: &str) -> Vec<&str> {
+        match self.get_type(type_name) {
+            Some(TypeDefinition::Object(object)) => vec![object.name.as_ref()],
+            Some(TypeDefinition::Union(union)) => union.types.iter().map(AsRef::as_ref).collect(),
+            Some(TypeDefinition::Interface(interface)) => self
+                .types()
+                .filter_map(|type_definition| match type_definition {
+                    TypeDefinition::Object(object)
+                        if object.implements_interfaces.contains(&interface.name) =>
+                    {
+                        Some(object.name.as_ref())
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn is_abstract(&self, type_name: &str) -> bool {
+        matches!(
+            self.get_type(type_name),
+            Some(TypeDefinition::Interface(_)) | Some(TypeDefinition::Union(_))
+        )
+    }
+
+    /// Whether `type_name` is a scalar or enum, counting built-in scalars SDL left undeclared
+    pub fn is_leaf(&self, type_name: &str) -> bool {
+        match self.get_type(type_name) {
+            Some(TypeDefinition::Scalar(_)) | Some(TypeDefinition::Enum(_)) => true,
+            Some(_) => false,
+            None => BUILT_IN_SCALARS.contains(&type_name),
+        }
+    }
+}
+
+impl<'a, T: Text<'a>> From<Document<'a, T>> for Schema<'a, T> {
+    fn from(document: Document<'a, T>) -> Self {
+        Schema::new(document)
+    }
+}
+
+pub fn type_definition_name<'s, 'a, T: Text<'a>>(
+    type_definition: &'s TypeDefinition<'a, T>,
+) -> &'s str {
+    match type_definition {
+        TypeDefinition::Scalar(scalar) => scalar.name.as_ref(),
+        TypeDefinition::Object(object) => object.name.as_ref(),
+        TypeDefinition::Interface(interface) => interface.name.as_ref(),
+        TypeDefinition::Union(union) => union.name.as_ref(),
+        TypeDefinition::Enum(enum_type) => enum_type.name.as_ref(),
+        TypeDefinition::InputObject(input_object) => input_object.name.as_ref(),
+    }
+}
+
+pub fn type_description<'s, 'a, T: Text<'a>>(
+    type_definition: &'s TypeDefinition<'a, T>,
+) -> &'s Option<String> {
+    match type_definition {
+        TypeDefinition::Scalar(scalar) => &scalar.description,
+        TypeDefinition::Object(object) => &object.description,
+        TypeDefinition::Interface(interface) => &interface.description,
+        TypeDefinition::Union(union) => &union.description,
+        TypeDefinition::Enum(enum_type) => &enum_type.description,
+        TypeDefinition::InputObject(input_object) => &input_object.description,
+    }
+}
+
+/// The default value of an argument or input field as GraphQL, ie: `10` or `{a: [1, 2]}`
+pub fn default_value_source<'a, T: Text<'a>>(input_value: &InputValue<'a, T>) -> Option<String> {
+    input_value.default_value.as_ref().map(ToString::to_string)
+}
+
+/// What kind of type a definition is, ie: `object` or `input object`
+pub fn kind_name<'a, T: Text<'a>>(type_definition: &TypeDefinition<'a, T>) -> &'static str {
+    match type_definition {
+        TypeDefinition::Scalar(_) => "scalar",
+        TypeDefinition::Object(_) => "object",
+        TypeDefinition::Interface(_) => "interface",
+        TypeDefinition::Union(_) => "union",
+        TypeDefinition::Enum(_) => "enum",
+        TypeDefinition::InputObject(_) => "input object",
+    }
+}
+
+/// The name at the bottom of any list and non-null wrappers
+pub fn named_type<'s, 'a, T: Text<'a>>(value_type: &'s Type<'a, T>) -> &'s str {
+    match value_type {
+        Type::NamedType(name) => name.as_ref(),
+        Type::ListType(inner) | Type::NonNullType(inner) => named_type(inner),
+    }
+}
+
+/// Whether a type is a list, under any non-null wrapper
+pub fn is_list<'a, T: Text<'a>>(value_type: &Type<'a, T>) -> bool {
+    match value_type {
+        Type::ListType(_) => true,
+        Type::NonNullType(inner) => is_list(inner),
+        Type::NamedType(_) => false,
+    }
+}
+
+/// How a field or enum value is deprecated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deprecation<'s> {
+    pub reason: Option<&'s str>,
+}
+
+/// Find the `@deprecated` directive of a field or enum value, if it has one
+pub fn deprecation<'s, 'a, T: Text<'a>>(
+    directives: &'s [Directive<'a, T>],
+) -> Option<Deprecation<'s>> {
+    directives
+        .iter()
+        .find(|directive| directive.name.as_ref() == "deprecated")
+        .map(|directive| Deprecation {
+            reason: directive
+                .arguments
+                .iter()
+                .find(|(name, _)| name.as_ref() == "reason")
+                .and_then(|(_, value)| match value {
+                    Value::String(reason) => Some(reason.as_str()),
+                    _ => None,
+                }),
+        })
+}
+
+/// The parts every kind of operation has
+#[derive(Debug, Clone, Copy)]
+pub struct OperationParts<'o, 'q, Q: Text<'q>> {
+    pub kind: OperationKind,
+    pub name: Option<&'o str>,
+    pub position: Pos,
+    pub variable_definitions: &'o [query::VariableDefinition<'q, Q>],
+    pub directives: &'o [query::Directive<'q, Q>],
+    pub selection_set: &'o SelectionSet<'q, Q>,
+}
+
+/// Split an operation into its parts, treating the `{ ... }` shorthand as an anonymous query
+/// without variables or directives
+pub fn operation_parts<'o, 'q, Q: Text<'q>>(
+    operation: &'o OperationDefinition<'q, Q>,
+) -> OperationParts<'o, 'q, Q> {
+    match operation {
+        OperationDefinition::SelectionSet(selection_set) => OperationParts {
+            kind: OperationKind::Query,
+            name: None,
+            position: selection_set.span.0,
+            variable_definitions: &[],
+            directives: &[],
+            selection_set,
+        },
+        OperationDefinition::Query(query) => OperationParts {
+            kind: OperationKind::Query,
+            name: query.name.as_ref().map(AsRef::as_ref),
+            position: query.position,
+            variable_definitions: &query.variable_definitions,
+            directives: &query.directives,
+            selection_set: &query.selection_set,
+        },
+        OperationDefinition::Mutation(mutation) => OperationParts {
+            kind: OperationKind::Mutation,
+            name: mutation.name.as_ref().map(AsRef::as_ref),
+            position: mutation.position,
+            variable_definitions: &mutation.variable_definitions,
+            directives: &mutation.directives,
+            selection_set: &mutation.selection_set,
+        },
+        OperationDefinition::Subscription(subscription) => OperationParts {
+            kind: OperationKind::Subscription,
+            name: subscription.name.as_ref().map(AsRef::as_ref),
+            position: subscription.position,
+            variable_definitions: &subscription.variable_definitions,
+            directives: &subscription.directives,
+            selection_set: &subscription.selection_set,
+        },
+    }
+}
+
+/// The fragment definitions among `definitions`, by name
+pub fn fragments<'d, 'q: 'd, Q: Text<'q>>(
+    definitions: impl IntoIterator<Item = &'d query::Definition<'q, Q>>,
+) -> HashMap<&'d str, &'d FragmentDefinition<'q, Q>> {
+    definitions
+        .into_iter()
+        .filter_map(|definition| match definition {
+            query::Definition::Fragment(fragment) => Some((fragment.name.as_ref(), fragment)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether `@skip(if:)` and `@include(if:)` keep a selection, reading variables from
+/// `variables`
+pub fn is_included<'q, Q: Text<'q>>(
+    directives: &[query::Directive<'q, Q>],
+    variables: &Map<String, json::Value>,
+) -> bool {
+    directives.iter().all(|directive| {
+        let condition = directive
+            .arguments
+            .iter()
+            .find(|(name, _)| name.as_ref() == "if")
+            .map(|(_, value)| match value {
+                Value::Boolean(condition) => *condition,
+                Value::Variable(name) => variables
+                    .get(name.as_ref())
+                    .and_then(json::Value::as_bool)
+                    .unwrap_or(false),
+                _ => false,
+            });
+
+        match (directive.name.as_ref(), condition) {
+            ("skip", Some(condition)) => !condition,
+            ("include", Some(condition)) => condition,
+            _ => true,
+        }
+    })
+}
+
+/// Copy a document so it owns all of its names, keeping every directive and position
+pub fn to_owned_document<'a, T: Text<'a>>(document: &Document<'a, T>) -> Document<'static, String> {
+    Document {
+        definitions: document
+            .definitions
+            .iter()
+            .map(|definition| match definition {
+                Definition::SchemaDefinition(schema_definition) => {
+                    Definition::SchemaDefinition(SchemaDefinition {
+                        position: schema_definition.position,
+                        directives: to_owned_directives(&schema_definition.directives),
+                        query: schema_definition.query.as_ref().map(owned),
+                        mutation: schema_definition.mutation.as_ref().map(owned),
+                        subscription: schema_definition.subscription.as_ref().map(owned),
+                    })
+                }
+                Definition::TypeDefinition(type_definition) => {
+                    Definition::TypeDefinition(to_owned_type_definition(type_definition))
+                }
+                Definition::TypeExtension(type_extension) => {
+                    Definition::TypeExtension(to_owned_type_extension(type_extension))
+                }
+                Definition::DirectiveDefinition(directive) => {
+                    Definition::DirectiveDefinition(DirectiveDefinition {
+                        position: directive.position,
+                        description: directive.description.clone(),
+                        name: owned(&directive.name),
+                        arguments: to_owned_input_values(&directive.arguments),
+                        repeatable: directive.repeatable,
+                        locations: directive.locations.clone(),
+                    })
+                }
+            })
+            .collect(),
+    }
+}
+
+fn to_owned_type_definition<'a, T: Text<'a>>(
+    type_definition: &TypeDefinition<'a, T>,
+) -> TypeDefinition<'static, String> {
+    match type_definition {
+        TypeDefinition::Scalar(scalar) => TypeDefinition::Scalar(ScalarType {
+            position: scalar.position,
+            description: scalar.description.clone(),
+            name: owned(&scalar.name),
+            directives: to_owned_directives(&scalar.directives),
+        }),
+        TypeDefinition::Object(object) => TypeDefinition::Object(ObjectType {
+            position: object.position,
+            description: object.description.clone(),
+            name: owned(&object.name),
+            implements_interfaces: object.implements_interfaces.iter().map(owned).collect(),
+            directives: to_owned_directives(&object.directives),
+            fields: to_owned_fields(&object.fields),
+        }),
+        TypeDefinition::Interface(interface) => TypeDefinition::Interface(InterfaceType {
+            position: interface.position,
+            description: interface.description.clone(),
+            name: owned(&interface.name),
+            implements_interfaces: interface.implements_interfaces.iter().map(owned).collect(),
+            directives: to_owned_directives(&interface.directives),
+            fields: to_owned_fields(&interface.fields),
+        }),
+        TypeDefinition::Union(union) => TypeDefinition::Union(UnionType {
+            position: union.position,
+            description: union.description.clone(),
+            name: owned(&union.name),
+            directives: to_owned_directives(&union.directives),
+            types: union.types.iter().map(owned).collect(),
+        }),
+        TypeDefinition::Enum(enum_type) => TypeDefinition::Enum(EnumType {
+            position: enum_type.position,
+            description: enum_type.description.clone(),
+            name: owned(&enum_type.name),
+            directives: to_owned_directives(&enum_type.directives),
+            values: to_owned_enum_values(&enum_type.values),
+        }),
+        TypeDefinition::InputObject(input_object) => TypeDefinition::InputObject(InputObjectType {
+            position: input_object.position,
+            description: input_object.description.clone(),
+            name: owned(&input_object.name),
+            directives: to_owned_directives(&input_object.directives),
+            fields: to_owned_input_values(&input_object.fields),
+        }),
+    }
+}
+
+fn to_owned_type_extension<'a, T: Text<'a>>(
+    type_extension: &TypeExtension<'a, T>,
+) -> TypeExtension<'static, String> {
+    match type_extension {
+        TypeExtension::Scalar(scalar) => TypeExtension::Scalar(ScalarTypeExtension {
+            position: scalar.position,
+            name: owned(&scalar.name),
+            directives: to_owned_directives(&scalar.directives),
+        }),
+        TypeExtension::Object(object) => TypeExtension::Object(ObjectTypeExtension {
+            position: object.position,
+            name: owned(&object.name),
+            implements_interfaces: object.implements_interfaces.iter().map(owned).collect(),
+            directives: to_owned_directives(&object.directives),
+            fields: to_owned_fields(&object.fields),
+        }),
+        TypeExtension::Interface(interface) => TypeExtension::Interface(InterfaceTypeExtension {
+            position: interface.position,
+            name: owned(&interface.name),
+            implements_interfaces: interface.implements_interfaces.iter().map(owned).collect(),
+            directives: to_owned_directives(&interface.directives),
+            fields: to_owned_fields(&interface.fields),
+        }),
+        TypeExtension::Union(union) => TypeExtension::Union(UnionTypeExtension {
+            position: union.position,
+            name: owned(&union.name),
+            directives: to_owned_directives(&union.directives),
+            types: union.types.iter().map(owned).collect(),
+        }),
+        TypeExtension::Enum(enum_type) => TypeExtension::Enum(EnumTypeExtension {
+            position: enum_type.position,
+            name: owned(&enum_type.name),
+            directives: to_owned_directives(&enum_type.directives),
+            values: to_owned_enum_values(&enum_type.values),
+        }),
+        TypeExtension::InputObject(input_object) => {
+            TypeExtension::InputObject(InputObjectTypeExtension {
+                position: input_object.position,
+                name: owned(&input_object.name),
+                directives: to_owned_directives(&input_object.directives),
+                fields: to_owned_input_values(&input_object.fields),
+            })
+        }
+    }
+}
+
+fn to_owned_fields<'a, T: Text<'a>>(fields: &[Field<'a, T>]) -> Vec<Field<'static, String>> {
+    fields
+        .iter()
+        .map(|field| Field {
+            position: field.position,
+            description: field.description.clone(),
+            name: owned(&field.name),
+            arguments: to_owned_input_values(&field.arguments),
+            field_type: to_owned_type(&field.field_type),
+            directives: to_owned_directives(&field.directives),
+        })
+        .collect()
+}
+
+fn to_owned_input_values<'a, T: Text<'a>>(
+    input_values: &[InputValue<'a, T>],
+) -> Vec<InputValue<'static, String>> {
+    input_values
+        .iter()
+        .map(|input_value| InputValue {
+            position: input_value.position,
+            description: input_value.description.clone(),
+            name: owned(&input_value.name),
+            value_type: to_owned_type(&input_value.value_type),
+            default_value: input_value.default_value.as_ref().map(to_owned_value),
+            directives: to_owned_directives(&input_value.directives),
+        })
+        .collect()
+}
+
+fn to_owned_enum_values<'a, T: Text<'a>>(
+    values: &[EnumValue<'a, T>],
+) -> Vec<EnumValue<'static, String>> {
+    values
+        .iter()
+        .map(|value| EnumValue {
+            position: value.position,
+            description: value.description.clone(),
+            name: owned(&value.name),
+            directives: to_owned_directives(&value.directives),
+        })
+        .collect()
+}
+
+fn to_owned_directives<'a, T: Text<'a>>(
+    directives: &[Directive<'a, T>],
+) -> Vec<Directive<'static, String>> {
+    directives
+        .iter()
+        .map(|directive| Directive {
+            position: directive.position,
+            name: owned(&directive.name),
+            arguments: directive
+                .arguments
+                .iter()
+                .map(|(name, value)| (owned(name), to_owned_value(value)))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Copy a type so it owns its names, ie: to use a schema's argument type in a generated query
+pub fn to_owned_type<'a, T: Text<'a>>(value_type: &Type<'a, T>) -> Type<'static, String> {
+    match value_type {
+        Type::NamedType(name) => Type::NamedType(owned(name)),
+        Type::ListType(inner) => Type::ListType(Box::new(to_owned_type(inner))),
+        Type::NonNullType(inner) => Type::NonNullType(Box::new(to_owned_type(inner))),
+    }
+}
+
+pub fn to_owned_value<'a, T: Text<'a>>(value: &Value<'a, T>) -> Value<'static, String> {
+    match value {
+        Value::Variable(name) => Value::Variable(owned(name)),
+        Value::Int(number) => Value::Int(number.clone()),
+        Value::Float(float) => Value::Float(*float),
+        Value::String(string) => Value::String(string.clone()),
+        Value::Boolean(boolean) => Value::Boolean(*boolean),
+        Value::Null => Value::Null,
+        Value::Enum(name) => Value::Enum(owned(name)),
+        Value::List(values) => Value::List(values.iter().map(to_owned_value).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (owned(name), to_owned_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Copy a name out of any `Text` value
+pub(crate) fn owned<V: AsRef<str>>(name: &V) -> String {
+    name.as_ref().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    #[test]
+    fn root_types_fall_back_to_their_default_names() {
+        let schema = Schema::new(
+            parse_schema::<String>("type Query { a: Int } type Subscription { b: Int }").unwrap(),
+        );
+        assert_eq!(schema.root_type_name(OperationKind::Query), Some("Query"));
+        assert_eq!(schema.root_type_name(OperationKind::Mutation), None);
+        assert_eq!(
+            schema.root_type_name(OperationKind::Subscription),
+            Some("Subscription")
+        );
+
+        // an explicit schema definition wins, even over types with the default names
+        let schema = Schema::new(
+            parse_schema::<String>(
+                "schema { query: Root } type Root { a: Int } type Mutation { b: Int }",
+            )
+            .unwrap(),
+        );
+        assert_eq!(schema.root_type_name(OperationKind::Query), Some("Root"));
+        assert_eq!(schema.root_type_name(OperationKind::Mutation), None);
+    }
+
+    #[test]
+    fn leaf_types_include_undeclared_built_in_scalars() {
+        let schema = Schema::new(
+            parse_schema::<String>("type Query { a: Int role: Role } enum Role { A }").unwrap(),
+        );
+        assert!(schema.is_leaf("Int"));
+        assert!(schema.is_leaf("Role"));
+        assert!(!schema.is_leaf("Query"));
+        assert!(!schema.is_leaf("Missing"));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::introspection::to_introspection;
+use crate::mock::{self, MockOptions, MockValue};
+use crate::schema::Schema;
+use graphql_parser::schema::Text;
+use serde::Deserialize;
+use serde_json::{self as json, json, Map};
+use std::error::Error;
+use std::fmt;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+#[derive(Deserialize)]
+struct GraphQLRequest {
+    query: String,
+    #[serde(rename = "operationName")]
+    operation_name: Option<String>,
+    variables: Option<Map<String, json::Value>>,
+}
+
+/// Serve mocked responses for every operation POSTed to `127.0.0.1:{port}`, on any path.
+///
+/// Introspection queries are answered from the schema itself, so tools like GraphiQL can point
+/// at the server as though it were the real thing. This blocks for as long as the server runs.
+pub fn serve<'a, T: Text<'a>>(
+    schema: &Schema<'a, T>,
+    port: u16,
+    options: &MockOptions,
+) -> Result<(), Box<dyn Error>> {
+    let server = Server::http(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+        .map_err(|e| format!("couldn't listen on port {}: {}", port, e))?;
+    // every introspection query is answered from the same JSON
+    let options = MockOptions {
+        introspection: Some(Arc::new(to_introspection(schema.document()))),
+        ..options.clone()
+    };
+
+    for request in server.incoming_requests() {
+        if let Err(e) = handle_request(schema, &options, request) {
+            log::warn!("Failed to respond to a request: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_request<'a, T: Text<'a>>(
+    schema: &Schema<'a, T>,
+    options: &MockOptions,
+    mut request: Request,
+) -> std::io::Result<()> {
+    let response = match request.method() {
+        Method::Post => {
+            let mut body = String::new();
+            let (status, result) = match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => respond(schema, &body, options),
+                Err(e) => (400, error_response(&e)),
+            };
+
+            let body = json::to_string(&result).expect("responses have string keys");
+            Response::from_string(body)
+                .with_status_code(status)
+                .with_header(header("Content-Type", "application/json"))
+        }
+        // CORS preflight, so frontends on another localhost port can call us
+        Method::Options => Response::from_string("").with_status_code(204),
+        _ => Response::from_string("only POST is supported")
+            .with_status_code(405)
+            .with_header(header("Allow", "POST, OPTIONS")),
+    };
+
+    request.respond(
+        response
+            .with_header(header("Access-Control-Allow-Origin", "*"))
+            .with_header(header("Access-Control-Allow-Methods", "POST, OPTIONS"))
+            .with_header(header("Access-Control-Allow-Headers", "Content-Type")),
+    )
+}
+
+/// Answer the body of a GraphQL POST, returning the HTTP status and the JSON response
+pub fn respond<'a, T: Text<'a>>(
+    schema: &Schema<'a, T>,
+    body: &str,
+    options: &MockOptions,
+) -> (u16, MockValue) {
+    let request: GraphQLRequest = match json::from_str(body) {
+        Ok(request) => request,
+        Err(e) => return (400, error_response(&e)),
+    };
+    let document = match graphql_parser::parse_query::<&str>(&request.query) {
+        Ok(document) => document,
+        Err(e) => return (400, error_response(&e)),
+    };
+
+    let options = MockOptions {
+        operation_name: request.operation_name,
+        variables: request.variables.unwrap_or_default(),
+        ..options.clone()
+    };
+
+    match mock::mock_with_options(schema, &document, &options) {
+        Ok(response) => (200, response),
+        Err(e) => (400, error_response(&e)),
+    }
+}
+
+fn error_response(error: &dyn fmt::Display) -> MockValue {
+    json!({ "errors": [{ "message": error.to_string() }] }).into()
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).expect("static headers are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    const SDL: &str = r#"
+        type Query { user(id: ID!): User }
+        type User { id: ID! name: String! role: Role! }
+        enum Role { ADMIN USER }
+    "#;
+
+    fn post(body: json::Value) -> (u16, json::Value) {
+        let schema = Schema::new(parse_schema::<String>(SDL).unwrap());
+        let (status, response) = respond(&schema, &body.to_string(), &MockOptions::default());
+        (status, response.into())
+    }
+
+    #[test]
+    fn answers_introspection_from_the_schema() {
+        let (status, response) = post(json!({
+            "query": "query Names { __schema { queryType { name } } __type(name: \"Role\") { enumValues { name } } }",
+        }));
+
+        assert_eq!(status, 200);
+        assert_eq!(
+            response,
+            json!({ "data": {
+                "__schema": { "queryType": { "name": "Query" } },
+                "__type": { "enumValues": [{ "name": "ADMIN" }, { "name": "USER" }] },
+            } })
+        );
+    }
+
+    #[test]
+    fn mocks_the_requested_operation() {
+        let (status, response) = post(json!({
+            "query": "query A { user(id: $id) { id } } query B { user(id: $id) { name role } }",
+            "operationName": "B",
+            "variables": { "id": "1" },
+        }));
+
+        assert_eq!(status, 200);
+        let user = response["data"]["user"].as_object().unwrap();
+        assert_eq!(user.keys().collect::<Vec<_>>(), vec!["name", "role"]);
+        assert!(user["name"].is_string());
+        assert!(["ADMIN", "USER"].contains(&user["role"].as_str().unwrap()));
+    }
+
+    #[test]
+    fn reports_bad_requests() {
+        let (status, response) = post(json!({ "query": "{ user(id: 1) {" }));
+        assert_eq!(status, 400);
+        assert!(response["errors"][0]["message"].is_string());
+
+        let (status, _) =
+            post(json!({ "query": "query A { user { id } } query B { user { id } }" }));
+        assert_eq!(status, 400);
+
+        let schema = Schema::new(parse_schema::<String>(SDL).unwrap());
+        let (status, _) = respond(&schema, "not json", &MockOptions::default());
+        assert_eq!(status, 400);
+    }
+}
@@ -0,0 +1,606 @@
+use crate::schema::{deprecation, owned, Schema};
+use crate::{deprecation_directives, parse_default_value, CowStr};
+use graphql_parser::schema::{
+    Definition, Directive, DirectiveDefinition, Document, EnumType, EnumValue, Field,
+    InputObjectType, InputValue, InterfaceType, ObjectType, ScalarType, SchemaDefinition, Text,
+    Type, TypeDefinition, UnionType, Value,
+};
+use graphql_parser::Pos;
+use serde::de::value::Error as ValueError;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+/// A parsed schema in a shape serde can store in any format, so it can be written once as
+/// bincode or CBOR and loaded at startup without parsing the introspection JSON again. A build
+/// script can write the snapshot and the binary can embed it with `include_bytes!`.
+///
+/// Source positions aren't kept, and neither are type extensions since introspection never
+/// produces them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaSnapshot {
+    pub definitions: Vec<DefinitionSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DefinitionSnapshot {
+    Schema {
+        query: Option<String>,
+        mutation: Option<String>,
+        subscription: Option<String>,
+    },
+    Scalar {
+        name: String,
+        description: Option<String>,
+    },
+    Object {
+        name: String,
+        description: Option<String>,
+        interfaces: Vec<String>,
+        fields: Vec<FieldSnapshot>,
+    },
+    Interface {
+        name: String,
+        description: Option<String>,
+        interfaces: Vec<String>,
+        fields: Vec<FieldSnapshot>,
+    },
+    Union {
+        name: String,
+        description: Option<String>,
+        types: Vec<String>,
+    },
+    Enum {
+        name: String,
+        description: Option<String>,
+        values: Vec<EnumValueSnapshot>,
+    },
+    InputObject {
+        name: String,
+        description: Option<String>,
+        fields: Vec<InputValueSnapshot>,
+    },
+    Directive {
+        name: String,
+        description: Option<String>,
+        arguments: Vec<InputValueSnapshot>,
+        repeatable: bool,
+        /// As written in SDL, ie: `FIELD_DEFINITION`
+        locations: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldSnapshot {
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<InputValueSnapshot>,
+    pub field_type: TypeSnapshot,
+    pub deprecation: Option<DeprecationSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputValueSnapshot {
+    pub name: String,
+    pub description: Option<String>,
+    pub value_type: TypeSnapshot,
+    pub default_value: Option<ValueSnapshot>,
+    pub deprecation: Option<DeprecationSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumValueSnapshot {
+    pub name: String,
+    pub description: Option<String>,
+    pub deprecation: Option<DeprecationSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeprecationSnapshot {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TypeSnapshot {
+    Named(String),
+    List(Box<TypeSnapshot>),
+    NonNull(Box<TypeSnapshot>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValueSnapshot {
+    Variable(String),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Boolean(bool),
+    Null,
+    Enum(String),
+    List(Vec<ValueSnapshot>),
+    Object(Vec<(String, ValueSnapshot)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    /// A directive location that isn't in the GraphQL spec, only possible if the snapshot was
+    /// written by something else or edited
+    UnknownDirectiveLocation { directive: String, location: String },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::UnknownDirectiveLocation {
+                directive,
+                location,
+            } => write!(
+                f,
+                "unknown location '{}' on directive '@{}'",
+                location, directive
+            ),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl SchemaSnapshot {
+    pub fn new<'a, T: Text<'a>>(document: &Document<'a, T>) -> Self {
+        SchemaSnapshot {
+            definitions: document
+                .definitions
+                .iter()
+                .filter_map(|definition| match definition {
+                    Definition::SchemaDefinition(schema) => Some(DefinitionSnapshot::Schema {
+                        query: schema.query.as_ref().map(owned),
+                        mutation: schema.mutation.as_ref().map(owned),
+                        subscription: schema.subscription.as_ref().map(owned),
+                    }),
+                    Definition::TypeDefinition(type_definition) => {
+                        Some(type_definition_snapshot(type_definition))
+                    }
+                    Definition::DirectiveDefinition(directive) => {
+                        Some(DefinitionSnapshot::Directive {
+                            name: owned(&directive.name),
+                            description: directive.description.clone(),
+                            arguments: directive
+                                .arguments
+                                .iter()
+                                .map(input_value_snapshot)
+                                .collect(),
+                            repeatable: directive.repeatable,
+                            locations: directive
+                                .locations
+                                .iter()
+                                .map(|location| location.as_str().to_string())
+                                .collect(),
+                        })
+                    }
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn into_document(self) -> Result<Document<'static, String>, SnapshotError> {
+        Ok(Document {
+            definitions: self
+                .definitions
+                .into_iter()
+                .map(DefinitionSnapshot::into_definition)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl<'a, T: Text<'a>> From<&Schema<'a, T>> for SchemaSnapshot {
+    fn from(schema: &Schema<'a, T>) -> Self {
+        SchemaSnapshot::new(schema.document())
+    }
+}
+
+impl TryFrom<SchemaSnapshot> for Schema<'static, String> {
+    type Error = SnapshotError;
+
+    fn try_from(snapshot: SchemaSnapshot) -> Result<Self, Self::Error> {
+        Ok(Schema::new(snapshot.into_document()?))
+    }
+}
+
+fn type_definition_snapshot<'a, T: Text<'a>>(
+    type_definition: &TypeDefinition<'a, T>,
+) -> DefinitionSnapshot {
+    match type_definition {
+        TypeDefinition::Scalar(scalar) => DefinitionSnapshot::Scalar {
+            name: owned(&scalar.name),
+            description: scalar.description.clone(),
+        },
+        TypeDefinition::Object(object) => DefinitionSnapshot::Object {
+            name: owned(&object.name),
+            description: object.description.clone(),
+            interfaces: object.implements_interfaces.iter().map(owned).collect(),
+            fields: object.fields.iter().map(field_snapshot).collect(),
+        },
+        TypeDefinition::Interface(interface) => DefinitionSnapshot::Interface {
+            name: owned(&interface.name),
+            description: interface.description.clone(),
+            interfaces: interface.implements_interfaces.iter().map(owned).collect(),
+            fields: interface.fields.iter().map(field_snapshot).collect(),
+        },
+        TypeDefinition::Union(union) => DefinitionSnapshot::Union {
+            name: owned(&union.name),
+            description: union.description.clone(),
+            types: union.types.iter().map(owned).collect(),
+        },
+        TypeDefinition::Enum(enum_type) => DefinitionSnapshot::Enum {
+            name: owned(&enum_type.name),
+            description: enum_type.description.clone(),
+            values: enum_type
+                .values
+                .iter()
+                .map(|value| EnumValueSnapshot {
+                    name: owned(&value.name),
+                    description: value.description.clone(),
+                    deprecation: DeprecationSnapshot::new(&value.directives),
+                })
+                .collect(),
+        },
+        TypeDefinition::InputObject(input_object) => DefinitionSnapshot::InputObject {
+            name: owned(&input_object.name),
+            description: input_object.description.clone(),
+            fields: input_object
+                .fields
+                .iter()
+                .map(input_value_snapshot)
+                .collect(),
+        },
+    }
+}
+
+fn field_snapshot<'a, T: Text<'a>>(field: &Field<'a, T>) -> FieldSnapshot {
+    FieldSnapshot {
+        name: owned(&field.name),
+        description: field.description.clone(),
+        arguments: field.arguments.iter().map(input_value_snapshot).collect(),
+        field_type: TypeSnapshot::new(&field.field_type),
+        deprecation: DeprecationSnapshot::new(&field.directives),
+    }
+}
+
+fn input_value_snapshot<'a, T: Text<'a>>(input_value: &InputValue<'a, T>) -> InputValueSnapshot {
+    InputValueSnapshot {
+        name: owned(&input_value.name),
+        description: input_value.description.clone(),
+        value_type: TypeSnapshot::new(&input_value.value_type),
+        default_value: input_value.default_value.as_ref().map(ValueSnapshot::new),
+        deprecation: DeprecationSnapshot::new(&input_value.directives),
+    }
+}
+
+impl DefinitionSnapshot {
+    fn into_definition(self) -> Result<Definition<'static, String>, SnapshotError> {
+        let type_definition = match self {
+            DefinitionSnapshot::Schema {
+                query,
+                mutation,
+                subscription,
+            } => {
+                return Ok(Definition::SchemaDefinition(SchemaDefinition {
+                    position: Pos::default(),
+                    directives: vec![],
+                    query,
+                    mutation,
+                    subscription,
+                }))
+            }
+            DefinitionSnapshot::Directive {
+                name,
+                description,
+                arguments,
+                repeatable,
+                locations,
+            } => {
+                let locations = locations
+                    .into_iter()
+                    .map(|location| {
+                        location
+                            .parse()
+                            .map_err(|_| SnapshotError::UnknownDirectiveLocation {
+                                directive: name.clone(),
+                                location,
+                            })
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                return Ok(Definition::DirectiveDefinition(DirectiveDefinition {
+                    position: Pos::default(),
+                    description,
+                    name,
+                    arguments: arguments
+                        .into_iter()
+                        .map(InputValueSnapshot::into_input_value)
+                        .collect(),
+                    repeatable,
+                    locations,
+                }));
+            }
+            DefinitionSnapshot::Scalar { name, description } => {
+                TypeDefinition::Scalar(ScalarType {
+                    position: Pos::default(),
+                    description,
+                    name,
+                    directives: vec![],
+                })
+            }
+            DefinitionSnapshot::Object {
+                name,
+                description,
+                interfaces,
+                fields,
+            } => TypeDefinition::Object(ObjectType {
+                position: Pos::default(),
+                description,
+                name,
+                implements_interfaces: interfaces,
+                directives: vec![],
+                fields: fields.into_iter().map(FieldSnapshot::into_field).collect(),
+            }),
+            DefinitionSnapshot::Interface {
+                name,
+                description,
+                interfaces,
+                fields,
+            } => TypeDefinition::Interface(InterfaceType {
+                position: Pos::default(),
+                description,
+                name,
+                implements_interfaces: interfaces,
+                directives: vec![],
+                fields: fields.into_iter().map(FieldSnapshot::into_field).collect(),
+            }),
+            DefinitionSnapshot::Union {
+                name,
+                description,
+                types,
+            } => TypeDefinition::Union(UnionType {
+                position: Pos::default(),
+                description,
+                name,
+                directives: vec![],
+                types,
+            }),
+            DefinitionSnapshot::Enum {
+                name,
+                description,
+                values,
+            } => TypeDefinition::Enum(EnumType {
+                position: Pos::default(),
+                description,
+                name,
+                directives: vec![],
+                values: values
+                    .into_iter()
+                    .map(|value| EnumValue {
+                        position: Pos::default(),
+                        description: value.description,
+                        name: value.name,
+                        directives: DeprecationSnapshot::into_directives(value.deprecation),
+                    })
+                    .collect(),
+            }),
+            DefinitionSnapshot::InputObject {
+                name,
+                description,
+                fields,
+            } => TypeDefinition::InputObject(InputObjectType {
+                position: Pos::default(),
+                description,
+                name,
+                directives: vec![],
+                fields: fields
+                    .into_iter()
+                    .map(InputValueSnapshot::into_input_value)
+                    .collect(),
+            }),
+        };
+
+        Ok(Definition::TypeDefinition(type_definition))
+    }
+}
+
+impl FieldSnapshot {
+    fn into_field(self) -> Field<'static, String> {
+        Field {
+            position: Pos::default(),
+            description: self.description,
+            name: self.name,
+            arguments: self
+                .arguments
+                .into_iter()
+                .map(InputValueSnapshot::into_input_value)
+                .collect(),
+            field_type: self.field_type.into_type(),
+            directives: DeprecationSnapshot::into_directives(self.deprecation),
+        }
+    }
+}
+
+impl InputValueSnapshot {
+    fn into_input_value(self) -> InputValue<'static, String> {
+        InputValue {
+            position: Pos::default(),
+            description: self.description,
+            name: self.name,
+            value_type: self.value_type.into_type(),
+            default_value: self.default_value.map(ValueSnapshot::into_value),
+            directives: DeprecationSnapshot::into_directives(self.deprecation),
+        }
+    }
+}
+
+impl DeprecationSnapshot {
+    pub fn new<'a, T: Text<'a>>(directives: &[Directive<'a, T>]) -> Option<Self> {
+        deprecation(directives).map(|deprecation| DeprecationSnapshot {
+            reason: deprecation.reason.map(str::to_string),
+        })
+    }
+
+    fn into_directives(deprecation: Option<Self>) -> Vec<Directive<'static, String>> {
+        match deprecation {
+            Some(deprecation) => deprecation_directives(true, deprecation.reason),
+            None => vec![],
+        }
+    }
+}
+
+impl TypeSnapshot {
+    pub fn new<'a, T: Text<'a>>(value_type: &Type<'a, T>) -> Self {
+        match value_type {
+            Type::NamedType(name) => TypeSnapshot::Named(owned(name)),
+            Type::ListType(inner) => TypeSnapshot::List(Box::new(TypeSnapshot::new(inner))),
+            Type::NonNullType(inner) => TypeSnapshot::NonNull(Box::new(TypeSnapshot::new(inner))),
+        }
+    }
+
+    pub fn into_type(self) -> Type<'static, String> {
+        match self {
+            TypeSnapshot::Named(name) => Type::NamedType(name),
+            TypeSnapshot::List(inner) => Type::ListType(Box::new(inner.into_type())),
+            TypeSnapshot::NonNull(inner) => Type::NonNullType(Box::new(inner.into_type())),
+        }
+    }
+}
+
+impl ValueSnapshot {
+    pub fn new<'a, T: Text<'a>>(value: &Value<'a, T>) -> Self {
+        match value {
+            Value::Variable(name) => ValueSnapshot::Variable(owned(name)),
+            // graphql-parser only ever parses integers that fit in an i64
+            Value::Int(number) => ValueSnapshot::Int(number.as_i64().unwrap_or_default()),
+            Value::Float(float) => ValueSnapshot::Float(*float),
+            Value::String(string) => ValueSnapshot::String(string.clone()),
+            Value::Boolean(boolean) => ValueSnapshot::Boolean(*boolean),
+            Value::Null => ValueSnapshot::Null,
+            Value::Enum(name) => ValueSnapshot::Enum(owned(name)),
+            Value::List(values) => ValueSnapshot::List(values.iter().map(Self::new).collect()),
+            Value::Object(fields) => ValueSnapshot::Object(
+                fields
+                    .iter()
+                    .map(|(name, value)| (owned(name), Self::new(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn into_value(self) -> Value<'static, String> {
+        match self {
+            ValueSnapshot::Variable(name) => Value::Variable(name),
+            ValueSnapshot::Int(int) => match i32::try_from(int) {
+                Ok(int) => Value::Int(int.into()),
+                // graphql-parser can only build larger numbers by parsing them
+                Err(_) => {
+                    parse_default_value::<String, ValueError>(CowStr(Cow::Owned(int.to_string())))
+                        .unwrap_or(Value::Null)
+                }
+            },
+            ValueSnapshot::Float(float) => Value::Float(float),
+            ValueSnapshot::String(string) => Value::String(string),
+            ValueSnapshot::Boolean(boolean) => Value::Boolean(boolean),
+            ValueSnapshot::Null => Value::Null,
+            ValueSnapshot::Enum(name) => Value::Enum(name),
+            ValueSnapshot::List(values) => {
+                Value::List(values.into_iter().map(Self::into_value).collect())
+            }
+            ValueSnapshot::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| (name, value.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::to_introspection;
+    use graphql_parser::parse_schema;
+    use serde_json::json;
+
+    fn document() -> Document<'static, String> {
+        let sdl = parse_schema::<String>(
+            r#"
+            schema { query: Query }
+            "A user"
+            type Query implements Node {
+                id: ID!
+                users(first: Int = 10, order: Order = ASC): [User!]! @deprecated(reason: "Use search")
+            }
+            interface Node { id: ID! }
+            type User implements Node { id: ID! name: String }
+            union Result = Query | User
+            enum Order { ASC DESC @deprecated }
+            input Filter { name: String = "x" tags: [String!] = ["a"] ratio: Float = 0.5 }
+            directive @cached(ttl: Int) repeatable on FIELD | QUERY
+            "#,
+        )
+        .unwrap();
+        crate::parse::<String>(&json!({ "data": to_introspection(&sdl) }).to_string()).unwrap()
+    }
+
+    #[test]
+    fn round_trips_a_parsed_document() {
+        let document = document();
+        let snapshot = SchemaSnapshot::new(&document);
+        let stored: SchemaSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+        assert_eq!(stored, snapshot);
+        assert_eq!(
+            stored.into_document().unwrap().to_string(),
+            document.to_string()
+        );
+    }
+
+    #[test]
+    fn round_trips_through_binary_formats() {
+        let document = document();
+        let snapshot = SchemaSnapshot::new(&document);
+
+        let from_bincode: SchemaSnapshot =
+            bincode::deserialize(&bincode::serialize(&snapshot).unwrap()).unwrap();
+        assert_eq!(from_bincode, snapshot);
+
+        let mut cbor = vec![];
+        ciborium::ser::into_writer(&snapshot, &mut cbor).unwrap();
+        let from_cbor: SchemaSnapshot = ciborium::de::from_reader(&cbor[..]).unwrap();
+        assert_eq!(from_cbor, snapshot);
+
+        let schema = Schema::try_from(from_cbor).unwrap();
+        assert_eq!(schema.document().to_string(), document.to_string());
+    }
+
+    #[test]
+    fn rejects_unknown_directive_locations() {
+        let snapshot = SchemaSnapshot {
+            definitions: vec![DefinitionSnapshot::Directive {
+                name: "cached".to_string(),
+                description: None,
+                arguments: vec![],
+                repeatable: false,
+                locations: vec!["FIELD".to_string(), "EVERYWHERE".to_string()],
+            }],
+        };
+
+        assert_eq!(
+            snapshot.into_document(),
+            Err(SnapshotError::UnknownDirectiveLocation {
+                directive: "cached".to_string(),
+                location: "EVERYWHERE".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::schema::type_definition_name;
+use graphql_parser::schema::{Definition, Document, Field, InputValue, Text, Type, TypeDefinition};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumStyle {
+    /// `export type Role = "ADMIN" | "USER";`
+    StringUnion,
+    /// `export enum Role { ADMIN = "ADMIN", USER = "USER" }`
+    Enum,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeScriptOptions {
+    pub enum_style: EnumStyle,
+    /// TypeScript types for custom scalars, keyed by the GraphQL scalar name
+    pub scalars: HashMap<String, String>,
+    /// The TypeScript type used for any custom scalar missing from `scalars`
+    pub default_scalar: String,
+}
+
+impl Default for TypeScriptOptions {
+    fn default() -> Self {
+        TypeScriptOptions {
+            enum_style: EnumStyle::StringUnion,
+            scalars: HashMap::new(),
+            default_scalar: "unknown".to_string(),
+        }
+    }
+}
+
+/// Render every type in the schema as `.d.ts` declarations.
+///
+/// Introspection types (`__Type`, `__Schema`, ...) and the built in scalars are skipped since
+/// they map straight onto TypeScript primitives.
+pub fn generate<'a, T: Text<'a>>(
+    document: &Document<'a, T>,
+    options: &TypeScriptOptions,
+) -> String {
+    let mut out = String::new();
+
+    for definition in &document.definitions {
+        let type_definition = match definition {
+            Definition::TypeDefinition(type_definition) => type_definition,
+            _ => continue,
+        };
+        if type_definition_name(type_definition).starts_with("__") {
+            continue;
+        }
+
+        let declaration = match type_definition {
+            TypeDefinition::Scalar(scalar) => {
+                let name = scalar.name.as_ref();
+                if builtin_scalar(name).is_some() {
+                    continue;
+                }
+                let ts_type = options.scalars.get(name).unwrap_or(&options.default_scalar);
+
+                with_description(
+                    &scalar.description,
+                    "",
+                    format!("export type {} = {};\n", name, ts_type),
+                )
+            }
+            TypeDefinition::Object(object) => {
+                let name = object.name.as_ref();
+                let extends = extends_clause(&object.implements_interfaces);
+
+                let mut body = format!("export interface {}{} {{\n", name, extends);
+                writeln!(body, "  __typename?: {:?};", name).unwrap();
+                write_fields(&mut body, &object.fields);
+                body.push_str("}\n");
+                write_arguments(&mut body, name, &object.fields);
+
+                with_description(&object.description, "", body)
+            }
+            TypeDefinition::Interface(interface) => {
+                let name = interface.name.as_ref();
+                let extends = extends_clause(&interface.implements_interfaces);
+                let mut body = format!("export interface {}{} {{\n", name, extends);
+                write_fields(&mut body, &interface.fields);
+                body.push_str("}\n");
+                write_arguments(&mut body, name, &interface.fields);
+
+                with_description(&interface.description, "", body)
+            }
+            TypeDefinition::Union(union) => {
+                let members = if union.types.is_empty() {
+                    "never".to_string()
+                } else {
+                    union.types.join(" | ")
+                };
+
+                with_description(
+                    &union.description,
+                    "",
+                    format!("export type {} = {};\n", union.name.as_ref(), members),
+                )
+            }
+            TypeDefinition::Enum(enum_type) => {
+                let body = match options.enum_style {
+                    EnumStyle::StringUnion => {
+                        let values: Vec<String> = enum_type
+                            .values
+                            .iter()
+                            .map(|value| format!("{:?}", value.name.as_ref()))
+                            .collect();
+                        let values = if values.is_empty() {
+                            "never".to_string()
+                        } else {
+                            values.join(" | ")
+                        };
+
+                        format!("export type {} = {};\n", enum_type.name.as_ref(), values)
+                    }
+                    EnumStyle::Enum => {
+                        let mut body = format!("export enum {} {{\n", enum_type.name.as_ref());
+                        for value in &enum_type.values {
+                            let name = value.name.as_ref();
+                            let line = format!("  {} = {:?},\n", name, name);
+                            body.push_str(&with_description(&value.description, "  ", line));
+                        }
+                        body.push_str("}\n");
+
+                        body
+                    }
+                };
+
+                with_description(&enum_type.description, "", body)
+            }
+            TypeDefinition::InputObject(input_object) => {
+                let mut body = format!("export interface {} {{\n", input_object.name.as_ref());
+                write_input_values(&mut body, &input_object.fields);
+                body.push_str("}\n");
+
+                with_description(&input_object.description, "", body)
+            }
+        };
+
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&declaration);
+    }
+
+    out
+}
+
+/// ` extends A, B` for the interfaces a type implements, or nothing
+fn extends_clause<V: AsRef<str>>(interfaces: &[V]) -> String {
+    if interfaces.is_empty() {
+        String::new()
+    } else {
+        let names: Vec<&str> = interfaces.iter().map(AsRef::as_ref).collect();
+        format!(" extends {}", names.join(", "))
+    }
+}
+
+fn write_fields<'a, T: Text<'a>>(out: &mut String, fields: &[Field<'a, T>]) {
+    for field in fields {
+        let line = format!(
+            "  {}: {};\n",
+            field.name.as_ref(),
+            ts_type(&field.field_type, true)
+        );
+        out.push_str(&with_description(&field.description, "  ", line));
+    }
+}
+
+/// Fields with arguments get an `{Type}{Field}Args` interface so resolvers and clients can
+/// share the argument shapes
+fn write_arguments<'a, T: Text<'a>>(out: &mut String, type_name: &str, fields: &[Field<'a, T>]) {
+    for field in fields.iter().filter(|field| !field.arguments.is_empty()) {
+        let mut field_name = field.name.as_ref().to_string();
+        if let Some(first) = field_name.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+
+        writeln!(out, "\nexport interface {}{}Args {{", type_name, field_name).unwrap();
+        write_input_values(out, &field.arguments);
+        out.push_str("}\n");
+    }
+}
+
+/// Nullable inputs can be left out entirely, so they're optional as well as nullable
+fn write_input_values<'a, T: Text<'a>>(out: &mut String, values: &[InputValue<'a, T>]) {
+    for value in values {
+        let optional = match value.value_type {
+            Type::NonNullType(_) => "",
+            _ => "?",
+        };
+        let line = format!(
+            "  {}{}: {};\n",
+            value.name.as_ref(),
+            optional,
+            ts_type(&value.value_type, true)
+        );
+        out.push_str(&with_description(&value.description, "  ", line));
+    }
+}
+
+fn ts_type<'a, T: Text<'a>>(value_type: &Type<'a, T>, nullable: bool) -> String {
+    let ts_type = match value_type {
+        Type::NonNullType(inner) => return ts_type(inner, false),
+        Type::ListType(inner) => format!("Array<{}>", ts_type(inner, true)),
+        Type::NamedType(name) => builtin_scalar(name.as_ref())
+            .unwrap_or(name.as_ref())
+            .to_string(),
+    };
+
+    if nullable {
+        format!("{} | null", ts_type)
+    } else {
+        ts_type
+    }
+}
+
+fn builtin_scalar(name: &str) -> Option<&'static str> {
+    match name {
+        "String" | "ID" => Some("string"),
+        "Int" | "Float" => Some("number"),
+        "Boolean" => Some("boolean"),
+        _ => None,
+    }
+}
+
+fn with_description(description: &Option<String>, indent: &str, declaration: String) -> String {
+    match description {
+        Some(description) if !description.trim().is_empty() => {
+            let mut out = format!("{}/**\n", indent);
+            for line in description.trim().lines() {
+                let line = line.replace("*/", "*\\/");
+                if line.is_empty() {
+                    writeln!(out, "{} *", indent).unwrap();
+                } else {
+                    writeln!(out, "{} * {}", indent, line).unwrap();
+                }
+            }
+            writeln!(out, "{} */", indent).unwrap();
+            out.push_str(&declaration);
+
+            out
+        }
+        _ => declaration,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_schema;
+
+    #[test]
+    fn interfaces_extend_what_they_implement() {
+        let document = parse_schema::<String>(
+            r#"
+            interface Node { id: ID! }
+            interface Resource implements Node { id: ID! url: String }
+            type Image implements Resource & Node { id: ID! url: String width: Int }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            generate(&document, &TypeScriptOptions::default()),
+            "export interface Node {\n  id: string;\n}\n\n\
+             export interface Resource extends Node {\n  id: string;\n  url: string | null;\n}\n\n\
+             export interface Image extends Resource, Node {\n  __typename?: \"Image\";\n  id: string;\n  url: string | null;\n  width: number | null;\n}\n"
+        );
+    }
+}
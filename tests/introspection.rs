@@ -0,0 +1,70 @@
+use graphql_introspection_parser::coverage::coverage;
+use graphql_introspection_parser::introspection::to_introspection;
+use graphql_introspection_parser::mock::{mock_with_options, MockOptions};
+use graphql_introspection_parser::schema::Schema;
+use graphql_introspection_parser::{parse, parse_reader, parse_with_positions};
+use graphql_parser::parse_query;
+use serde_json::json;
+use std::fs::File;
+
+const SCHEMA: &str = include_str!("fixtures/schema.json");
+const OPERATIONS: &str = include_str!("fixtures/operations.graphql");
+
+#[test]
+fn parses_the_fixture_the_same_way_every_time() {
+    let document = parse::<String>(SCHEMA).unwrap();
+    let from_reader = parse_reader(File::open("tests/fixtures/schema.json").unwrap()).unwrap();
+    let with_positions = parse_with_positions::<String>(SCHEMA).unwrap();
+
+    assert_eq!(from_reader, document);
+    assert_eq!(with_positions.to_string(), document.to_string());
+
+    let reparsed =
+        parse::<String>(&json!({ "data": to_introspection(&document) }).to_string()).unwrap();
+    assert_eq!(reparsed, document);
+}
+
+#[test]
+fn checks_operations_against_the_fixture() {
+    let schema = Schema::new(parse::<String>(SCHEMA).unwrap());
+    let operations = parse_query::<String>(OPERATIONS).unwrap();
+
+    let report = coverage(&schema, &[("operations.graphql", &operations)]).unwrap();
+    let query = report
+        .types
+        .iter()
+        .find(|type_coverage| type_coverage.name == "Query")
+        .unwrap();
+    let covered: Vec<&str> = query
+        .members
+        .iter()
+        .filter(|member| !member.references.is_empty())
+        .map(|member| member.coordinate.as_str())
+        .collect();
+    assert_eq!(
+        covered,
+        vec![
+            "Query.node",
+            "Query.node(id:)",
+            "Query.search",
+            "Query.search(term:)",
+            "Query.users",
+            "Query.users(first:)",
+            "Query.users(after:)",
+            "Query.users(role:)",
+        ]
+    );
+
+    let response = mock_with_options(
+        &schema,
+        &operations,
+        &MockOptions {
+            operation_name: Some("Users".to_string()),
+            ..MockOptions::default()
+        },
+    )
+    .map(serde_json::Value::from)
+    .unwrap();
+    assert!(response["data"]["users"]["pageInfo"]["hasNextPage"].is_boolean());
+    assert!(response["data"]["node"]["__typename"].is_string());
+}